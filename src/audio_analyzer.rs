@@ -1,11 +1,11 @@
 // audio_analyzer.rs
 // Módulo de análisis de audio universal (versión compatible)
 
-// use rustfft::{FftPlanner, num_complex::Complex}; // Para implementación futura
-// use cpal::{Device, Host, Stream, SampleFormat, SampleRate, StreamConfig, traits::*};
-// use ringbuf::{HeapRb, Rb};
+use rustfft::{Fft, FftPlanner, num_complex::Complex};
+use cpal::Stream;
 use std::sync::mpsc::{self, Receiver, Sender};
-// use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex};
+use crate::loudness::LoudnessMeter;
 // use std::collections::VecDeque;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 
 // --- Configuración de análisis ---
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 /// Configuración detallada para el análisis de audio, tanto simulado como real.
 pub struct AudioAnalysisConfig {
     pub sample_rate: u32,
@@ -28,6 +29,8 @@ pub struct AudioAnalysisConfig {
     pub device_name: Option<String>, // Nueva: nombre del dispositivo específico
     pub verbose_logging: bool,          // Nuevo: mostrar logs detallados
     pub max_simulated_events: Option<usize>, // Nuevo: límite opcional de eventos simulados
+    pub channel_labels: Vec<String>,    // Nombre de instrumento/carril por canal de entrada
+    pub note_duration: f32,             // Duración asignada a cada ataque detectado (segundos)
 }
 
 impl Default for AudioAnalysisConfig {
@@ -46,6 +49,8 @@ impl Default for AudioAnalysisConfig {
             device_name: None,
             verbose_logging: true,
             max_simulated_events: None,
+            channel_labels: Vec::new(),
+            note_duration: 0.5,
         }
     }
 }
@@ -59,6 +64,8 @@ pub struct DetectedEvent {
     pub duration: f64,
     pub amplitude: f32,
     pub confidence: f32,
+    /// Canal de entrada del que procede el evento (`None` en simulación).
+    pub channel: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -87,11 +94,24 @@ pub enum DetectedEventType {
 }
 
 // --- Estructura para captura y análisis de audio ---
-/// Analizador de audio configurable que simula eventos acústicos o puede capturarlos en tiempo real (futuro).
+/// Analizador de audio configurable que simula eventos acústicos o los captura en tiempo real,
+/// analizando cada canal de entrada por separado. El hilo de análisis termina por sí solo al
+/// cerrarse el receptor de eventos o el flujo de captura.
 pub struct UniversalAudioAnalyzer {
     config: AudioAnalysisConfig,
-    // _audio_stream: Option<Stream>,  // Deshabilitado temporalmente
-    analysis_thread: Option<thread::JoinHandle<()>>,
+    _audio_stream: Option<Stream>,
+    channel_labels: Vec<String>,
+    loudness: Option<Arc<Mutex<LoudnessMeter>>>,
+}
+
+impl std::fmt::Debug for UniversalAudioAnalyzer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UniversalAudioAnalyzer")
+            .field("config", &self.config)
+            .field("real_audio", &self._audio_stream.is_some())
+            .field("channel_labels", &self.channel_labels)
+            .finish()
+    }
 }
 
 impl UniversalAudioAnalyzer {
@@ -99,33 +119,72 @@ impl UniversalAudioAnalyzer {
     /// Devuelve un receptor de eventos detectados.
     pub fn new(config: AudioAnalysisConfig) -> Result<(Self, Receiver<DetectedEvent>), Box<dyn std::error::Error>> {
         let (event_sender, event_receiver) = mpsc::channel();
-        
+
         if config.enable_real_audio {
-            println!("⚠️ Captura de audio real no disponible en esta versión");
-            println!("   Usando simulación avanzada de eventos...");
+            match Self::setup_real_audio(&config, event_sender.clone()) {
+                Ok((stream, channel_labels, loudness)) => {
+                    println!("🎤 Captura de audio real activada: {} canales", channel_labels.len());
+                    for (index, label) in channel_labels.iter().enumerate() {
+                        println!("   - Canal {} → {label}", index + 1);
+                    }
+                    return Ok((
+                        Self {
+                            config,
+                            _audio_stream: Some(stream),
+                            channel_labels,
+                            loudness: Some(loudness),
+                        },
+                        event_receiver,
+                    ));
+                }
+                Err(e) => {
+                    println!("⚠️ Captura de audio real no disponible: {e}");
+                    println!("   Usando simulación avanzada de eventos...");
+                }
+            }
         }
-        
-        // Por ahora, siempre usar simulación pero con análisis mejorado
-        let analysis_thread = Self::start_enhanced_simulation_thread(config.clone(), event_sender);
+
+        Self::start_enhanced_simulation_thread(config.clone(), event_sender);
         println!("🎤 Simulación avanzada de análisis de audio activada");
         println!("   - Eventos múltiples e inteligentes");
-        println!("   - Preparado para audio real en futuras versiones");
 
         Ok((
             Self {
                 config,
-                // _audio_stream: None,
-                analysis_thread: Some(analysis_thread),
+                _audio_stream: None,
+                channel_labels: Vec::new(),
+                loudness: None,
             },
             event_receiver,
         ))
     }
 
+    /// Etiquetas (instrumento/carril) de cada canal de entrada capturado.
+    /// Vacío cuando el analizador funciona en modo simulación.
+    pub fn channel_labels(&self) -> &[String] {
+        &self.channel_labels
+    }
+
+    /// Medidor de sonoridad alimentado con la entrada capturada (solo con audio real).
+    pub fn loudness(&self) -> Option<&Arc<Mutex<LoudnessMeter>>> {
+        self.loudness.as_ref()
+    }
+
+    /// Devuelve la etiqueta configurada para un canal, o `inN` si no hay ninguna.
+    pub fn label_for_channel(config: &AudioAnalysisConfig, channel: usize) -> String {
+        config
+            .channel_labels
+            .get(channel)
+            .filter(|label| !label.is_empty())
+            .cloned()
+            .unwrap_or_else(|| format!("in{}", channel + 1))
+    }
+
     /// Lanza un hilo que simula eventos acústicos variados con patrones temporales y espectrales.
     fn start_enhanced_simulation_thread(
         _config: AudioAnalysisConfig,
         event_sender: Sender<DetectedEvent>,
-    ) -> thread::JoinHandle<()> {
+    ) {
         thread::spawn(move || {
             let mut last_event = Instant::now();
             let mut event_counter = 0;
@@ -255,18 +314,225 @@ impl UniversalAudioAnalyzer {
 
                 thread::sleep(Duration::from_millis(50));
             }
-        })
+        });
     }
 
-    /* // Código para audio real (para implementación futura cuando se resuelvan dependencias)
+    /// Abre el dispositivo configurado y lanza un hilo que desentrelaza los bloques
+    /// recibidos, analiza cada canal con su propio `ChannelAnalyzer` y alimenta
+    /// el medidor de sonoridad con la mezcla de todos los canales.
+    #[allow(clippy::type_complexity)]
     fn setup_real_audio(
         config: &AudioAnalysisConfig,
         event_sender: Sender<DetectedEvent>,
-    ) -> Result<(Stream, thread::JoinHandle<()>), Box<dyn std::error::Error>> {
-        // TODO: Implementar cuando se resuelvan problemas de compatibilidad
-        Err("Audio real no disponible en esta versión".into())
+    ) -> Result<(Stream, Vec<String>, Arc<Mutex<LoudnessMeter>>), Box<dyn std::error::Error>> {
+        let (block_sender, block_receiver) = mpsc::channel::<Vec<f32>>();
+        let (stream, info) = crate::capture::start_multichannel_capture(config.device_name.as_deref(), block_sender)?;
+        println!("🎙️ Dispositivo de entrada: {} ({} Hz)", info.device_name, info.sample_rate);
+
+        let channels = info.channels.max(1);
+        let channel_labels: Vec<String> = (0..channels).map(|ch| Self::label_for_channel(config, ch)).collect();
+
+        let loudness = Arc::new(Mutex::new(LoudnessMeter::new(info.sample_rate, channels, 60.0)));
+        let thread_loudness = loudness.clone();
+
+        let thread_config = config.clone();
+        thread::spawn(move || {
+            let fft = FftPlanner::<f32>::new().plan_fft_forward(thread_config.fft_size);
+            let mut analyzers: Vec<ChannelAnalyzer> = (0..channels)
+                .map(|ch| ChannelAnalyzer::new(ch, info.sample_rate, &thread_config, fft.clone()))
+                .collect();
+            let mut channel_buffers: Vec<Vec<f32>> = vec![Vec::new(); channels];
+
+            for block in block_receiver.iter() {
+                if let Ok(mut meter) = thread_loudness.lock() {
+                    meter.process_interleaved(&block);
+                }
+                deinterleave(&block, &mut channel_buffers);
+                for (analyzer, samples) in analyzers.iter_mut().zip(&channel_buffers) {
+                    for event in analyzer.process(samples) {
+                        if event_sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok((stream, channel_labels, loudness))
+    }
+}
+
+/// Reparte un bloque entrelazado (`L R L R ...`) entre los búferes de cada canal,
+/// vaciándolos antes. Un último fotograma incompleto solo llena los canales que trae.
+fn deinterleave(block: &[f32], channel_buffers: &mut [Vec<f32>]) {
+    let channels = channel_buffers.len().max(1);
+    for buffer in channel_buffers.iter_mut() {
+        buffer.clear();
+    }
+    for frame in block.chunks(channels) {
+        for (buffer, &sample) in channel_buffers.iter_mut().zip(frame) {
+            buffer.push(sample);
+        }
+    }
+}
+
+/// Análisis independiente de un canal de entrada: nivel RMS, detección de ataques
+/// y estimación de altura por pico espectral con confianza armónica.
+struct ChannelAnalyzer {
+    channel: usize,
+    sample_rate: u32,
+    fft_size: usize,
+    hop_size: usize,
+    onset_threshold: f32,
+    pitch_confidence: f32,
+    noise_gate_db: f32,
+    min_onset_interval: f64,
+    note_duration: f64,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    buffer: Vec<f32>,
+    prev_rms: f32,
+    gate_open: bool,
+    last_onset: Option<Instant>,
+}
+
+impl ChannelAnalyzer {
+    fn new(channel: usize, sample_rate: u32, config: &AudioAnalysisConfig, fft: Arc<dyn Fft<f32>>) -> Self {
+        let fft_size = config.fft_size.max(64);
+        // Ventana de Hann para reducir la fuga espectral
+        let window = (0..fft_size)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / fft_size as f32).cos())
+            .collect();
+        Self {
+            channel,
+            sample_rate,
+            fft_size,
+            hop_size: config.hop_size.clamp(1, fft_size),
+            onset_threshold: config.onset_threshold,
+            pitch_confidence: config.pitch_confidence,
+            noise_gate_db: config.noise_gate,
+            min_onset_interval: 1.0 / config.max_events_per_second.max(1) as f64,
+            note_duration: config.note_duration as f64,
+            fft,
+            window,
+            buffer: Vec::with_capacity(fft_size * 2),
+            prev_rms: 0.0,
+            gate_open: false,
+            last_onset: None,
+        }
+    }
+
+    /// Acumula muestras y analiza cada salto (`hop_size`) completo.
+    fn process(&mut self, samples: &[f32]) -> Vec<DetectedEvent> {
+        self.buffer.extend_from_slice(samples);
+        let mut events = Vec::new();
+        while self.buffer.len() >= self.fft_size {
+            if let Some(event) = self.analyze_frame() {
+                events.push(event);
+            }
+            self.buffer.drain(..self.hop_size);
+        }
+        events
+    }
+
+    fn analyze_frame(&mut self) -> Option<DetectedEvent> {
+        let frame = &self.buffer[..self.fft_size];
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        let level_db = 20.0 * rms.max(1e-9).log10();
+        let prev_rms = self.prev_rms;
+        self.prev_rms = rms;
+
+        if level_db < self.noise_gate_db {
+            self.gate_open = false;
+            return None;
+        }
+
+        // Ataque: apertura de la puerta de ruido o subida relativa de energía
+        let rise = (rms - prev_rms) / prev_rms.max(1e-6);
+        let is_onset = !self.gate_open || rise > self.onset_threshold;
+        self.gate_open = true;
+        if !is_onset {
+            return None;
+        }
+        if self.last_onset.is_some_and(|t| t.elapsed().as_secs_f64() < self.min_onset_interval) {
+            return None;
+        }
+        self.last_onset = Some(Instant::now());
+
+        let (frequency, confidence, centroid, spread) = self.spectral_features();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        let event_type = if confidence >= self.pitch_confidence {
+            DetectedEventType::Onset { frequency, sharpness: rise.clamp(0.0, 1.0) }
+        } else {
+            DetectedEventType::NoiseTexture {
+                freq_center: centroid,
+                bandwidth: spread,
+                roughness: 1.0 - confidence,
+            }
+        };
+
+        Some(DetectedEvent {
+            event_type,
+            timestamp,
+            duration: self.note_duration,
+            amplitude: rms.min(1.0),
+            confidence,
+            channel: Some(self.channel),
+        })
+    }
+
+    /// Devuelve (frecuencia fundamental estimada, confianza, centroide, dispersión espectral).
+    fn spectral_features(&self) -> (f32, f32, f32, f32) {
+        let mut spectrum: Vec<Complex<f32>> = self.buffer[..self.fft_size]
+            .iter()
+            .zip(&self.window)
+            .map(|(s, w)| Complex::new(s * w, 0.0))
+            .collect();
+        self.fft.process(&mut spectrum);
+
+        let half = self.fft_size / 2;
+        let magnitudes: Vec<f32> = spectrum[..half].iter().map(|c| c.norm()).collect();
+        let bin_hz = self.sample_rate as f32 / self.fft_size as f32;
+        let total: f32 = magnitudes.iter().sum::<f32>().max(1e-9);
+
+        let centroid = magnitudes.iter().enumerate().map(|(i, m)| i as f32 * bin_hz * m).sum::<f32>() / total;
+        let spread = (magnitudes
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (i as f32 * bin_hz - centroid).powi(2) * m)
+            .sum::<f32>()
+            / total)
+            .sqrt();
+
+        let (peak, _) = magnitudes
+            .iter()
+            .enumerate()
+            .skip(1)
+            .fold((1, 0.0f32), |best, (i, &m)| if m > best.1 { (i, m) } else { best });
+
+        // Interpolación parabólica alrededor del pico
+        let delta = if peak + 1 < half {
+            let (a, b, c) = (magnitudes[peak - 1], magnitudes[peak], magnitudes[peak + 1]);
+            let denom = a - 2.0 * b + c;
+            if denom.abs() > 1e-9 { 0.5 * (a - c) / denom } else { 0.0 }
+        } else {
+            0.0
+        };
+        let frequency = (peak as f32 + delta) * bin_hz;
+
+        // Confianza: proporción de energía concentrada en los primeros armónicos del pico
+        let harmonic_energy: f32 = (1..=8)
+            .map(|h| ((peak as f32 + delta) * h as f32).round() as usize)
+            .take_while(|&center| center + 2 < half)
+            .map(|center| magnitudes[center.saturating_sub(2)..=center + 2].iter().sum::<f32>())
+            .sum();
+
+        (frequency, (harmonic_energy / total).clamp(0.0, 1.0), centroid, spread)
     }
-    */
 }
 
 impl UniversalAudioAnalyzer {
@@ -277,6 +543,7 @@ impl UniversalAudioAnalyzer {
             duration,
             amplitude,
             confidence,
+            channel: None,
         }
     }
 
@@ -287,6 +554,7 @@ impl UniversalAudioAnalyzer {
             duration,
             amplitude,
             confidence,
+            channel: None,
         }
     }
 
@@ -297,6 +565,7 @@ impl UniversalAudioAnalyzer {
             duration,
             amplitude,
             confidence,
+            channel: None,
         }
     }
 
@@ -307,6 +576,7 @@ impl UniversalAudioAnalyzer {
             duration,
             amplitude,
             confidence,
+            channel: None,
         }
     }
 
@@ -316,6 +586,94 @@ impl UniversalAudioAnalyzer {
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs_f64();
-        Self::generate_onset(440.0, 0.7, timestamp, 0.8, 0.9, 0.5)
+        Self::generate_onset(440.0, 0.7, timestamp, 0.8, 0.9, config.note_duration as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn sine(freq: f32, amplitude: f32, samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|n| amplitude * (2.0 * std::f32::consts::PI * freq * n as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn analyzer() -> ChannelAnalyzer {
+        // Sin límite práctico de ataques por segundo (la prueba corre mucho más rápido que el audio),
+        // así que el umbral sube por encima de lo que crece el RMS mientras la ventana entra en la nota.
+        let config = AudioAnalysisConfig {
+            max_events_per_second: 1_000_000,
+            onset_threshold: 0.5,
+            ..AudioAnalysisConfig::default()
+        };
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(config.fft_size);
+        ChannelAnalyzer::new(2, SAMPLE_RATE, &config, fft)
+    }
+
+    #[test]
+    fn test_peak_interpolation_finds_sine_between_bins() {
+        let mut analyzer = analyzer();
+        // 443 Hz cae entre dos bins (23.4 Hz cada uno con fft_size = 2048)
+        analyzer.buffer = sine(443.0, 0.5, analyzer.fft_size);
+        let (frequency, confidence, centroid, _) = analyzer.spectral_features();
+        assert!((frequency - 443.0).abs() < 2.0, "frecuencia {frequency}");
+        assert!(confidence >= 0.8, "confianza {confidence}");
+        assert!((centroid - 443.0).abs() < 50.0, "centroide {centroid}");
+    }
+
+    #[test]
+    fn test_onset_on_gate_open_and_not_while_sustained() {
+        let mut analyzer = analyzer();
+        let block = analyzer.fft_size;
+        let events = analyzer.process(&sine(330.0, 0.5, block * 3));
+        assert_eq!(events.len(), 1, "un solo ataque al abrirse la puerta");
+        let event = &events[0];
+        assert_eq!(event.channel, Some(2));
+        match event.event_type {
+            DetectedEventType::Onset { frequency, .. } => assert!((frequency - 330.0).abs() < 2.0, "frecuencia {frequency}"),
+            ref other => panic!("se esperaba un ataque: {other:?}"),
+        }
+
+        // El silencio cierra la puerta y la nota siguiente vuelve a detectarse una sola vez
+        assert!(analyzer.process(&vec![0.0; block * 2]).is_empty(), "el silencio no dispara ataques");
+        assert_eq!(analyzer.process(&sine(660.0, 0.5, block * 2)).len(), 1);
+    }
+
+    #[test]
+    fn test_noise_is_reported_as_texture() {
+        let mut analyzer = analyzer();
+        // Ruido blanco determinista (generador congruencial)
+        let mut state: u32 = 12345;
+        let noise: Vec<f32> = (0..analyzer.fft_size)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect();
+        let events = analyzer.process(&noise);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0].event_type, DetectedEventType::NoiseTexture { .. }), "{:?}", events[0].event_type);
+    }
+
+    #[test]
+    fn test_deinterleave_splits_channels() {
+        let mut buffers = vec![vec![9.0]; 3];
+        deinterleave(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0], &mut buffers);
+        assert_eq!(buffers, vec![vec![1.0, 4.0, 7.0], vec![2.0, 5.0], vec![3.0, 6.0]]);
+    }
+
+    #[test]
+    fn test_label_for_channel_falls_back_to_input_number() {
+        let config = AudioAnalysisConfig {
+            channel_labels: vec!["violin".to_string(), String::new()],
+            ..AudioAnalysisConfig::default()
+        };
+        assert_eq!(UniversalAudioAnalyzer::label_for_channel(&config, 0), "violin");
+        assert_eq!(UniversalAudioAnalyzer::label_for_channel(&config, 1), "in2");
+        assert_eq!(UniversalAudioAnalyzer::label_for_channel(&config, 2), "in3");
     }
 }
@@ -1,27 +1,81 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{SampleFormat, Stream};
+use cpal::{FromSample, Sample, SampleFormat, Stream};
+use std::fmt;
 use std::sync::mpsc::Sender;
-use thiserror::Error;
-use num_traits::ToPrimitive;
 
-#[derive(Error, Debug)]
+#[derive(Debug)]
 pub enum AudioCaptureError {
-    #[error("No se encontró dispositivo de entrada de audio")]
     NoInputDevice,
-    #[error("Error de configuración de entrada de audio: {0}")]
+    DeviceNotFound { name: String, available: Vec<String> },
     ConfigError(String),
-    #[error("Error al iniciar el stream de entrada: {0}")]
     StreamBuildError(String),
-    #[error("Error al reproducir stream: {0}")]
     StreamPlayError(String),
 }
 
+impl fmt::Display for AudioCaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AudioCaptureError::NoInputDevice => write!(f, "No se encontró dispositivo de entrada de audio"),
+            AudioCaptureError::DeviceNotFound { name, available } => {
+                write!(f, "Dispositivo de entrada '{name}' no encontrado. Disponibles: {}", available.join(", "))
+            }
+            AudioCaptureError::ConfigError(e) => write!(f, "Error de configuración de entrada de audio: {e}"),
+            AudioCaptureError::StreamBuildError(e) => write!(f, "Error al iniciar el stream de entrada: {e}"),
+            AudioCaptureError::StreamPlayError(e) => write!(f, "Error al reproducir stream: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioCaptureError {}
+
+/// Información del dispositivo de entrada abierto.
+#[derive(Debug, Clone)]
+pub struct CaptureInfo {
+    pub device_name: String,
+    pub channels: usize,
+    pub sample_rate: u32,
+}
+
+/// Devuelve los nombres de todos los dispositivos de entrada del host por defecto.
+pub fn list_input_devices() -> Vec<String> {
+    let host = cpal::default_host();
+    match host.input_devices() {
+        Ok(devices) => devices.filter_map(|d| d.name().ok()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Busca un dispositivo de entrada por nombre (coincidencia exacta o, en su defecto, parcial
+/// sin distinguir mayúsculas). Sin nombre, devuelve el dispositivo por defecto.
+fn find_input_device(host: &cpal::Host, device_name: Option<&str>) -> Result<cpal::Device, AudioCaptureError> {
+    let Some(wanted) = device_name else {
+        return host.default_input_device().ok_or(AudioCaptureError::NoInputDevice);
+    };
+
+    let devices: Vec<cpal::Device> = host
+        .input_devices()
+        .map_err(|e| AudioCaptureError::ConfigError(e.to_string()))?
+        .collect();
+
+    let wanted_lower = wanted.to_lowercase();
+    let exact = devices.iter().position(|d| d.name().is_ok_and(|n| n == wanted));
+    let partial = || devices.iter().position(|d| d.name().is_ok_and(|n| n.to_lowercase().contains(&wanted_lower)));
+
+    match exact.or_else(partial) {
+        Some(index) => Ok(devices.into_iter().nth(index).expect("índice de dispositivo válido")),
+        None => Err(AudioCaptureError::DeviceNotFound {
+            name: wanted.to_string(),
+            available: devices.iter().filter_map(|d| d.name().ok()).collect(),
+        }),
+    }
+}
+
 /// Inicia la captura de audio desde el micrófono.
 /// Envía muestras de tipo `f32` por el canal proporcionado.
 /// ⚠️ Importante: el `Stream` devuelto debe mantenerse vivo mientras quieras recibir datos del micrófono.
 pub fn start_audio_capture(event_sender: Sender<f32>) -> Result<Stream, AudioCaptureError> {
     let host = cpal::default_host();
-    let device = host.default_input_device().ok_or(AudioCaptureError::NoInputDevice)?;
+    let device = find_input_device(&host, None)?;
     let config = device
         .default_input_config()
         .map_err(|e| AudioCaptureError::ConfigError(e.to_string()))?;
@@ -46,6 +100,45 @@ pub fn start_audio_capture(event_sender: Sender<f32>) -> Result<Stream, AudioCap
     Ok(stream)
 }
 
+/// Inicia la captura multicanal desde el dispositivo indicado (o el de por defecto).
+/// Cada bloque enviado contiene las muestras entrelazadas tal como las entrega el driver;
+/// el número de canales para desentrelazarlas se devuelve en `CaptureInfo`.
+/// ⚠️ Igual que con `start_audio_capture`, el `Stream` debe mantenerse vivo.
+pub fn start_multichannel_capture(
+    device_name: Option<&str>,
+    block_sender: Sender<Vec<f32>>,
+) -> Result<(Stream, CaptureInfo), AudioCaptureError> {
+    let host = cpal::default_host();
+    let device = find_input_device(&host, device_name)?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| AudioCaptureError::ConfigError(e.to_string()))?;
+
+    let info = CaptureInfo {
+        device_name: device.name().unwrap_or_else(|_| "desconocido".to_string()),
+        channels: supported.channels() as usize,
+        sample_rate: supported.sample_rate().0,
+    };
+
+    let sample_format = supported.sample_format();
+    let config = supported.into();
+
+    let stream_result = match sample_format {
+        SampleFormat::F32 => build_block_stream::<f32>(&device, &config, block_sender),
+        SampleFormat::I16 => build_block_stream::<i16>(&device, &config, block_sender),
+        SampleFormat::U16 => build_block_stream::<u16>(&device, &config, block_sender),
+        _ => {
+            return Err(AudioCaptureError::ConfigError(format!("Formato de muestra no soportado: {sample_format:?}")));
+        }
+    };
+
+    let stream = stream_result.map_err(|e| AudioCaptureError::StreamBuildError(e.to_string()))?;
+    stream
+        .play()
+        .map_err(|e| AudioCaptureError::StreamPlayError(e.to_string()))?;
+    Ok((stream, info))
+}
+
 // Función genérica para capturar cualquier formato de muestra
 fn build_stream<T>(
     device: &cpal::Device,
@@ -53,13 +146,14 @@ fn build_stream<T>(
     sender: Sender<f32>,
 ) -> Result<Stream, cpal::BuildStreamError>
 where
-    T: cpal::Sample + cpal::SizedSample + cpal::FromSample<f32> + dasp_sample::ToSample<f32> + ToPrimitive,
+    T: cpal::SizedSample,
+    f32: FromSample<T>,
 {
     device.build_input_stream(
         config,
         move |data: &[T], _| {
             for &sample in data {
-                let val = sample.to_f32();
+                let val = f32::from_sample(sample);
                 if sender.send(val).is_err() {
                     break;
                 }
@@ -71,3 +165,26 @@ where
         None,
     )
 }
+
+// Variante que envía bloques completos entrelazados en lugar de muestra a muestra
+fn build_block_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sender: Sender<Vec<f32>>,
+) -> Result<Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample,
+    f32: FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |data: &[T], _| {
+            let block: Vec<f32> = data.iter().map(|&s| f32::from_sample(s)).collect();
+            let _ = sender.send(block);
+        },
+        move |err| {
+            eprintln!("⚠️ Error en el stream de audio: {}", err);
+        },
+        None,
+    )
+}
@@ -0,0 +1,149 @@
+// src/clock.rs
+
+//! 🕰️ Reloj inyectable
+//!
+//! El modelo, el servidor OSC, la sincronización y los renderizadores obtienen la hora de un
+//! `Clock` compartido en lugar de llamar a `Instant::now()`:
+//!
+//! - `RealTimeClock`: hora del sistema (uso normal en directo).
+//! - `ManualClock`: avanza solo cuando se le indica (pruebas de disposición y de lógica).
+//! - `OfflineClock`: avanza un paso fijo por frame (render offline con precisión de frame).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Instante actual según este reloj.
+    fn now(&self) -> Instant;
+}
+
+/// Reloj compartido entre el modelo y los hilos (p. ej. el servidor OSC).
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Reloj en tiempo real por defecto.
+pub fn real_time() -> SharedClock {
+    Arc::new(RealTimeClock)
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealTimeClock;
+
+impl Clock for RealTimeClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Reloj que solo avanza con `advance` o `set_elapsed`.
+#[derive(Debug)]
+pub struct ManualClock {
+    origin: Instant,
+    elapsed_nanos: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self { origin: Instant::now(), elapsed_nanos: AtomicU64::new(0) }
+    }
+
+    pub fn origin(&self) -> Instant {
+        self.origin
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.elapsed_nanos.fetch_add(by.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    pub fn set_elapsed(&self, elapsed: Duration) {
+        self.elapsed_nanos.store(elapsed.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_nanos.load(Ordering::SeqCst))
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+}
+
+/// Reloj de render offline: el frame `n` ocurre exactamente en `origen + n / fps`.
+#[derive(Debug)]
+pub struct OfflineClock {
+    origin: Instant,
+    fps: f64,
+    frame: AtomicU64,
+}
+
+impl OfflineClock {
+    pub fn new(fps: f64) -> Self {
+        Self { origin: Instant::now(), fps: fps.max(1.0), frame: AtomicU64::new(0) }
+    }
+
+    pub fn origin(&self) -> Instant {
+        self.origin
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame.load(Ordering::SeqCst)
+    }
+
+    /// Pasa al frame siguiente y devuelve su número.
+    pub fn advance_frame(&self) -> u64 {
+        self.frame.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn set_frame(&self, frame: u64) {
+        self.frame.store(frame, Ordering::SeqCst);
+    }
+
+    /// Tiempo del frame actual desde el origen.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.frame() as f64 / self.fps)
+    }
+}
+
+impl Clock for OfflineClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_only_moves_when_told() {
+        let clock = ManualClock::new();
+        let t0 = clock.now();
+        assert_eq!(clock.now(), t0);
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(clock.now() - t0, Duration::from_millis(250));
+        clock.set_elapsed(Duration::from_secs(2));
+        assert_eq!(clock.now() - clock.origin(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_offline_clock_is_frame_accurate() {
+        let clock = OfflineClock::new(60.0);
+        for _ in 0..600 {
+            clock.advance_frame();
+        }
+        // 600 frames a 60 fps son exactamente 10 s, sin deriva acumulada
+        assert_eq!(clock.now() - clock.origin(), Duration::from_secs(10));
+    }
+}
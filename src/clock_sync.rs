@@ -0,0 +1,295 @@
+// src/clock_sync.rs
+
+//! ⏱️ Sincronización de reloj con SuperCollider (`/viz/sync`)
+//!
+//! Protocolo de ida y vuelta al estilo NTP:
+//!
+//! 1. El visualizador envía `/viz/sync id t1` a sclang (`t1`: su reloj local, segundos).
+//! 2. sclang responde `/viz/sync/reply id t2 t3`, donde `t2` es su reloj al recibir
+//!    y `t3` su reloj al responder (p. ej. `Main.elapsedTime`).
+//! 3. Al recibir la respuesta en `t4`:
+//!    - desfase  = ((t2 - t1) + (t3 - t4)) / 2   (reloj de sclang − reloj local)
+//!    - retardo  = (t4 - t1) - (t3 - t2)         (ida y vuelta por la red)
+//!
+//! Se conservan las últimas muestras y se estima el desfase con la mediana de las de
+//! menor retardo, que son las menos afectadas por colas en la red.
+//!
+//! Con la estimación, la marca de tiempo de los bundles OSC se traslada al reloj local
+//! (`remote_to_instant`) y los eventos se dibujan cuando suenan, no cuando llegan.
+//! Las marcas de los bundles son segundos NTP (desde 1900), así que solo se trasladan con
+//! una estimación medida en esa misma escala: la respuesta se envía también en un bundle y
+//! su marca de tiempo hace de `t2` y `t3`:
+//! ```text
+//! OSCdef(\vizSync, { |msg, time, addr|
+//!     addr.sendBundle(0, ["/viz/sync/reply", msg[1]]);
+//! }, "/viz/sync");
+//! ```
+//! La respuesta con mensajes sueltos,
+//! `addr.sendMsg("/viz/sync/reply", msg[1], Main.elapsedTime, Main.elapsedTime)`, mide el
+//! desfase en la escala de `Main.elapsedTime`: sirve para el desfase y la latencia, pero con
+//! ella los bundles se dibujan al llegar.
+
+use nannou_osc as osc;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use crate::config::SyncConfig;
+use crate::logging::Logger;
+
+/// Escala del reloj de sclang en la que llegó la respuesta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockDomain {
+    /// Marca de tiempo de un bundle (segundos NTP, la misma escala que los eventos en bundles).
+    Bundle,
+    /// Tiempos en los argumentos de un mensaje suelto (p. ej. `Main.elapsedTime`).
+    Message,
+}
+
+/// Una medida de ida y vuelta.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncSample {
+    pub offset: f64,
+    pub round_trip: f64,
+}
+
+/// Estimación actual del desfase de reloj y de la latencia de red.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncEstimate {
+    /// Reloj de sclang menos reloj local (segundos).
+    pub offset: f64,
+    /// Latencia de un sentido (mitad del retardo de ida y vuelta mediano, segundos).
+    pub latency: f64,
+    /// Dispersión del desfase entre las muestras filtradas (segundos).
+    pub jitter: f64,
+    pub samples: usize,
+    pub domain: ClockDomain,
+}
+
+/// Envía sondas `/viz/sync` periódicamente y mantiene la estimación del desfase.
+pub struct ClockSync {
+    config: SyncConfig,
+    sender: Option<osc::Sender>,
+    pending: HashMap<i32, f64>,
+    samples: VecDeque<SyncSample>,
+    domain: ClockDomain,
+    next_id: i32,
+    last_probe: Option<f64>,
+    estimate: Option<SyncEstimate>,
+}
+
+impl std::fmt::Debug for ClockSync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClockSync")
+            .field("config", &self.config)
+            .field("pending", &self.pending.len())
+            .field("samples", &self.samples.len())
+            .field("estimate", &self.estimate)
+            .finish()
+    }
+}
+
+impl ClockSync {
+    /// Crea el sincronizador. El socket de envío solo se abre si la sincronización está activada.
+    pub fn new(config: SyncConfig) -> Self {
+        let sender = if config.enabled {
+            match osc::sender() {
+                Ok(sender) => Some(sender),
+                Err(e) => {
+                    Logger::log_error(&format!("❌ No se pudo crear el emisor OSC para /viz/sync: {e}"));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        Self {
+            config,
+            sender,
+            pending: HashMap::new(),
+            samples: VecDeque::new(),
+            domain: ClockDomain::Message,
+            next_id: 0,
+            last_probe: None,
+            estimate: None,
+        }
+    }
+
+    /// Envía una sonda si ha pasado `interval_secs` desde la anterior. `now`: reloj local.
+    pub fn tick(&mut self, now: f64) {
+        if self.sender.is_none() {
+            return;
+        }
+        if self.last_probe.is_some_and(|last| now - last < self.config.interval_secs as f64) {
+            return;
+        }
+        let id = self.start_probe(now);
+        let target = format!("{}:{}", self.config.target_host, self.config.target_port);
+        let message = osc::Message {
+            addr: "/viz/sync".to_string(),
+            args: vec![osc::Type::Int(id), osc::Type::Double(now)],
+        };
+        if let Some(sender) = &self.sender {
+            if let Err(e) = sender.send(message, target.as_str()) {
+                Logger::log_warn(&format!("⚠️ Error al enviar /viz/sync a {target}: {e}"));
+            }
+        }
+    }
+
+    /// Registra una sonda enviada en `t1` y devuelve su identificador.
+    pub fn start_probe(&mut self, t1: f64) -> i32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.last_probe = Some(t1);
+        self.pending.insert(id, t1);
+        // Las sondas sin respuesta no se acumulan indefinidamente
+        let max_pending = self.config.max_samples.max(1) * 2;
+        if self.pending.len() > max_pending {
+            let oldest = self.pending.iter().min_by(|a, b| a.1.total_cmp(b.1)).map(|(id, _)| *id);
+            if let Some(oldest) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+        id
+    }
+
+    /// Procesa una respuesta `/viz/sync/reply id t2 t3` recibida en `t4` (reloj local), con
+    /// `t2` y `t3` en la escala `domain`. Las muestras de otra escala se descartan.
+    /// Devuelve la muestra si la respuesta corresponde a una sonda pendiente.
+    pub fn handle_reply(&mut self, id: i32, t2: f64, t3: f64, t4: f64, domain: ClockDomain) -> Option<SyncSample> {
+        let t1 = self.pending.remove(&id)?;
+        if domain != self.domain {
+            self.samples.clear();
+            self.domain = domain;
+        }
+        let round_trip = ((t4 - t1) - (t3 - t2)).max(0.0);
+        let sample = SyncSample { offset: ((t2 - t1) + (t3 - t4)) / 2.0, round_trip };
+        self.samples.push_back(sample);
+        while self.samples.len() > self.config.max_samples.max(1) {
+            self.samples.pop_front();
+        }
+        self.estimate = Self::estimate_from(&self.samples, domain);
+        Some(sample)
+    }
+
+    /// Mediana del desfase entre la mitad de las muestras con menor retardo.
+    fn estimate_from(samples: &VecDeque<SyncSample>, domain: ClockDomain) -> Option<SyncEstimate> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut best: Vec<SyncSample> = samples.iter().copied().collect();
+        best.sort_by(|a, b| a.round_trip.total_cmp(&b.round_trip));
+        best.truncate(best.len().div_ceil(2));
+
+        let mut offsets: Vec<f64> = best.iter().map(|s| s.offset).collect();
+        offsets.sort_by(f64::total_cmp);
+        let offset = median(&offsets);
+        let mut deviations: Vec<f64> = offsets.iter().map(|o| (o - offset).abs()).collect();
+        deviations.sort_by(f64::total_cmp);
+        let mut round_trips: Vec<f64> = samples.iter().map(|s| s.round_trip).collect();
+        round_trips.sort_by(f64::total_cmp);
+
+        Some(SyncEstimate {
+            offset,
+            latency: median(&round_trips) / 2.0,
+            jitter: median(&deviations),
+            samples: samples.len(),
+            domain,
+        })
+    }
+
+    pub fn estimate(&self) -> Option<SyncEstimate> {
+        self.estimate
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Convierte un instante del reloj de sclang al reloj local.
+    pub fn remote_to_local(&self, remote: f64) -> f64 {
+        remote - self.estimate.map_or(0.0, |e| e.offset)
+    }
+
+    /// Instante local de la marca de tiempo de un bundle, con `origin` como inicio de la sesión.
+    /// `None` sin una estimación medida con bundles: las escalas no serían comparables.
+    pub fn remote_to_instant(&self, remote: f64, origin: Instant) -> Option<Instant> {
+        if self.estimate?.domain != ClockDomain::Bundle {
+            return None;
+        }
+        Some(origin + Duration::from_secs_f64(self.remote_to_local(remote).max(0.0)))
+    }
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n == 0 {
+        0.0
+    } else if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_and_latency_from_round_trip() {
+        let mut sync = ClockSync::new(SyncConfig::default());
+        // Reloj remoto adelantado 100 s, 10 ms por sentido, 1 ms de proceso en sclang
+        let t1 = 5.0;
+        let id = sync.start_probe(t1);
+        let t2 = t1 + 0.010 + 100.0;
+        let t3 = t2 + 0.001;
+        let t4 = t1 + 0.021;
+        let sample = sync.handle_reply(id, t2, t3, t4, ClockDomain::Message).unwrap();
+        assert!((sample.offset - 100.0).abs() < 1e-9);
+        assert!((sample.round_trip - 0.020).abs() < 1e-9);
+        let estimate = sync.estimate().unwrap();
+        assert!((estimate.latency - 0.010).abs() < 1e-9);
+        assert!((sync.remote_to_local(t2) - (t1 + 0.010)).abs() < 1e-9);
+        // Una respuesta repetida o desconocida se ignora
+        assert!(sync.handle_reply(id, t2, t3, t4, ClockDomain::Message).is_none());
+    }
+
+    #[test]
+    fn test_median_filter_rejects_congested_samples() {
+        let mut sync = ClockSync::new(SyncConfig::default());
+        for i in 0..8 {
+            let t1 = i as f64;
+            let id = sync.start_probe(t1);
+            // Una de cada cuatro muestras sufre un retardo asimétrico de 200 ms en la vuelta
+            let back = if i % 4 == 0 { 0.2 } else { 0.005 };
+            let t2 = t1 + 0.005 + 2.0;
+            sync.handle_reply(id, t2, t2, t1 + 0.005 + back, ClockDomain::Message);
+        }
+        let estimate = sync.estimate().unwrap();
+        assert!((estimate.offset - 2.0).abs() < 1e-6);
+        assert_eq!(estimate.samples, 8);
+    }
+
+    #[test]
+    fn test_bundle_time_follows_offset() {
+        let origin = Instant::now();
+        let mut sync = ClockSync::new(SyncConfig { max_samples: 1, ..SyncConfig::default() });
+        // Un evento marcado en el segundo 3605 de sclang
+        assert!(sync.remote_to_instant(3605.0, origin).is_none());
+
+        let id = sync.start_probe(1.0);
+        sync.handle_reply(id, 3601.0, 3601.0, 1.0, ClockDomain::Bundle);
+        let at = sync.remote_to_instant(3605.0, origin).unwrap();
+        assert!((at.duration_since(origin).as_secs_f64() - 5.0).abs() < 1e-6);
+
+        // Un nuevo desfase desplaza el mismo evento
+        let id = sync.start_probe(2.0);
+        sync.handle_reply(id, 3601.5, 3601.5, 2.0, ClockDomain::Bundle);
+        let moved = sync.remote_to_instant(3605.0, origin).unwrap();
+        assert!((moved.duration_since(origin).as_secs_f64() - 5.5).abs() < 1e-6);
+
+        // Una estimación en la escala de `Main.elapsedTime` no sirve para los bundles
+        let id = sync.start_probe(3.0);
+        sync.handle_reply(id, 50.0, 50.0, 3.0, ClockDomain::Message);
+        assert!(sync.remote_to_instant(3605.0, origin).is_none());
+    }
+}
@@ -10,6 +10,7 @@ use std::fs;
 use std::path::Path;
 use toml;
 use serde::Deserialize;
+use crate::audio_analyzer::AudioAnalysisConfig;
 
 /// Configuración global de la aplicación. Se carga desde `config.toml` e incluye todos los módulos de configuración.
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -23,6 +24,42 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     pub performance: PerformanceConfig,
     pub airport_visual: AirportVisualConfig,
+    #[serde(default)]
+    pub analysis: AudioAnalysisConfig,
+    #[serde(default)]
+    pub meters: MetersConfig,
+    #[serde(default)]
+    pub features: FeatureBusConfig,
+    #[serde(default)]
+    pub scroll: ScrollConfig,
+    #[serde(default)]
+    pub view: ViewConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub tempo: TempoConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    #[serde(default)]
+    pub lod: LodConfig,
+    #[serde(default)]
+    pub themes: ThemeConfig,
+    #[serde(default)]
+    pub particles: ParticlesConfig,
+    #[serde(default)]
+    pub lanes: LanesConfig,
+    #[serde(default)]
+    pub staff: StaffConfig,
+    #[serde(default)]
+    pub piano_roll: PianoRollConfig,
+    #[serde(default)]
+    pub radial: RadialConfig,
+    #[serde(default)]
+    pub outputs: OutputsConfig,
 }
 
 /// Configuración del servidor OSC, incluyendo dirección, puerto y control de buffer y tiempo de espera.
@@ -102,6 +139,432 @@ pub struct PerformanceConfig {
     pub cleanup_interval_frames: u32,
 }
 
+/// Medidores de sonoridad EBU R128: objetivo, tolerancia y longitud de la tira de dinámica.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct MetersConfig {
+    pub show_on_start: bool,
+    pub target_lufs: f32,
+    pub tolerance_lu: f32,
+    pub max_true_peak: f32,
+    pub history_seconds: f32,
+}
+
+impl Default for MetersConfig {
+    fn default() -> Self {
+        Self {
+            show_on_start: false,
+            target_lufs: -23.0,
+            tolerance_lu: 1.0,
+            max_true_peak: -1.0,
+            history_seconds: 60.0,
+        }
+    }
+}
+
+/// Bus de características de audio: suavizado, decaimiento de ataques y reactividad global.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct FeatureBusConfig {
+    pub enabled: bool,
+    pub rms_smoothing: f32,
+    pub onset_decay: f32,
+    pub centroid_max: f32,
+    pub reactive_background: bool,
+    pub reactive_scale: f32,
+    pub history_seconds: f32,
+}
+
+impl Default for FeatureBusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rms_smoothing: 0.85,
+            onset_decay: 6.0,
+            centroid_max: 8000.0,
+            reactive_background: true,
+            reactive_scale: 0.3,
+            history_seconds: 120.0,
+        }
+    }
+}
+
+/// Desplazamiento de la línea de tiempo: modo inicial, velocidad del scroll continuo
+/// y tratamiento de la página anterior en el modo página.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScrollConfig {
+    pub mode: String,
+    pub scroll_speed: f32,
+    pub keep_previous_page: bool,
+    pub previous_page_opacity: f32,
+    pub scrub_step: f32,
+}
+
+impl Default for ScrollConfig {
+    fn default() -> Self {
+        Self {
+            mode: "Continuous".to_string(),
+            scroll_speed: 100.0,
+            keep_previous_page: true,
+            previous_page_opacity: 0.3,
+            scrub_step: 1.0,
+        }
+    }
+}
+
+/// Zoom y desplazamiento interactivos de la vista y reglas de tiempo y altura.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ViewConfig {
+    pub zoom_step: f32,
+    pub min_time_zoom: f32,
+    pub max_time_zoom: f32,
+    pub min_octaves: f32,
+    pub pan_step_octaves: f32,
+    pub smoothing: f32,
+    pub show_time_ruler: bool,
+    pub show_pitch_ruler: bool,
+    /// Etiquetas de la regla de alturas: "hz", "note" o "midi".
+    pub pitch_labels: String,
+    /// Frecuencia del La4 usada para nombres de nota y semitonos.
+    pub a4_reference: f32,
+    pub show_cents: bool,
+}
+
+impl Default for ViewConfig {
+    fn default() -> Self {
+        Self {
+            zoom_step: 1.25,
+            min_time_zoom: 0.05,
+            max_time_zoom: 50.0,
+            min_octaves: 0.5,
+            pan_step_octaves: 0.5,
+            smoothing: 12.0,
+            show_time_ruler: true,
+            show_pitch_ruler: true,
+            pitch_labels: "hz".to_string(),
+            a4_reference: 440.0,
+            show_cents: false,
+        }
+    }
+}
+
+/// Sincronización de reloj con sclang mediante `/viz/sync`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub target_host: String,
+    pub target_port: u16,
+    pub interval_secs: f32,
+    pub max_samples: usize,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_host: "127.0.0.1".to_string(),
+            target_port: 57120,
+            interval_secs: 1.0,
+            max_samples: 16,
+        }
+    }
+}
+
+/// Rejilla de compases y pulsos a partir de `/tempo` y `/beat`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TempoConfig {
+    pub show_grid: bool,
+    pub subdivisions: u32,
+    pub quantize: bool,
+    pub quantize_division: u32,
+}
+
+impl Default for TempoConfig {
+    fn default() -> Self {
+        Self {
+            show_grid: true,
+            subdivisions: 4,
+            quantize: false,
+            quantize_division: 4,
+        }
+    }
+}
+
+/// Política de retención de `musical_events`. Lo que sale de ella pasa al historial compacto.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub max_age_secs: f32,
+    pub max_events: usize,
+    pub max_memory_mb: f32,
+    pub history_max_events: usize,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age_secs: 30.0,
+            max_events: 20_000,
+            max_memory_mb: 64.0,
+            history_max_events: 1_000_000,
+        }
+    }
+}
+
+/// Exportación de la línea de tiempo a archivos vectoriales (SVG).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ExportConfig {
+    pub directory: String,
+    pub width: f32,
+    pub height: f32,
+    pub legend: bool,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            directory: "exports".to_string(),
+            width: 1920.0,
+            height: 1080.0,
+            legend: true,
+        }
+    }
+}
+
+/// Grabación de sesiones (mensajes OSC con su tiempo) para reproducirlas o renderizarlas offline.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RecordingConfig {
+    pub enabled: bool,
+    pub directory: String,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: "recordings".to_string(),
+        }
+    }
+}
+
+/// Nivel de detalle: con muchos eventos puntuales a la vista se agregan en una rejilla de densidad.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LodConfig {
+    pub enabled: bool,
+    /// Eventos puntuales visibles (notas, acordes, clusters) a partir de los que se agregan.
+    pub max_individual_events: usize,
+    /// Lado en píxeles de las celdas de densidad.
+    pub cell_px: f32,
+}
+
+impl Default for LodConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_individual_events: 2000,
+            cell_px: 6.0,
+        }
+    }
+}
+
+/// Directorio de temas visuales (`<nombre>.toml`) y frecuencia de comprobación de cambios.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub directory: String,
+    pub reload_interval_secs: f32,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            directory: "themes".to_string(),
+            reload_interval_secs: 1.0,
+        }
+    }
+}
+
+/// Capa de partículas: ráfagas en los ataques fuertes y estelas lentas en los drones.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ParticlesConfig {
+    pub enabled: bool,
+    /// Semilla del generador; la misma semilla reproduce las mismas partículas.
+    pub seed: u64,
+    /// Máximo de partículas vivas a la vez.
+    pub max_particles: usize,
+    /// Fuerza de ataque (amplitud, 0-1) a partir de la que un evento emite una ráfaga.
+    pub onset_threshold: f32,
+    /// Partículas por ráfaga a fuerza máxima.
+    pub burst_size: usize,
+    /// Partículas por segundo que desprende un drone a amplitud máxima.
+    pub drone_rate: f32,
+    /// Fracción de velocidad conservada por cada 1/60 s.
+    pub friction: f32,
+}
+
+impl Default for ParticlesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            seed: 0x5C07E,
+            max_particles: 2000,
+            onset_threshold: 0.5,
+            burst_size: 24,
+            drone_rate: 6.0,
+            friction: 0.98,
+        }
+    }
+}
+
+/// Carriles por instrumento: cada fuente tiene su franja, su nombre y su propio rango de alturas.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LanesConfig {
+    /// Carriles fijos, de arriba abajo.
+    pub order: Vec<String>,
+    /// Añadir un carril por cada instrumento nuevo que llegue.
+    pub auto_add: bool,
+    /// Ajustar el rango de alturas de cada carril a las notas que recibe.
+    pub auto_range: bool,
+    /// Crear también un carril por cada instrumento de `[midi].channel_instruments`.
+    pub include_midi: bool,
+    /// Altura relativa por defecto de cada carril.
+    pub lane_height: f32,
+    /// Ajustes de carriles concretos (`[[lanes.lane]]`).
+    pub lane: Vec<LaneSpec>,
+}
+
+impl Default for LanesConfig {
+    fn default() -> Self {
+        Self {
+            order: Vec::new(),
+            auto_add: false,
+            auto_range: true,
+            include_midi: false,
+            lane_height: 1.0,
+            lane: Vec::new(),
+        }
+    }
+}
+
+/// Ajustes de un carril: altura relativa, rango de alturas fijo y si empieza plegado.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LaneSpec {
+    pub name: String,
+    pub height: Option<f32>,
+    pub freq_min: Option<f32>,
+    pub freq_max: Option<f32>,
+    pub collapsed: bool,
+}
+
+/// Modo partitura: cuantización de alturas y escritura de las alteraciones.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct StaffConfig {
+    /// "semitone" o "quarter" (cuartos de tono).
+    pub resolution: String,
+    /// Escribir las teclas negras con bemoles en lugar de sostenidos.
+    pub prefer_flats: bool,
+    /// Tempo con el que se leen las duraciones mientras no llega `/tempo`.
+    pub default_bpm: f32,
+}
+
+impl Default for StaffConfig {
+    fn default() -> Self {
+        Self {
+            resolution: "semitone".to_string(),
+            prefer_flats: false,
+            default_bpm: 60.0,
+        }
+    }
+}
+
+/// Modo piano roll: rango de teclas (números MIDI) y ancho del teclado.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PianoRollConfig {
+    pub low_note: i32,
+    pub high_note: i32,
+    pub keyboard_width: f32,
+}
+
+impl Default for PianoRollConfig {
+    fn default() -> Self {
+        Self {
+            low_note: 21,
+            high_note: 108,
+            keyboard_width: 60.0,
+        }
+    }
+}
+
+/// Modo radial: qué representa el ángulo, duración del ciclo y estela de ciclos anteriores.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RadialConfig {
+    /// "clock" (fase del ciclo) o "chroma" (clase de altura).
+    pub layout: String,
+    /// "bar" (un compás de `/tempo`, si se conoce) o "seconds".
+    pub cycle: String,
+    /// Duración del ciclo cuando no hay tempo o `cycle = "seconds"`.
+    pub cycle_seconds: f32,
+    /// Radio del anillo interior respecto al exterior (0-0.9).
+    pub inner_radius: f32,
+    /// Ciclos anteriores que siguen visibles, atenuándose.
+    pub trail_cycles: f32,
+}
+
+impl Default for RadialConfig {
+    fn default() -> Self {
+        Self {
+            layout: "clock".to_string(),
+            cycle: "bar".to_string(),
+            cycle_seconds: 4.0,
+            inner_radius: 0.15,
+            trail_cycles: 1.0,
+        }
+    }
+}
+
+/// Ventanas de concierto: proyección limpia a pantalla completa y ventana de control (`[window]`).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OutputsConfig {
+    /// Abrir la ventana de proyección además de la de control.
+    pub projection: bool,
+    /// Monitor de la proyección (índice de la lista que se muestra al arrancar).
+    pub projection_monitor: usize,
+    pub projection_fullscreen: bool,
+    /// Monitor de la ventana de control; sin él, donde la coloque el sistema.
+    pub control_monitor: Option<usize>,
+    /// Modo inicial de la vista de monitorización de la ventana de control.
+    pub control_mode: String,
+    /// Líneas del registro de mensajes de la ventana de control.
+    pub log_lines: usize,
+}
+
+impl Default for OutputsConfig {
+    fn default() -> Self {
+        Self {
+            projection: false,
+            projection_monitor: 1,
+            projection_fullscreen: true,
+            control_monitor: None,
+            control_mode: "events".to_string(),
+            log_lines: 40,
+        }
+    }
+}
+
 impl AppConfig {
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let path = Path::new("config.toml");
@@ -0,0 +1,409 @@
+// src/event_history.rs
+
+//! 🗄️ Historial compacto de eventos
+//!
+//! Los eventos que salen de la ventana de retención de `Model::musical_events` se guardan
+//! aquí en un formato compacto (sin `String` ni `Instant`), ordenados por tiempo de sesión,
+//! para que la navegación por el historial y la exportación sigan disponiendo de ellos.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::events::{MusicalEvent, RealtimeData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactKind {
+    Note,
+    NoteColored,
+    Drone,
+    Cluster,
+    Realtime,
+    Glissando,
+    Noise,
+    Chord,
+}
+
+/// Evento archivado. Los campos `a`, `b` y `c` dependen del tipo:
+/// `NoteColored` → color RGB, `Cluster` → ancho de banda y densidad, `Realtime` → centroide,
+/// `Glissando` → frecuencia final y curva, `Noise` → ancho de banda.
+/// En los acordes `freq` es la nota más grave y el resto está en la tabla de acordes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompactEvent {
+    pub kind: CompactKind,
+    /// Inicio en segundos de sesión.
+    pub time: f32,
+    pub duration: f32,
+    pub freq: f32,
+    pub amp: f32,
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    /// Índice en la tabla de instrumentos del historial.
+    pub instrument: u16,
+    /// Posición de las alturas del acorde en la tabla de acordes (`Chord`).
+    pub chord: u32,
+}
+
+/// Almacén ordenado por tiempo con límite opcional de eventos.
+#[derive(Debug, Clone, Default)]
+pub struct EventHistory {
+    events: VecDeque<CompactEvent>,
+    instruments: Vec<String>,
+    /// Alturas de los acordes: número de notas seguido de las frecuencias.
+    chord_tones: Vec<f32>,
+    /// Entradas de `chord_tones` que pertenecen a acordes aún archivados.
+    live_tones: usize,
+    max_duration: f32,
+    max_events: usize,
+    dropped: u64,
+}
+
+impl EventHistory {
+    /// `max_events = 0` conserva todo el historial.
+    pub fn new(max_events: usize) -> Self {
+        Self { max_events, ..Self::default() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Eventos descartados por superar `max_events`.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Memoria aproximada ocupada por el historial (bytes).
+    pub fn memory_bytes(&self) -> usize {
+        self.events.capacity() * std::mem::size_of::<CompactEvent>()
+            + self.instruments.iter().map(|s| s.capacity() + std::mem::size_of::<String>()).sum::<usize>()
+            + self.chord_tones.capacity() * std::mem::size_of::<f32>()
+    }
+
+    fn intern(&mut self, instrument: &str) -> u16 {
+        if let Some(index) = self.instruments.iter().position(|s| s == instrument) {
+            return index as u16;
+        }
+        if self.instruments.len() >= u16::MAX as usize {
+            return 0;
+        }
+        self.instruments.push(instrument.to_string());
+        (self.instruments.len() - 1) as u16
+    }
+
+    pub fn instrument_name(&self, index: u16) -> &str {
+        self.instruments.get(index as usize).map_or("default", String::as_str)
+    }
+
+    /// Archiva `event`, que empezó `time` segundos después del inicio de la sesión.
+    /// Los eventos sin tiempo (datos de análisis) no se archivan.
+    pub fn push(&mut self, event: &MusicalEvent, time: f32) {
+        let blank = CompactEvent {
+            kind: CompactKind::Note,
+            time,
+            duration: 0.0,
+            freq: 0.0,
+            amp: 0.0,
+            a: 0.0,
+            b: 0.0,
+            c: 0.0,
+            instrument: 0,
+            chord: 0,
+        };
+        let compact = match event {
+            MusicalEvent::Note { frequency, amplitude, duration, instrument, .. } => CompactEvent {
+                duration: *duration,
+                freq: *frequency,
+                amp: *amplitude,
+                instrument: self.intern(instrument),
+                ..blank
+            },
+            MusicalEvent::NoteColored { frequency, amplitude, duration, r, g, b, .. } => CompactEvent {
+                kind: CompactKind::NoteColored,
+                duration: *duration,
+                freq: *frequency,
+                amp: *amplitude,
+                a: *r,
+                b: *g,
+                c: *b,
+                ..blank
+            },
+            MusicalEvent::Drone { frequency, amplitude, instrument, duration, .. } => CompactEvent {
+                kind: CompactKind::Drone,
+                duration: *duration,
+                freq: *frequency,
+                amp: *amplitude,
+                instrument: self.intern(instrument),
+                ..blank
+            },
+            MusicalEvent::Cluster { center_freq, freq_width, density, amplitude, duration, instrument, .. } => CompactEvent {
+                kind: CompactKind::Cluster,
+                duration: *duration,
+                freq: *center_freq,
+                amp: *amplitude,
+                a: *freq_width,
+                b: *density,
+                instrument: self.intern(instrument),
+                ..blank
+            },
+            MusicalEvent::Realtime(data) => CompactEvent {
+                kind: CompactKind::Realtime,
+                freq: data.pitch,
+                amp: data.amplitude,
+                a: data.centroid,
+                ..blank
+            },
+            MusicalEvent::Glissando { start_freq, end_freq, amplitude, duration, curve, instrument, .. } => CompactEvent {
+                kind: CompactKind::Glissando,
+                duration: *duration,
+                freq: *start_freq,
+                amp: *amplitude,
+                a: *end_freq,
+                b: *curve,
+                instrument: self.intern(instrument),
+                ..blank
+            },
+            MusicalEvent::Noise { center_freq, bandwidth, amplitude, duration, instrument, .. } => CompactEvent {
+                kind: CompactKind::Noise,
+                duration: *duration,
+                freq: *center_freq,
+                amp: *amplitude,
+                a: *bandwidth,
+                instrument: self.intern(instrument),
+                ..blank
+            },
+            MusicalEvent::Chord { frequencies, amplitude, duration, instrument, .. } => {
+                let instrument = self.intern(instrument);
+                let chord = self.chord_tones.len() as u32;
+                self.chord_tones.push(frequencies.len() as f32);
+                self.chord_tones.extend_from_slice(frequencies);
+                self.live_tones += frequencies.len() + 1;
+                CompactEvent {
+                    kind: CompactKind::Chord,
+                    duration: *duration,
+                    freq: frequencies.iter().copied().fold(f32::INFINITY, f32::min),
+                    amp: *amplitude,
+                    instrument,
+                    chord,
+                    ..blank
+                }
+            }
+            MusicalEvent::AnalysisData { .. } => return,
+        };
+
+        self.max_duration = self.max_duration.max(compact.duration);
+        // Normalmente llegan en orden; si no, se insertan en su sitio
+        if self.events.back().is_some_and(|last| last.time > time) {
+            let index = self.events.partition_point(|e| e.time <= time);
+            self.events.insert(index, compact);
+        } else {
+            self.events.push_back(compact);
+        }
+
+        if self.max_events > 0 && self.events.len() > self.max_events {
+            while self.events.len() > self.max_events {
+                if let Some(old) = self.events.pop_front() {
+                    self.live_tones -= self.tone_entries(&old);
+                    self.dropped += 1;
+                }
+            }
+            // La tabla se reconstruye cuando la mitad son acordes ya descartados
+            if self.chord_tones.len() > 2 * self.live_tones {
+                self.compact_chords();
+            }
+        }
+    }
+
+    /// Entradas que ocupa `event` en la tabla de acordes.
+    fn tone_entries(&self, event: &CompactEvent) -> usize {
+        match event.kind {
+            CompactKind::Chord => self.chord_frequencies(event).len() + 1,
+            _ => 0,
+        }
+    }
+
+    /// Reconstruye la tabla de acordes con solo los acordes que siguen archivados.
+    fn compact_chords(&mut self) {
+        let mut tones = Vec::with_capacity(self.live_tones);
+        for event in self.events.iter_mut().filter(|e| e.kind == CompactKind::Chord) {
+            let start = event.chord as usize;
+            let count = self.chord_tones.get(start).map_or(0, |c| *c as usize);
+            event.chord = tones.len() as u32;
+            tones.push(count as f32);
+            tones.extend_from_slice(self.chord_tones.get(start + 1..start + 1 + count).unwrap_or(&[]));
+        }
+        self.live_tones = tones.len();
+        self.chord_tones = tones;
+    }
+
+    /// Eventos que suenan en algún momento de `[from, to]`.
+    pub fn range(&self, from: f32, to: f32) -> impl Iterator<Item = &CompactEvent> {
+        let first = self.events.partition_point(|e| e.time < from - self.max_duration);
+        let last = self.events.partition_point(|e| e.time <= to);
+        self.events
+            .range(first..last.max(first))
+            .filter(move |e| e.time + e.duration >= from)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CompactEvent> {
+        self.events.iter()
+    }
+
+    /// Reconstruye el `MusicalEvent` original; `session_start` es el inicio de la sesión.
+    pub fn to_musical(&self, event: &CompactEvent, session_start: Instant) -> MusicalEvent {
+        let start_time = session_start + Duration::from_secs_f32(event.time.max(0.0));
+        match event.kind {
+            CompactKind::Note => MusicalEvent::Note {
+                frequency: event.freq,
+                amplitude: event.amp,
+                duration: event.duration,
+                instrument: self.instrument_name(event.instrument).to_string(),
+                start_time,
+            },
+            CompactKind::NoteColored => MusicalEvent::NoteColored {
+                frequency: event.freq,
+                amplitude: event.amp,
+                duration: event.duration,
+                r: event.a,
+                g: event.b,
+                b: event.c,
+                start_time,
+            },
+            CompactKind::Drone => MusicalEvent::Drone {
+                frequency: event.freq,
+                amplitude: event.amp,
+                instrument: self.instrument_name(event.instrument).to_string(),
+                start_time,
+                duration: event.duration,
+            },
+            CompactKind::Cluster => MusicalEvent::Cluster {
+                center_freq: event.freq,
+                freq_width: event.a,
+                density: event.b,
+                amplitude: event.amp,
+                duration: event.duration,
+                instrument: self.instrument_name(event.instrument).to_string(),
+                start_time,
+            },
+            CompactKind::Realtime => MusicalEvent::Realtime(RealtimeData {
+                pitch: event.freq,
+                amplitude: event.amp,
+                centroid: event.a,
+                timestamp: start_time,
+            }),
+            CompactKind::Glissando => MusicalEvent::Glissando {
+                start_freq: event.freq,
+                end_freq: event.a,
+                amplitude: event.amp,
+                duration: event.duration,
+                curve: event.b,
+                instrument: self.instrument_name(event.instrument).to_string(),
+                start_time,
+            },
+            CompactKind::Noise => MusicalEvent::Noise {
+                center_freq: event.freq,
+                bandwidth: event.a,
+                amplitude: event.amp,
+                duration: event.duration,
+                instrument: self.instrument_name(event.instrument).to_string(),
+                start_time,
+            },
+            CompactKind::Chord => MusicalEvent::Chord {
+                frequencies: self.chord_frequencies(event).to_vec(),
+                amplitude: event.amp,
+                duration: event.duration,
+                instrument: self.instrument_name(event.instrument).to_string(),
+                start_time,
+            },
+        }
+    }
+
+    /// Alturas de un acorde archivado.
+    pub fn chord_frequencies(&self, event: &CompactEvent) -> &[f32] {
+        let start = event.chord as usize;
+        match self.chord_tones.get(start) {
+            Some(count) => self.chord_tones.get(start + 1..start + 1 + *count as usize).unwrap_or(&[]),
+            None => &[],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.chord_tones.clear();
+        self.live_tones = 0;
+        self.max_duration = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(start: Instant, duration: f32) -> MusicalEvent {
+        MusicalEvent::Note {
+            frequency: 440.0,
+            amplitude: 0.5,
+            duration,
+            instrument: "violin".to_string(),
+            start_time: start,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_range() {
+        let start = Instant::now();
+        let mut history = EventHistory::new(0);
+        history.push(&note(start, 5.0), 1.0);
+        history.push(&note(start, 0.5), 10.0);
+        history.push(&note(start, 0.5), 4.0);
+
+        let times: Vec<f32> = history.iter().map(|e| e.time).collect();
+        assert_eq!(times, vec![1.0, 4.0, 10.0]);
+        // El evento largo que empezó en 1.0 sigue sonando en 5.5
+        let visible: Vec<f32> = history.range(5.5, 9.0).map(|e| e.time).collect();
+        assert_eq!(visible, vec![1.0]);
+
+        let restored = history.to_musical(history.iter().next().unwrap(), start);
+        match restored {
+            MusicalEvent::Note { instrument, duration, .. } => {
+                assert_eq!(instrument, "violin");
+                assert_eq!(duration, 5.0);
+            }
+            other => panic!("tipo inesperado: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_max_events_drops_oldest() {
+        let start = Instant::now();
+        let mut history = EventHistory::new(2);
+        for i in 0..5 {
+            history.push(&note(start, 0.1), i as f32);
+        }
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.dropped(), 3);
+        assert_eq!(history.iter().next().unwrap().time, 3.0);
+    }
+
+    #[test]
+    fn test_dropped_chords_are_compacted() {
+        let start = Instant::now();
+        let mut history = EventHistory::new(2);
+        for i in 0..50 {
+            let chord = MusicalEvent::Chord {
+                frequencies: vec![100.0 + i as f32, 200.0, 300.0],
+                amplitude: 0.5,
+                duration: 0.1,
+                instrument: "piano".to_string(),
+                start_time: start,
+            };
+            history.push(&chord, i as f32);
+        }
+        assert!(history.chord_tones.len() <= 2 * 2 * 4);
+        let lowest: Vec<f32> = history.iter().map(|e| history.chord_frequencies(e)[0]).collect();
+        assert_eq!(lowest, vec![148.0, 149.0]);
+    }
+}
@@ -50,8 +50,50 @@ pub enum MusicalEvent {
         density: f32,
         amplitude: f32,
         duration: f32,
+        instrument: String,
+        start_time: Instant,
+    },
+    /// Glissando de `start_freq` a `end_freq` durante `duration` segundos. `curve` sigue la
+    /// convención de `Env` de SuperCollider: 0 es lineal en altura, positivo empieza lento.
+    Glissando {
+        start_freq: f32,
+        end_freq: f32,
+        amplitude: f32,
+        duration: f32,
+        curve: f32,
+        instrument: String,
         start_time: Instant,
     },
+    /// Banda de ruido centrada en `center_freq` con `bandwidth` Hz de ancho.
+    Noise {
+        center_freq: f32,
+        bandwidth: f32,
+        amplitude: f32,
+        duration: f32,
+        instrument: String,
+        start_time: Instant,
+    },
+    /// Acorde: varias alturas que empiezan a la vez.
+    Chord {
+        frequencies: Vec<f32>,
+        amplitude: f32,
+        duration: f32,
+        instrument: String,
+        start_time: Instant,
+    },
+}
+
+/// Frecuencia de un glissando tras `progress` (0.0 - 1.0) de su duración.
+/// La interpolación es exponencial en Hz (lineal en altura) y `curve` la deforma como en `Env`.
+pub fn glissando_freq(start_freq: f32, end_freq: f32, curve: f32, progress: f32) -> f32 {
+    let progress = progress.clamp(0.0, 1.0);
+    let shaped = if curve.abs() < 1e-3 {
+        progress
+    } else {
+        (1.0 - (curve * progress).exp()) / (1.0 - curve.exp())
+    };
+    let (start, end) = (start_freq.max(1.0), end_freq.max(1.0));
+    start * (end / start).powf(shaped)
 }
 
 impl MusicalEvent {
@@ -64,6 +106,48 @@ impl MusicalEvent {
             _ => None,
         }
     }
+
+    /// Inicio del evento para cualquier tipo con tiempo (incluye NoteColored y Cluster).
+    pub fn start_time(&self) -> Option<Instant> {
+        match self {
+            MusicalEvent::NoteColored { start_time, .. }
+            | MusicalEvent::Cluster { start_time, .. }
+            | MusicalEvent::Glissando { start_time, .. }
+            | MusicalEvent::Noise { start_time, .. }
+            | MusicalEvent::Chord { start_time, .. } => Some(*start_time),
+            other => other.timestamp(),
+        }
+    }
+
+    /// Duración del evento en segundos (0.0 para datos instantáneos).
+    pub fn duration(&self) -> f32 {
+        match self {
+            MusicalEvent::Note { duration, .. }
+            | MusicalEvent::NoteColored { duration, .. }
+            | MusicalEvent::Drone { duration, .. }
+            | MusicalEvent::Cluster { duration, .. }
+            | MusicalEvent::Glissando { duration, .. }
+            | MusicalEvent::Noise { duration, .. }
+            | MusicalEvent::Chord { duration, .. } => *duration,
+            _ => 0.0,
+        }
+    }
+
+    /// Memoria aproximada que ocupa el evento (bytes), incluido el texto del instrumento.
+    pub fn approx_size(&self) -> usize {
+        let heap = match self {
+            MusicalEvent::Note { instrument, .. }
+            | MusicalEvent::Drone { instrument, .. }
+            | MusicalEvent::Cluster { instrument, .. }
+            | MusicalEvent::Glissando { instrument, .. }
+            | MusicalEvent::Noise { instrument, .. } => instrument.capacity(),
+            MusicalEvent::Chord { frequencies, instrument, .. } => {
+                frequencies.capacity() * std::mem::size_of::<f32>() + instrument.capacity()
+            }
+            _ => 0,
+        };
+        std::mem::size_of::<MusicalEvent>() + heap
+    }
 }
 
 /// Datos musicales en tiempo real (por ejemplo, para análisis continuo)
@@ -89,4 +173,7 @@ pub struct ProcessedOscMessage {
     pub addr: String,
     pub args: Vec<osc::Type>,
     pub timestamp: std::time::Instant,
+    /// Marca de tiempo del bundle que lo contenía (segundos del reloj de sclang);
+    /// `None` si llegó suelto o con la marca «inmediatamente».
+    pub remote_time: Option<f64>,
 }
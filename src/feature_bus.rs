@@ -0,0 +1,250 @@
+// src/feature_bus.rs
+
+//! 🔊 Bus de características de audio
+//!
+//! Reúne en cada frame las características globales del sonido (RMS, fuerza de ataque,
+//! fase del pulso, centroide espectral y el último `RealtimeData`) a partir de todas las
+//! fuentes disponibles: OSC, análisis de la entrada y medidor de sonoridad.
+//! Los valores se exponen tanto a los renderizadores de CPU (`AudioFeatures`) como al
+//! bloque de uniforms de los shaders (`Uniforms`).
+
+use std::collections::VecDeque;
+use std::time::Instant;
+use crate::config::FeatureBusConfig;
+use crate::events::RealtimeData;
+use crate::visual::uniforms::Uniforms;
+
+/// Instantánea de las características de audio del frame actual.
+#[derive(Debug, Clone, Default)]
+pub struct AudioFeatures {
+    /// Tiempo transcurrido desde el inicio (segundos).
+    pub time: f32,
+    /// Nivel RMS suavizado (0.0 - 1.0).
+    pub rms: f32,
+    /// Fuerza del último ataque, con decaimiento exponencial (0.0 - 1.0).
+    pub onset_strength: f32,
+    /// Fase dentro del pulso actual (0.0 justo en el pulso, tiende a 1.0 antes del siguiente).
+    pub beat_phase: f32,
+    /// Periodo estimado del pulso en segundos (0.0 si aún no se conoce).
+    pub beat_period: f32,
+    /// Centroide espectral suavizado (Hz).
+    pub centroid: f32,
+    /// Último dato en tiempo real recibido.
+    pub realtime: Option<RealtimeData>,
+}
+
+impl AudioFeatures {
+    /// Intensidad del pulso: 1.0 en el pulso y cae a lo largo del periodo.
+    pub fn beat_intensity(&self) -> f32 {
+        if self.beat_period <= 0.0 {
+            0.0
+        } else {
+            (1.0 - self.beat_phase).powi(3)
+        }
+    }
+}
+
+/// Muestra del historial de análisis, para dibujar curvas en el modo Análisis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalysisSample {
+    /// Segundos de sesión.
+    pub time: f32,
+    pub rms: f32,
+    pub onset_strength: f32,
+    pub centroid: f32,
+    /// Último `/analysis_data` (amplitud, brillo, ruido).
+    pub amplitude: f32,
+    pub brightness: f32,
+    pub noisy: f32,
+    /// Altura del último `/realtime`, si lo hay.
+    pub pitch: Option<f32>,
+}
+
+/// Intervalo mínimo entre muestras del historial (segundos).
+const HISTORY_INTERVAL: f32 = 1.0 / 30.0;
+
+/// Acumula las entradas del frame y produce `AudioFeatures` y `Uniforms`.
+#[derive(Debug)]
+pub struct FeatureBus {
+    config: FeatureBusConfig,
+    features: AudioFeatures,
+    uniforms: Uniforms,
+    rms_target: f32,
+    centroid_target: f32,
+    last_beat: Option<Instant>,
+    analysis: (f32, f32, f32),
+    history: VecDeque<AnalysisSample>,
+}
+
+impl FeatureBus {
+    pub fn new(config: FeatureBusConfig) -> Self {
+        Self {
+            config,
+            features: AudioFeatures::default(),
+            uniforms: Uniforms::new(),
+            rms_target: 0.0,
+            centroid_target: 0.0,
+            last_beat: None,
+            analysis: (0.0, 0.0, 0.0),
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Registra un nivel RMS (lineal) procedente de cualquier fuente.
+    pub fn push_rms(&mut self, rms: f32) {
+        self.rms_target = rms.abs().clamp(0.0, 1.0);
+    }
+
+    /// Registra un ataque; se conserva el más fuerte hasta que decae.
+    pub fn push_onset(&mut self, strength: f32) {
+        self.features.onset_strength = self.features.onset_strength.max(strength.clamp(0.0, 1.0));
+    }
+
+    /// Registra un centroide espectral en Hz.
+    pub fn push_centroid(&mut self, centroid: f32) {
+        if centroid.is_finite() && centroid > 0.0 {
+            self.centroid_target = centroid;
+        }
+    }
+
+    /// Registra un pulso. El periodo se estima a partir del intervalo entre pulsos.
+    pub fn push_beat(&mut self, at: Instant) {
+        if let Some(previous) = self.last_beat {
+            let interval = at.duration_since(previous).as_secs_f32();
+            if interval > 0.05 && interval < 4.0 {
+                self.features.beat_period = if self.features.beat_period > 0.0 {
+                    self.features.beat_period * 0.7 + interval * 0.3
+                } else {
+                    interval
+                };
+            }
+        }
+        self.last_beat = Some(at);
+        self.features.beat_phase = 0.0;
+    }
+
+    /// Fija el periodo del pulso directamente (p. ej. desde un tempo conocido).
+    pub fn set_beat_period(&mut self, period: f32) {
+        self.features.beat_period = period.max(0.0);
+    }
+
+    /// Registra un `/analysis_data` (amplitud, brillo, ruido).
+    pub fn push_analysis(&mut self, amplitude: f32, brightness: f32, noisy: f32) {
+        self.push_rms(amplitude);
+        self.analysis = (amplitude, brightness, noisy);
+    }
+
+    /// Registra el último dato en tiempo real (pitch, amplitud, centroide).
+    pub fn push_realtime(&mut self, data: RealtimeData) {
+        self.push_rms(data.amplitude);
+        self.push_centroid(data.centroid);
+        self.features.realtime = Some(data);
+    }
+
+    /// Avanza el bus un frame: suaviza, aplica decaimientos, calcula la fase del pulso
+    /// y actualiza el bloque de uniforms.
+    pub fn update(&mut self, now: Instant, time: f32, dt: f32) {
+        let smoothing = self.config.rms_smoothing.clamp(0.0, 0.999);
+        let features = &mut self.features;
+        features.time = time;
+        features.rms = features.rms * smoothing + self.rms_target * (1.0 - smoothing);
+        features.centroid = if features.centroid <= 0.0 {
+            self.centroid_target
+        } else {
+            features.centroid * smoothing + self.centroid_target * (1.0 - smoothing)
+        };
+        features.onset_strength *= (-self.config.onset_decay * dt).exp();
+
+        if let (Some(last), true) = (self.last_beat, features.beat_period > 0.0) {
+            let since = now.saturating_duration_since(last).as_secs_f32();
+            features.beat_phase = (since / features.beat_period).fract();
+        }
+
+        self.uniforms.update_from_features(features, self.config.centroid_max);
+        self.record_history(time);
+    }
+
+    fn record_history(&mut self, time: f32) {
+        if self.history.back().is_some_and(|last| time - last.time < HISTORY_INTERVAL) {
+            return;
+        }
+        let features = &self.features;
+        self.history.push_back(AnalysisSample {
+            time,
+            rms: features.rms,
+            onset_strength: features.onset_strength,
+            centroid: features.centroid,
+            amplitude: self.analysis.0,
+            brightness: self.analysis.1,
+            noisy: self.analysis.2,
+            pitch: features.realtime.as_ref().map(|r| r.pitch).filter(|p| *p > 0.0),
+        });
+        let horizon = time - self.config.history_seconds.max(1.0);
+        while self.history.front().is_some_and(|s| s.time < horizon) {
+            self.history.pop_front();
+        }
+    }
+
+    /// Historial de características, del más antiguo al más reciente.
+    pub fn history(&self) -> &VecDeque<AnalysisSample> {
+        &self.history
+    }
+
+    pub fn features(&self) -> &AudioFeatures {
+        &self.features
+    }
+
+    pub fn uniforms(&self) -> &Uniforms {
+        &self.uniforms
+    }
+
+    pub fn config(&self) -> &FeatureBusConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_onset_decays() {
+        let mut bus = FeatureBus::new(FeatureBusConfig::default());
+        let now = Instant::now();
+        bus.push_onset(1.0);
+        bus.update(now, 0.0, 0.0);
+        let initial = bus.features().onset_strength;
+        bus.update(now, 0.5, 0.5);
+        assert!(bus.features().onset_strength < initial);
+    }
+
+    #[test]
+    fn test_beat_period_and_phase() {
+        let mut bus = FeatureBus::new(FeatureBusConfig::default());
+        let start = Instant::now();
+        bus.push_beat(start);
+        bus.push_beat(start + Duration::from_millis(500));
+        assert!((bus.features().beat_period - 0.5).abs() < 1e-3);
+
+        bus.update(start + Duration::from_millis(750), 0.75, 0.016);
+        assert!((bus.features().beat_phase - 0.5).abs() < 0.01);
+        assert!((bus.uniforms().beat_intensity - 0.125).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_history_is_sampled_and_bounded() {
+        let config = FeatureBusConfig { history_seconds: 2.0, ..FeatureBusConfig::default() };
+        let mut bus = FeatureBus::new(config);
+        let now = Instant::now();
+        bus.push_analysis(0.5, 0.2, 0.1);
+        for frame in 0..600 {
+            bus.update(now, frame as f32 / 120.0, 1.0 / 120.0);
+        }
+        let history = bus.history();
+        // 120 fps muestreados a 30 Hz como máximo, solo los últimos 2 s
+        assert!(history.len() <= 61 && history.len() >= 40);
+        assert!(history.back().unwrap().time - history.front().unwrap().time <= 2.0);
+        assert_eq!(history.back().unwrap().brightness, 0.2);
+    }
+}
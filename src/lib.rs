@@ -18,3 +18,25 @@ pub mod visual;
 pub mod events;
 /// Módulo de servidor OSC (legacy, actualmente desacoplado del modelo)
 pub mod osc_server;
+/// Captura de audio de entrada (selección de dispositivo y multicanal)
+pub mod capture;
+/// Análisis de audio por canal (ataques, altura, texturas)
+pub mod audio_analyzer;
+/// Medición de sonoridad EBU R128 (LUFS, LRA, pico verdadero)
+pub mod loudness;
+/// Bus de características de audio compartido por shaders y renderizadores
+pub mod feature_bus;
+/// Sincronización de reloj con sclang (desfase y latencia de red)
+pub mod clock_sync;
+/// Mapa de tempo de `TempoClock` (compases y pulsos)
+pub mod tempo_map;
+/// Historial compacto de eventos fuera de la ventana de retención
+pub mod event_history;
+/// Reloj inyectable (tiempo real, manual y offline de paso fijo)
+pub mod clock;
+/// Grabación y lectura de sesiones (mensajes OSC con su tiempo)
+pub mod session_recording;
+/// Render offline de sesiones grabadas a secuencias PNG
+pub mod offline_render;
+/// Conversión entre frecuencia, número MIDI y nombre de nota
+pub mod pitch;
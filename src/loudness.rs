@@ -0,0 +1,485 @@
+// src/loudness.rs
+
+//! 📏 Medición de sonoridad según EBU R128 / ITU-R BS.1770
+//!
+//! Calcula sonoridad momentánea (400 ms), de corto plazo (3 s), integrada (con puertas
+//! absoluta y relativa), rango de sonoridad (LRA) y pico verdadero con sobremuestreo 4x.
+//! Puede alimentarse con muestras de la entrada de audio o, en su defecto, con la
+//! amplitud recibida por OSC (`/analysis_data`), en cuyo caso no se aplica ponderación K.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Duración de cada sub-bloque de medida (100 ms).
+const SUBBLOCK_SECS: f64 = 0.1;
+/// Sub-bloques que forman la ventana momentánea (400 ms).
+const MOMENTARY_SUBBLOCKS: usize = 4;
+/// Sub-bloques que forman la ventana de corto plazo (3 s).
+const SHORT_TERM_SUBBLOCKS: usize = 30;
+/// Puerta absoluta en LUFS.
+const ABSOLUTE_GATE: f64 = -70.0;
+/// Límite superior y anchura de las clases de los histogramas de bloques (LU).
+const HISTOGRAM_MAX: f64 = 10.0;
+const HISTOGRAM_STEP: f64 = 0.1;
+/// Factor de sobremuestreo para el pico verdadero.
+const OVERSAMPLING: usize = 4;
+/// Coeficientes por fase del interpolador de pico verdadero.
+const TRUE_PEAK_TAPS: usize = 12;
+
+/// Lecturas actuales del medidor. Los valores sin señal valen `f32::NEG_INFINITY`.
+#[derive(Debug, Clone, Copy)]
+pub struct LoudnessReadings {
+    pub momentary: f32,
+    pub short_term: f32,
+    pub integrated: f32,
+    pub loudness_range: f32,
+    pub true_peak_db: f32,
+    pub max_true_peak_db: f32,
+    /// `true` si las lecturas proceden de amplitud OSC y no de muestras ponderadas.
+    pub from_amplitude: bool,
+}
+
+impl Default for LoudnessReadings {
+    fn default() -> Self {
+        Self {
+            momentary: f32::NEG_INFINITY,
+            short_term: f32::NEG_INFINITY,
+            integrated: f32::NEG_INFINITY,
+            loudness_range: 0.0,
+            true_peak_db: f32::NEG_INFINITY,
+            max_true_peak_db: f32::NEG_INFINITY,
+            from_amplitude: false,
+        }
+    }
+}
+
+/// Punto de la tira de dinámica: un valor por sub-bloque de 100 ms.
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicsPoint {
+    pub momentary: f32,
+    pub short_term: f32,
+    pub peak_db: f32,
+}
+
+/// Filtro biquad en forma directa II transpuesta.
+#[derive(Debug, Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self { b, a, z1: 0.0, z2: 0.0 }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z1;
+        self.z1 = self.b[1] * x - self.a[1] * y + self.z2;
+        self.z2 = self.b[2] * x - self.a[2] * y;
+        y
+    }
+}
+
+/// Ponderación K (pre-filtro de estantería + filtro RLB) para una frecuencia de muestreo.
+fn k_weighting(sample_rate: f64) -> (Biquad, Biquad) {
+    use std::f64::consts::PI;
+
+    let f0 = 1681.974450955533;
+    let gain_db = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    (shelf, highpass)
+}
+
+/// Convierte energía media (suma de cuadrados medios por canal) en LUFS.
+fn energy_to_lufs(energy: f64) -> f32 {
+    if energy <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        (-0.691 + 10.0 * energy.log10()) as f32
+    }
+}
+
+fn lufs_to_energy(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+fn amplitude_to_db(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        20.0 * amplitude.log10()
+    }
+}
+
+/// Histograma fijo de bloques por encima de la puerta absoluta, en clases de 0.1 LU entre
+/// -70 y +10 LUFS (lo más fuerte cae en la última). Guarda la cuenta y la energía de cada
+/// clase, así que la memoria y el coste de cada lectura no crecen con la duración de la sesión.
+#[derive(Debug, Clone)]
+struct BlockHistogram {
+    counts: Vec<u64>,
+    energies: Vec<f64>,
+}
+
+impl BlockHistogram {
+    fn new() -> Self {
+        let bins = ((HISTOGRAM_MAX - ABSOLUTE_GATE) / HISTOGRAM_STEP).round() as usize;
+        Self { counts: vec![0; bins], energies: vec![0.0; bins] }
+    }
+
+    /// Clase de una sonoridad (LUFS) por encima de la puerta absoluta.
+    fn bin_of(&self, lufs: f64) -> usize {
+        (((lufs - ABSOLUTE_GATE) / HISTOGRAM_STEP).floor().max(0.0) as usize).min(self.counts.len() - 1)
+    }
+
+    /// Sonoridad del centro de la clase `bin`.
+    fn bin_lufs(bin: usize) -> f32 {
+        (ABSOLUTE_GATE + (bin as f64 + 0.5) * HISTOGRAM_STEP) as f32
+    }
+
+    fn push(&mut self, energy: f64) {
+        if energy > lufs_to_energy(ABSOLUTE_GATE) {
+            let bin = self.bin_of(energy_to_lufs(energy) as f64);
+            self.counts[bin] += 1;
+            self.energies[bin] += energy;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|c| *c = 0);
+        self.energies.iter_mut().for_each(|e| *e = 0.0);
+    }
+
+    /// Primera clase que supera la puerta relativa de `gate_lu` bajo la media, y número de bloques.
+    fn relative_gate(&self, gate_lu: f64) -> Option<usize> {
+        let count: u64 = self.counts.iter().sum();
+        if count == 0 {
+            return None;
+        }
+        let mean = self.energies.iter().sum::<f64>() / count as f64;
+        Some(self.bin_of(energy_to_lufs(mean) as f64 - gate_lu))
+    }
+
+    /// Sonoridad integrada con puerta absoluta (-70 LUFS) y relativa (-10 LU).
+    fn integrated(&self) -> f32 {
+        let Some(gate) = self.relative_gate(10.0) else { return f32::NEG_INFINITY };
+        let count: u64 = self.counts[gate..].iter().sum();
+        if count == 0 {
+            return f32::NEG_INFINITY;
+        }
+        energy_to_lufs(self.energies[gate..].iter().sum::<f64>() / count as f64)
+    }
+
+    /// Rango de sonoridad (EBU Tech 3342): percentiles 10 y 95 de la sonoridad de corto
+    /// plazo, con puerta absoluta y relativa de -20 LU.
+    fn loudness_range(&self) -> f32 {
+        let Some(gate) = self.relative_gate(20.0) else { return 0.0 };
+        let counts = &self.counts[gate..];
+        let total: u64 = counts.iter().sum();
+        if total < 2 {
+            return 0.0;
+        }
+        let percentile = |p: f64| {
+            let rank = ((total - 1) as f64 * p).round() as u64;
+            let mut seen = 0;
+            let bin = counts.iter().position(|&c| {
+                seen += c;
+                seen > rank
+            });
+            Self::bin_lufs(gate + bin.unwrap_or(counts.len() - 1))
+        };
+        percentile(0.95) - percentile(0.10)
+    }
+}
+
+/// Medidor de sonoridad EBU R128.
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    sample_rate: f64,
+    channels: usize,
+    filters: Vec<(Biquad, Biquad)>,
+    subblock_len: usize,
+    subblock_pos: usize,
+    subblock_sum: f64,
+    subblock_peak: f32,
+    /// Últimos 30 sub-bloques (energía media por sub-bloque).
+    recent: VecDeque<f64>,
+    /// Bloques de 400 ms solapados al 75 % (para la sonoridad integrada).
+    gating_blocks: BlockHistogram,
+    /// Bloques de corto plazo (para el LRA).
+    short_term_blocks: BlockHistogram,
+    true_peak_history: Vec<VecDeque<f32>>,
+    true_peak_phases: Vec<[f32; TRUE_PEAK_TAPS]>,
+    history: VecDeque<DynamicsPoint>,
+    history_len: usize,
+    readings: LoudnessReadings,
+    amplitude_time: f64,
+    last_amplitude_at: Option<Instant>,
+}
+
+impl LoudnessMeter {
+    /// Crea un medidor para `channels` canales a `sample_rate` Hz que conserva
+    /// `history_secs` segundos de tira de dinámica.
+    pub fn new(sample_rate: u32, channels: usize, history_secs: f32) -> Self {
+        let channels = channels.max(1);
+        let sample_rate = sample_rate.max(1) as f64;
+        Self {
+            sample_rate,
+            channels,
+            filters: (0..channels).map(|_| k_weighting(sample_rate)).collect(),
+            subblock_len: ((sample_rate * SUBBLOCK_SECS) as usize).max(1),
+            subblock_pos: 0,
+            subblock_sum: 0.0,
+            subblock_peak: 0.0,
+            recent: VecDeque::with_capacity(SHORT_TERM_SUBBLOCKS),
+            gating_blocks: BlockHistogram::new(),
+            short_term_blocks: BlockHistogram::new(),
+            true_peak_history: (0..channels).map(|_| VecDeque::from(vec![0.0; TRUE_PEAK_TAPS])).collect(),
+            true_peak_phases: Self::true_peak_filter(),
+            history: VecDeque::new(),
+            history_len: ((history_secs as f64 / SUBBLOCK_SECS) as usize).max(1),
+            readings: LoudnessReadings::default(),
+            amplitude_time: 0.0,
+            last_amplitude_at: None,
+        }
+    }
+
+    /// Fases del interpolador sinc enventanado usado para estimar el pico verdadero.
+    fn true_peak_filter() -> Vec<[f32; TRUE_PEAK_TAPS]> {
+        use std::f64::consts::PI;
+
+        let delay = (TRUE_PEAK_TAPS / 2) as f64;
+        let half_width = delay + 0.5;
+        (0..OVERSAMPLING)
+            .map(|phase| {
+                let mut taps = [0.0f32; TRUE_PEAK_TAPS];
+                for (k, tap) in taps.iter_mut().enumerate() {
+                    let x = k as f64 - delay + phase as f64 / OVERSAMPLING as f64;
+                    let sinc = if x.abs() < 1e-9 { 1.0 } else { (PI * x).sin() / (PI * x) };
+                    let window = if x.abs() < half_width { 0.5 + 0.5 * (PI * x / half_width).cos() } else { 0.0 };
+                    *tap = (sinc * window) as f32;
+                }
+                taps
+            })
+            .collect()
+    }
+
+    /// Procesa un bloque de muestras entrelazadas.
+    pub fn process_interleaved(&mut self, samples: &[f32]) {
+        for frame in samples.chunks(self.channels) {
+            let mut frame_energy = 0.0;
+            for (ch, &sample) in frame.iter().enumerate() {
+                let (shelf, highpass) = &mut self.filters[ch];
+                let weighted = highpass.process(shelf.process(sample as f64));
+                frame_energy += weighted * weighted;
+
+                let peak = self.true_peak_sample(ch, sample);
+                self.subblock_peak = self.subblock_peak.max(peak);
+            }
+            self.subblock_sum += frame_energy;
+            self.subblock_pos += 1;
+            if self.subblock_pos >= self.subblock_len {
+                let energy = self.subblock_sum / self.subblock_len as f64;
+                let peak = self.subblock_peak;
+                self.subblock_pos = 0;
+                self.subblock_sum = 0.0;
+                self.subblock_peak = 0.0;
+                self.push_subblock(energy, peak, false);
+            }
+        }
+    }
+
+    /// Interpola 4x la señal del canal y devuelve el máximo absoluto de las muestras interpoladas.
+    fn true_peak_sample(&mut self, channel: usize, sample: f32) -> f32 {
+        let history = &mut self.true_peak_history[channel];
+        history.pop_back();
+        history.push_front(sample);
+        self.true_peak_phases
+            .iter()
+            .map(|taps| taps.iter().zip(history.iter()).map(|(t, x)| t * x).sum::<f32>().abs())
+            .fold(sample.abs(), f32::max)
+    }
+
+    /// Alimenta el medidor con una amplitud (RMS lineal) recibida por OSC.
+    /// El tiempo entre llamadas determina el peso de cada valor.
+    pub fn feed_amplitude(&mut self, amplitude: f32, now: Instant) {
+        let dt = match self.last_amplitude_at {
+            Some(previous) => now.duration_since(previous).as_secs_f64().min(SUBBLOCK_SECS),
+            None => SUBBLOCK_SECS,
+        };
+        self.last_amplitude_at = Some(now);
+
+        let amplitude = amplitude.abs();
+        self.subblock_sum += (amplitude as f64).powi(2) * dt;
+        self.subblock_peak = self.subblock_peak.max(amplitude);
+        self.amplitude_time += dt;
+        if self.amplitude_time >= SUBBLOCK_SECS {
+            let energy = self.subblock_sum / self.amplitude_time;
+            let peak = self.subblock_peak;
+            self.amplitude_time = 0.0;
+            self.subblock_sum = 0.0;
+            self.subblock_peak = 0.0;
+            self.push_subblock(energy, peak, true);
+        }
+    }
+
+    fn push_subblock(&mut self, energy: f64, peak: f32, from_amplitude: bool) {
+        if self.recent.len() == SHORT_TERM_SUBBLOCKS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(energy);
+
+        let mean_of_last = |n: usize| -> Option<f64> {
+            if self.recent.len() < n {
+                return None;
+            }
+            Some(self.recent.iter().rev().take(n).sum::<f64>() / n as f64)
+        };
+        let momentary = mean_of_last(MOMENTARY_SUBBLOCKS);
+        let short_term = mean_of_last(SHORT_TERM_SUBBLOCKS);
+
+        if let Some(block) = momentary {
+            self.gating_blocks.push(block);
+        }
+        if let Some(block) = short_term {
+            self.short_term_blocks.push(block);
+        }
+
+        let peak_db = amplitude_to_db(peak);
+        let readings = &mut self.readings;
+        readings.momentary = momentary.map_or(f32::NEG_INFINITY, energy_to_lufs);
+        readings.short_term = short_term.map_or(f32::NEG_INFINITY, energy_to_lufs);
+        readings.true_peak_db = peak_db;
+        readings.max_true_peak_db = readings.max_true_peak_db.max(peak_db);
+        readings.from_amplitude = from_amplitude;
+        readings.integrated = self.gating_blocks.integrated();
+        readings.loudness_range = self.short_term_blocks.loudness_range();
+
+        if self.history.len() == self.history_len {
+            self.history.pop_front();
+        }
+        self.history.push_back(DynamicsPoint {
+            momentary: self.readings.momentary,
+            short_term: self.readings.short_term,
+            peak_db,
+        });
+    }
+
+    pub fn readings(&self) -> LoudnessReadings {
+        self.readings
+    }
+
+    pub fn history(&self) -> &VecDeque<DynamicsPoint> {
+        &self.history
+    }
+
+    /// Reinicia la medida integrada, el LRA y el pico máximo (como al empezar una nueva toma).
+    pub fn reset_integrated(&mut self) {
+        self.gating_blocks.clear();
+        self.short_term_blocks.clear();
+        self.readings.integrated = f32::NEG_INFINITY;
+        self.readings.loudness_range = 0.0;
+        self.readings.max_true_peak_db = f32::NEG_INFINITY;
+    }
+
+    /// Cambia la longitud de la tira de dinámica conservada.
+    pub fn set_history_secs(&mut self, history_secs: f32) {
+        self.history_len = ((history_secs as f64 / SUBBLOCK_SECS) as usize).max(1);
+        while self.history.len() > self.history_len {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, amplitude: f32, sample_rate: u32, secs: f32, channels: usize) -> Vec<f32> {
+        let frames = (sample_rate as f32 * secs) as usize;
+        (0..frames)
+            .flat_map(|n| {
+                let v = amplitude * (2.0 * std::f32::consts::PI * freq * n as f32 / sample_rate as f32).sin();
+                std::iter::repeat_n(v, channels)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sine_reference_level() {
+        // Un seno de 997 Hz a -20 dBFS en un canal mide aprox. -23 LUFS
+        let mut meter = LoudnessMeter::new(48000, 1, 10.0);
+        meter.process_interleaved(&sine(997.0, 0.1, 48000, 5.0, 1));
+        let r = meter.readings();
+        assert!((r.momentary + 23.0).abs() < 0.3, "momentánea = {}", r.momentary);
+        assert!((r.short_term + 23.0).abs() < 0.3, "corto plazo = {}", r.short_term);
+        assert!((r.integrated + 23.0).abs() < 0.3, "integrada = {}", r.integrated);
+    }
+
+    #[test]
+    fn test_true_peak_of_sine() {
+        let mut meter = LoudnessMeter::new(48000, 2, 10.0);
+        meter.process_interleaved(&sine(1000.0, 0.5, 48000, 1.0, 2));
+        let r = meter.readings();
+        assert!((r.max_true_peak_db + 6.02).abs() < 0.5, "pico verdadero = {}", r.max_true_peak_db);
+    }
+
+    #[test]
+    fn test_silence_is_gated() {
+        let mut meter = LoudnessMeter::new(48000, 1, 10.0);
+        meter.process_interleaved(&vec![0.0; 48000 * 2]);
+        assert!(meter.readings().integrated.is_infinite());
+        assert_eq!(meter.history().len(), 20);
+    }
+
+    #[test]
+    fn test_block_histogram_gates_and_range() {
+        // Mitad de los bloques a -40 LUFS y mitad a -20: LRA de 20 LU
+        let mut blocks = BlockHistogram::new();
+        for i in 0..1000 {
+            blocks.push(lufs_to_energy(if i % 2 == 0 { -40.0 } else { -20.0 }));
+        }
+        blocks.push(0.0);
+        assert!((blocks.loudness_range() - 20.0).abs() < 0.15, "LRA = {}", blocks.loudness_range());
+        // La puerta relativa (-10 LU bajo la media, unos -23 LUFS) descarta los de -40
+        assert!((blocks.integrated() + 20.0).abs() < 0.01, "integrada = {}", blocks.integrated());
+        blocks.clear();
+        assert!(blocks.integrated().is_infinite());
+    }
+
+    #[test]
+    fn test_amplitude_feed_produces_readings() {
+        let mut meter = LoudnessMeter::new(48000, 1, 10.0);
+        let start = Instant::now();
+        for i in 0..100 {
+            meter.feed_amplitude(0.5, start + std::time::Duration::from_millis(i * 50));
+        }
+        let r = meter.readings();
+        assert!(r.from_amplitude);
+        assert!(r.short_term.is_finite());
+    }
+}
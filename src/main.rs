@@ -1,7 +1,6 @@
 use std::net::UdpSocket;
 use std::sync::mpsc;
 use std::thread;
-use std::time::Instant;
 use crate::logging::Logger;
 pub mod model;
 pub mod config;
@@ -11,13 +10,25 @@ pub mod osc_server;
 pub mod logging;
 pub mod midi;
 pub mod errors;
+pub mod capture;
+pub mod audio_analyzer;
+pub mod loudness;
+pub mod feature_bus;
+pub mod clock_sync;
+pub mod tempo_map;
+pub mod event_history;
+pub mod clock;
+pub mod session_recording;
+pub mod offline_render;
+pub mod pitch;
 
 use nannou::prelude::*;
-use nannou::event::{Event, Key, WindowEvent::KeyPressed};
+use nannou::event::{Event, Key, MouseButton, MouseScrollDelta, WindowEvent};
 use clap::Parser;
 use crate::osc_server::OscServer;
 use crate::model::Model;
 use crate::model::DisplayMode;
+use crate::visual::outputs::{ControlLayout, ModeTarget, WindowRole};
 use crate::visual::shader_manager::ShaderManager;
 use std::sync::Arc;
 use crate::errors::VisualizerError; // Asegúrate de importar VisualizerError
@@ -27,6 +38,24 @@ use crate::errors::VisualizerError; // Asegúrate de importar VisualizerError
 struct CliArgs {
     #[arg(long, help = "Activar modo debug detallado")]
     debug: bool,
+    #[arg(long, value_name = "SESION", help = "Renderizar offline una sesión grabada a PNG numerados y salir (sin ventana)")]
+    render: Option<std::path::PathBuf>,
+    #[arg(long, default_value = "frames", help = "Carpeta de destino de los frames de --render")]
+    out: std::path::PathBuf,
+    #[arg(long, default_value_t = 30.0, help = "Frames por segundo de --render")]
+    fps: f64,
+    #[arg(long, help = "Ancho de los frames de --render o del SVG de --svg (por defecto [export].width)")]
+    width: Option<f32>,
+    #[arg(long, help = "Alto de los frames de --render o del SVG de --svg (por defecto [export].height)")]
+    height: Option<f32>,
+    #[arg(long, default_value_t = 2.0, help = "Segundos renderizados tras el último mensaje")]
+    tail: f64,
+    #[arg(long, value_name = "SESION", help = "Exportar a SVG un intervalo de una sesión grabada y salir (sin ventana)")]
+    svg: Option<std::path::PathBuf>,
+    #[arg(long, default_value_t = 0.0, help = "Inicio (segundos de sesión) del intervalo de --svg")]
+    from: f32,
+    #[arg(long, help = "Final (segundos de sesión) del intervalo de --svg (por defecto el último mensaje)")]
+    to: Option<f32>,
 }
 
 /// Punto de entrada principal de la aplicación SC Score Visualizer.
@@ -39,6 +68,40 @@ fn main() {
 
     crate::logging::Logger::init();
     println!("Logger inicializado y funcionando.");
+
+    // Render offline: no se abre ninguna ventana, así que funciona sin pantalla
+    if let Some(session) = args.render {
+        let config = crate::config::AppConfig::load().expect("Error al cargar configuración");
+        let options = crate::offline_render::RenderOptions {
+            session,
+            out_dir: args.out,
+            fps: args.fps,
+            width: args.width.unwrap_or(config.export.width),
+            height: args.height.unwrap_or(config.export.height),
+            tail_secs: args.tail,
+        };
+        match crate::offline_render::render_session(config, &options) {
+            Ok(frames) => println!("🎬 {frames} frames escritos en {}", options.out_dir.display()),
+            Err(e) => {
+                eprintln!("❌ Error en el render offline: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(session) = args.svg {
+        let mut config = crate::config::AppConfig::load().expect("Error al cargar configuración");
+        config.export.width = args.width.unwrap_or(config.export.width);
+        config.export.height = args.height.unwrap_or(config.export.height);
+        match crate::offline_render::export_session_svg(config, &session, args.from, args.to) {
+            Ok(path) => println!("🖼️ SVG escrito en {}", path.display()),
+            Err(e) => {
+                eprintln!("❌ Error al exportar el SVG: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     println!("🚀 Iniciando SC Score Visualizer v2.0");
     
     nannou::app(model_setup)
@@ -61,21 +124,58 @@ fn model_setup(app: &App) -> Model {
     let window_height = config.window.height;
     let window_title = config.window.title.clone();
     let window_resizable = config.window.resizable;
-    app.new_window()
+    let outputs = config.outputs.clone();
+    let monitors = app.available_monitors();
+    for (index, monitor) in monitors.iter().enumerate() {
+        let size = monitor.size();
+        println!("🖥️  Monitor {index}: {} ({}x{})", monitor.name().unwrap_or_default(), size.width, size.height);
+    }
+    // Con proyección, la ventana de `[window]` pasa a ser la de control
+    let control_window = app.new_window()
+        .window(monitor_placement(&monitors, outputs.control_monitor))
         .title(&window_title)
         .size(window_width, window_height)
         .resizable(window_resizable)
-        .view(view)
+        .view(if outputs.projection { view_control } else { view })
         .build()
         .unwrap();
+    let projection_window = if outputs.projection {
+        let monitor = monitors.get(outputs.projection_monitor).cloned();
+        if monitor.is_none() {
+            crate::logging::Logger::log_warn(&format!("No existe el monitor {} para la proyección; se abre en el principal", outputs.projection_monitor));
+        }
+        let fullscreen = outputs.projection_fullscreen.then(|| nannou::winit::window::Fullscreen::Borderless(monitor.clone()));
+        let id = app.new_window()
+            .window(monitor_placement(&monitors, Some(outputs.projection_monitor)))
+            .title(format!("{window_title} · proyección"))
+            .size(window_width, window_height)
+            .fullscreen_with(fullscreen)
+            .view(view_projection)
+            .build()
+            .unwrap();
+        if let Some(window) = app.window(id) {
+            window.set_cursor_visible(false);
+        }
+        println!("📽️ Proyección en el monitor {}{}", outputs.projection_monitor, if outputs.projection_fullscreen { " (pantalla completa)" } else { "" });
+        Some(id)
+    } else {
+        None
+    };
+    let uniforms_gpu = {
+        let window = app.main_window();
+        crate::visual::uniforms_gpu::UniformsGpu::new(window.device(), &crate::visual::uniforms::Uniforms::new())
+    };
     eprintln!("DEBUG: Ventana configurada");
     println!("DEBUG: model_setup ha sido llamado.");
     println!("🔧 Configurando ventana principal...");
     
+    // Reloj compartido por el modelo y el servidor OSC (marcas de tiempo de los mensajes)
+    let clock = crate::clock::real_time();
+
     // --- Lógica de inicialización del servidor OSC más robusta ---
     let (osc_server_instance, osc_rx_for_events) = {
         // --- Inicialización del servidor OSC SOLO en el puerto especificado ---
-        let osc_server_result = OscServer::new(config.osc.clone(), config.audio.clone());
+        let osc_server_result = OscServer::new_with_clock(config.osc.clone(), config.audio.clone(), clock.clone());
         match osc_server_result {
             Ok((server, rx)) => {
                 println!("✅ Servidor OSC iniciado exitosamente en puerto: {}", config.osc.listen_port);
@@ -93,12 +193,30 @@ fn model_setup(app: &App) -> Model {
     println!("✅ Receptor OSC de eventos musicales iniciado.");
 
     println!("📊 Inicializando modelo de datos...");
-    let model = Model::new_with_receiver(config.clone(), osc_rx_for_events, osc_server_instance);
+    let mut model = Model::new_with_clock(config.clone(), osc_rx_for_events, osc_server_instance, clock);
+    model.uniforms_gpu = Some(uniforms_gpu);
+    model.outputs.control = Some(control_window);
+    model.outputs.projection = projection_window;
+    if model.config.recording.enabled {
+        match model.toggle_recording() {
+            Ok(path) => println!("🎙️ Grabando sesión en {}", path.display()),
+            Err(e) => eprintln!("❌ No se pudo iniciar la grabación: {e}"),
+        }
+    }
 
     println!("✅ Modelo de datos inicializado."); // Nuevo mensaje de depuración
     model
 }
 
+/// Coloca una ventana nueva en el monitor `index` (si existe); sin él decide el sistema.
+fn monitor_placement(monitors: &[nannou::winit::monitor::MonitorHandle], index: Option<usize>) -> nannou::winit::window::WindowBuilder {
+    let builder = nannou::winit::window::WindowBuilder::new();
+    match index.and_then(|index| monitors.get(index)) {
+        Some(monitor) => builder.with_position(monitor.position()),
+        None => builder,
+    }
+}
+
 /// Bucle de actualización que se ejecuta en cada frame.
 /// Realiza limpieza periódica y muestra estadísticas si están activadas.
 fn update(_app: &App, model: &mut Model, _update: Update) {
@@ -109,11 +227,13 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
 
     if model.config.logging.show_performance_stats && 
        frame % model.config.logging.stats_interval_frames as u64 == 0 {
-        // osc_stats no se actualiza automáticamente, pero mostramos el contador de eventos
-        println!("⚡ Frame {}: {} eventos activos | {} notas visuales | OSC: N/A | Conectado: N/A", 
-               frame, 
+        println!("⚡ Frame {}: {} eventos activos | {} notas visuales | OSC: {:.0} msg/s, {} fallos | Conectado: {}",
+               frame,
                model.musical_events.len(),
-               model.visual_notes.len());
+               model.visual_notes.len(),
+               model.frame_meter.messages_per_sec(),
+               model.osc_stats.failed_messages,
+               if model.osc_stats.is_connected { "sí" } else { "no" });
     }
     
     if frame % model.config.performance.cleanup_interval_frames as u64 == 0 {
@@ -135,113 +255,205 @@ use crate::visual::renderer::render_visualizer;
 
 fn view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
-    let win = app.window_rect();
-    draw.background().color(rgba(0.05, 0.05, 0.1, 1.0));
-
-    // Timeline animado: los eventos se desplazan de derecha a izquierda según su tiempo de aparición
-    let now = model.time_info.elapsed_time;
-    let timeline_secs = model.config.visual.timeline_duration;
-    let win_left = win.left();
-    let win_right = win.right();
-    let win_width = win_right - win_left;
-
-    for event in &model.musical_events {
-        // Cada evento debe tener un campo de tiempo de aparición (start_time)
-        let (start_time, duration, y, shape, color, size1, size2, opacity) = match event {
-            crate::events::MusicalEvent::Note { frequency, amplitude, start_time, .. } => {
-                let y = crate::events::map_freq_to_y(*frequency, &model.config.audio, win);
-                let radius = amplitude.abs() * 40.0 + 10.0;
-                // Color según frecuencia (hue mapeado de 0.0 a 360.0 para espectro completo)
-                let min_freq = 20.0;
-                let max_freq = 5000.0;
-                let norm = ((*frequency - min_freq) / (max_freq - min_freq)).clamp(0.0, 1.0);
-                let hue_deg = norm * 360.0; // 0.0 a 360.0 grados
-                let hsv = nannou::color::Hsv::new(hue_deg, 0.85, 1.0);
-                let rgb: nannou::color::Rgb<f32> = nannou::color::Rgb::from(hsv);
-                let (r, g, b) = rgb.into_components();
-                let color = rgba(r, g, b, 0.8);
-                (*start_time, 1.0, y, "ellipse", color, radius, 0.0, 0.8)
-            }
-            crate::events::MusicalEvent::Drone { frequency, amplitude, duration, start_time, .. } => {
-                let y = crate::events::map_freq_to_y(*frequency, &model.config.audio, win);
-                let width = duration.abs() * 60.0 + 30.0;
-                let height = amplitude.abs() * 20.0 + 8.0;
-                // Color verde fijo para drones
-                let color = rgba(0.1, 0.9, 0.3, 0.6);
-                (*start_time, *duration, y, "rect", color, width, height, 0.6)
-            }
-            crate::events::MusicalEvent::Cluster { center_freq, density, amplitude, start_time, duration, .. } => {
-                let y = crate::events::map_freq_to_y(*center_freq, &model.config.audio, win);
-                let height = density.abs() * 80.0 + 20.0;
-                let width = amplitude.abs() * 10.0 + 4.0;
-                // Color violeta fijo para clusters
-                let color = rgba(0.7, 0.2, 0.8, 0.7);
-                (*start_time, *duration, y, "line", color, width, height, 0.7)
-            }
-            _ => { continue; }
-        };
+    let win = frame.rect();
+    update_gpu_uniforms(app, model);
 
-        // Calcular posición X en el timeline (izquierda a derecha)
-        let elapsed = Instant::now().duration_since(start_time).as_secs_f32();
-        if elapsed < 0.0 || elapsed > timeline_secs { continue; } // Solo mostrar eventos activos en el timeline
-        let x = win_left + (elapsed / timeline_secs) * win_width;
-
-        // Fade out visual al final de la vida
-        let fade = if elapsed > duration && duration > 0.0 {
-            let t = ((elapsed - duration) / (timeline_secs - duration)).min(1.0).max(0.0);
-            1.0 - t
-        } else { 1.0 };
-        let final_opacity = opacity * fade;
-        let (r, g, b, _a) = color.into_components();
-        let color = rgba(r, g, b, final_opacity as f32);
-
-        match shape {
-            "ellipse" => {
-                draw.ellipse()
-                    .x_y(x, y)
-                    .radius(size1)
-                    .color(color);
-            }
-            "rect" => {
-                draw.rect()
-                    .x_y(x, y)
-                    .w(size1)
-                    .h(size2)
-                    .color(color);
+    // Timeline: scroll continuo (derecha a izquierda) o por páginas según `model.scroll_mode`
+    // (congelado o desplazado por el historial si `model.transport` lo indica)
+    let timeline = model.timeline_view(win);
+    // Fondo, reglas, carriles, eventos, análisis, rejilla y cabeza de lectura: la misma
+    // escena que se exporta a SVG (tecla E)
+    let options = crate::visual::scene::SceneOptions { transport: true, ..Default::default() };
+    let scene = crate::visual::scene::build_scene(model, win, &timeline, options);
+    scene.draw(&draw);
+
+    draw_overlays(&draw, win, model);
+    draw.to_frame(app, &frame).unwrap();
+}
+
+/// Salida del proyector: solo la escena, sin HUD, depuración ni barra de transporte.
+fn view_projection(app: &App, model: &Model, frame: Frame) {
+    let draw = app.draw();
+    let win = frame.rect();
+    let timeline = model.timeline_view(win);
+    crate::visual::scene::build_scene(model, win, &timeline, Default::default()).draw(&draw);
+    draw.to_frame(app, &frame).unwrap();
+}
+
+/// Ventana del operador: selectores de modo, monitorización, vista previa de la proyección,
+/// HUD y registro de mensajes (ver `visual::outputs`).
+fn view_control(app: &App, model: &Model, frame: Frame) {
+    let draw = app.draw();
+    let win = frame.rect();
+    let [r, g, b, _] = model.theme().background;
+    draw.background().color(rgba(r * 0.6, g * 0.6, b * 0.6, 1.0));
+
+    let layout = crate::visual::outputs::ControlLayout::new(win);
+    let projection_bounds = model.outputs.projection.and_then(|id| app.window(id)).map_or(win, |window| window.rect());
+    crate::visual::scene::draw_shapes(&draw, &crate::visual::outputs::control_shapes(model, &layout, projection_bounds));
+    if model.config.visual.show_debug {
+        crate::visual::renderer::draw_debug_info(&draw, &layout.monitor, model);
+    }
+    if model.show_meters || model.outputs.control_mode == DisplayMode::Analysis {
+        let (readings, history) = model.loudness_snapshot();
+        crate::visual::meters::draw_loudness_panel(&draw, layout.monitor, &readings, &history, &model.config.meters);
+    }
+    draw.to_frame(app, &frame).unwrap();
+}
+
+/// Características globales del audio: reaccionan fondo, tamaños y shaders.
+fn update_gpu_uniforms(app: &App, model: &Model) {
+    if model.config.features.enabled {
+        if let Some(gpu) = &model.uniforms_gpu {
+            gpu.update(app.main_window().queue(), model.feature_bus.uniforms());
+        }
+    }
+}
+
+/// Depuración, HUD y sonoridad sobre la escena de la ventana única.
+fn draw_overlays(draw: &Draw, win: Rect, model: &Model) {
+    // Información de depuración (tecla D): modos, análisis y sincronización de reloj
+    if model.config.visual.show_debug {
+        crate::visual::renderer::draw_debug_info(draw, &win, model);
+    }
+    // HUD de rendimiento: FPS, caudal OSC, eventos por tipo y memoria
+    if model.show_hud {
+        crate::visual::scene::draw_shapes(draw, &crate::visual::hud::hud_shapes(win, model));
+    }
+
+    // Panel de sonoridad EBU R128 (tecla L; siempre visible en modo Análisis)
+    if model.show_meters || model.display_mode == DisplayMode::Analysis {
+        let (readings, history) = model.loudness_snapshot();
+        crate::visual::meters::draw_loudness_panel(draw, win, &readings, &history, &model.config.meters);
+    }
+}
+
+/// Maneja eventos de entrada como teclado o ratón.
+/// Actualmente reacciona a eventos de teclado y delega su manejo.
+fn event(app: &App, model: &mut Model, event: Event) {
+    if let Event::WindowEvent { id, simple: Some(window_event) } = event {
+        let Some(window_rect) = app.window(id).map(|window| window.rect()) else { return };
+        // Zona de la línea de tiempo de la ventana y su modo: en la de control, la monitorización
+        let role = model.outputs.role(id);
+        let (win, mode) = match role {
+            WindowRole::Control => (ControlLayout::new(window_rect).monitor, model.outputs.control_mode),
+            _ => (window_rect, model.display_mode),
+        };
+        let inside = win.contains(app.mouse.position());
+        match window_event {
+            WindowEvent::KeyPressed(key) => handle_key_pressed(app, model, key),
+            WindowEvent::MousePressed(MouseButton::Left) => {
+                let selected = role == WindowRole::Control && handle_mode_selector(app, model, window_rect);
+                if !selected && inside {
+                    handle_mouse_pressed(app, model, win, mode);
+                }
             }
-            "line" => {
-                draw.line()
-                    .start(pt2(x - size1, y - size2/2.0))
-                    .end(pt2(x + size1, y + size2/2.0))
-                    .color(color)
-                    .weight(size1);
+            WindowEvent::MouseReleased(MouseButton::Left) => {
+                model.transport.drag_anchor = None;
+                model.viewport.pan_anchor = None;
             }
+            WindowEvent::MouseMoved(pos) => handle_mouse_dragged(model, win, pos),
+            WindowEvent::MouseWheel(delta, _) if inside => handle_mouse_wheel(app, model, win, delta),
             _ => {}
         }
     }
+}
 
-    draw.to_frame(app, &frame).unwrap();
+/// Clic en un selector de modo de la ventana de control. Devuelve `false` si no había ninguno.
+fn handle_mode_selector(app: &App, model: &mut Model, window_rect: Rect) -> bool {
+    match ControlLayout::new(window_rect).button_at(app.mouse.position()) {
+        Some((ModeTarget::Projection, mode)) => {
+            model.set_display_mode(mode);
+            println!("📽️ Proyección: {}", mode.label());
+            true
+        }
+        Some((ModeTarget::Monitor, mode)) => {
+            model.outputs.control_mode = mode;
+            println!("🖥️ Monitor: {}", mode.label());
+            true
+        }
+        None => false,
+    }
 }
 
-/// Maneja eventos de entrada como teclado o ratón.
-/// Actualmente reacciona a eventos de teclado y delega su manejo.
-fn event(_app: &App, model: &mut Model, event: Event) {
-    if let Event::WindowEvent { simple: Some(window_event), .. } = event {
-        if let KeyPressed(key) = window_event {
-            handle_key_pressed(model, key);
+/// Clic en la barra de navegación: salta a ese instante de la sesión.
+/// Clic en la cabecera de un carril: lo pliega o despliega.
+/// Clic en el resto de la ventana: empieza a arrastrar la línea de tiempo.
+fn handle_mouse_pressed(app: &App, model: &mut Model, win: Rect, mode: DisplayMode) {
+    let mouse = app.mouse.position();
+    let bar = crate::visual::timeline::transport_bar_rect(win);
+    if model.is_frozen() && mouse.y <= bar.top() + 6.0 {
+        let fraction = ((mouse.x - bar.left()) / bar.w()).clamp(0.0, 1.0);
+        model.scrub_to(fraction * model.time_info.elapsed_time);
+        return;
+    }
+    // Clic en el nombre de un carril: plegarlo o desplegarlo
+    if let (Some(pane), _) = crate::visual::scene::panes(win, mode) {
+        if let Some(index) = model.lane_layout.header_at(pane, mouse) {
+            model.lane_layout.toggle_collapsed(index);
+            return;
+        }
+    }
+    model.transport.drag_anchor = Some((mouse.x, model.display_time()));
+    let (log_min, log_max) = model.viewport.pitch_target();
+    model.viewport.pan_anchor = Some((mouse.y, log_min, log_max));
+}
+
+/// Arrastrar hacia la derecha muestra momentos anteriores y arrastrar en vertical
+/// desplaza el rango de alturas (el contenido sigue al ratón en ambos ejes).
+fn handle_mouse_dragged(model: &mut Model, win: Rect, mouse: Point2) {
+    if let Some((anchor_x, anchor_time)) = model.transport.drag_anchor {
+        let seconds_per_pixel = model.timeline_view(win).seconds_per_pixel();
+        let target = anchor_time - (mouse.x - anchor_x) * seconds_per_pixel;
+        if (mouse.x - anchor_x).abs() > 2.0 {
+            model.scrub_to(target);
+        }
+    }
+    if let Some((anchor_y, log_min, log_max)) = model.viewport.pan_anchor {
+        if (mouse.y - anchor_y).abs() > 2.0 {
+            model.viewport.drag_pitch(log_min, log_max, (mouse.y - anchor_y) / win.h().max(1.0));
+        }
+    }
+}
+
+/// Rueda vertical: zoom temporal (con Mayús, zoom de alturas centrado en el ratón).
+/// Rueda horizontal: recorre el historial en pasos de `scroll.scrub_step` segundos.
+fn handle_mouse_wheel(app: &App, model: &mut Model, win: Rect, delta: MouseScrollDelta) {
+    let (dx, dy) = match delta {
+        MouseScrollDelta::LineDelta(x, y) => (x, y),
+        MouseScrollDelta::PixelDelta(pos) => (pos.x as f32 / 20.0, pos.y as f32 / 20.0),
+    };
+    if dx != 0.0 {
+        model.scrub_by(dx * model.config.scroll.scrub_step);
+    }
+    if dy != 0.0 {
+        if app.keys.mods.shift() {
+            let (freq_min, freq_max) = model.viewport.freq_range();
+            let fraction = ((app.mouse.y - (win.bottom() + 50.0)) / (win.h() - 100.0).max(1.0)).clamp(0.0, 1.0);
+            let anchor = (freq_min.log2() + fraction * (freq_max.log2() - freq_min.log2())).exp2();
+            model.viewport.zoom_pitch(dy, Some(anchor));
+        } else {
+            model.viewport.zoom_time(dy);
         }
     }
 }
 
 /// Procesa pulsaciones de teclado individuales para controlar la visualización,
 /// alternar modos de scroll, cambiar modos visuales, mostrar ayuda o cerrar la app.
-fn handle_key_pressed(model: &mut Model, key: Key) {
+fn handle_key_pressed(app: &App, model: &mut Model, key: Key) {
     match key {
         Key::S => {
             model.toggle_scroll_mode();
             println!("🔄 Modo de scroll cambiado a: {:?}", model.get_scroll_mode());
         }
         
+        // Con la vista congelada, las flechas recorren el historial en lugar de cambiar la velocidad
+        Key::Left if model.is_frozen() => {
+            model.scrub_by(-model.config.scroll.scrub_step);
+        }
+        Key::Right if model.is_frozen() => {
+            model.scrub_by(model.config.scroll.scrub_step);
+        }
         Key::Left => {
             let new_speed = (model.get_scroll_speed() - 20.0).max(0.0);
             model.set_scroll_speed(new_speed);
@@ -252,6 +464,31 @@ fn handle_key_pressed(model: &mut Model, key: Key) {
             model.set_scroll_speed(new_speed);
             println!("➡️  Velocidad de scroll: {new_speed:.0} px/s");
         }
+
+        Key::Space => {
+            model.toggle_freeze();
+            println!("{}", if model.is_frozen() { "⏸ Vista congelada (los eventos se siguen registrando)" } else { "▶️ En directo" });
+        }
+        // Zoom y desplazamiento de la vista
+        Key::Equals | Key::Plus | Key::NumpadAdd => model.viewport.zoom_time(1.0),
+        Key::Minus | Key::NumpadSubtract => model.viewport.zoom_time(-1.0),
+        Key::RBracket => model.viewport.zoom_pitch(1.0, None),
+        Key::LBracket => model.viewport.zoom_pitch(-1.0, None),
+        Key::Up => model.viewport.pan_pitch_steps(1.0),
+        Key::Down => model.viewport.pan_pitch_steps(-1.0),
+        Key::Key0 => {
+            model.viewport.reset();
+            println!("🔍 Vista restablecida");
+        }
+
+        Key::Home => {
+            model.scrub_to(0.0);
+            println!("⏮ Inicio de la sesión");
+        }
+        Key::End => {
+            model.go_live();
+            println!("▶️ En directo");
+        }
         
         Key::M => {
             let next_mode = cycle_display_mode(&model.display_mode);
@@ -279,6 +516,18 @@ fn handle_key_pressed(model: &mut Model, key: Key) {
             model.set_display_mode(DisplayMode::Combined);
             println!("🔄 Modo: Combinado");
         }
+        Key::Key6 => {
+            model.set_display_mode(DisplayMode::Staff);
+            println!("🎼 Modo: Partitura");
+        }
+        Key::Key7 => {
+            model.set_display_mode(DisplayMode::PianoRoll);
+            println!("🎹 Modo: Piano roll");
+        }
+        Key::Key8 => {
+            model.set_display_mode(DisplayMode::Radial);
+            println!("🕰️ Modo: Radial");
+        }
         
         Key::C => {
             model.clear_events();
@@ -289,7 +538,8 @@ fn handle_key_pressed(model: &mut Model, key: Key) {
         Key::D => {
             let show_debug = !model.display_config().visual.show_debug;
             model.set_display_config(show_debug, model.display_config().visual.show_grid);
-            println!("🐛 Debug info: {}", if show_debug { "ON" } else { "OFF" });
+            model.show_hud = show_debug;
+            println!("🐛 Debug info y HUD: {}", if show_debug { "ON" } else { "OFF" });
         }
         
         Key::G => {
@@ -298,21 +548,97 @@ fn handle_key_pressed(model: &mut Model, key: Key) {
             println!("📐 Grid: {}", if show_grid { "ON" } else { "OFF" });
         }
         
+        Key::L => {
+            model.show_meters = !model.show_meters;
+            println!("📏 Medidores de sonoridad: {}", if model.show_meters { "ON" } else { "OFF" });
+        }
+
+        Key::B => {
+            model.show_beat_grid = !model.show_beat_grid;
+            println!("🥁 Rejilla de compases: {}", if model.show_beat_grid { "ON" } else { "OFF" });
+        }
+
+        Key::T => match model.next_theme() {
+            Ok(name) => println!("🎨 Tema: {name}"),
+            Err(e) => eprintln!("❌ No se pudo cargar el tema: {e}"),
+        },
+
+        Key::N => {
+            model.pitch_labels = model.pitch_labels.next();
+            println!("🎼 Regla de alturas en: {}", model.pitch_labels.name());
+        }
+
+        Key::Q => {
+            model.quantize = !model.quantize;
+            println!("🥁 Cuantización a 1/{} de pulso: {}", model.config.tempo.quantize_division, if model.quantize { "ON" } else { "OFF" });
+        }
+
+        // Exportación SVG: vista actual o, con Mayús, la sesión completa
+        Key::E => {
+            let result = if app.keys.mods.shift() {
+                model.export_range_svg(0.0, model.time_info.elapsed_time)
+            } else {
+                model.export_view_svg()
+            };
+            match result {
+                Ok(path) => println!("🖋️ SVG exportado: {}", path.display()),
+                Err(e) => crate::logging::Logger::log_error(&format!("No se pudo exportar el SVG: {e}")),
+            }
+        }
+
+        Key::R => {
+            let was_recording = model.is_recording();
+            match model.toggle_recording() {
+                Ok(path) if was_recording => println!("🎙️ Grabación guardada: {}", path.display()),
+                Ok(path) => println!("🎙️ Grabando sesión en {}", path.display()),
+                Err(e) => crate::logging::Logger::log_error(&format!("Error de grabación: {e}")),
+            }
+        }
+
+        Key::I => {
+            model.reset_loudness();
+            println!("📏 Sonoridad integrada reiniciada");
+        }
+
         Key::H => {
             println!("\n🎹 === CONTROLES DE TECLADO ===");
-            println!("S          - Alternar modo scroll (Fijo/Scrolling)");
-            println!("← →        - Ajustar velocidad de scroll");
-            println!("M          - Cambiar modo de display (Eventos/Análisis/Drones/Cluster/Combinado)");
-            println!("1-5        - Cambiar modo de display (alternativo)");
+            println!("S          - Alternar modo scroll (Continuo/Página)");
+            println!("← →        - Ajustar velocidad de scroll (congelado: recorrer historial)");
+            println!("Espacio    - Congelar vista / volver al directo");
+            println!("Inicio/Fin - Ir al inicio de la sesión / volver al directo");
+            println!("Ratón      - Arrastrar: recorrer historial / desplazar alturas");
+            println!("             Clic en el nombre de un carril: plegar/desplegar");
+            println!("Rueda      - Zoom temporal (Mayús: zoom de alturas; horizontal: historial)");
+            println!("+ -        - Zoom temporal");
+            println!("[ ]        - Zoom de alturas");
+            println!("↑ ↓        - Desplazar rango de alturas");
+            println!("0          - Restablecer zoom y desplazamiento");
+            println!("M          - Cambiar modo de display (Eventos/Análisis/Drones/Cluster/Combinado/Partitura/Piano roll/Radial)");
+            println!("1-8        - Cambiar modo de display (alternativo)");
+            println!("             Con [outputs].projection, siempre el de la proyección;");
+            println!("             en la ventana de control, clic en los selectores de modo");
             println!("C          - Limpiar eventos");
-            println!("D          - Alternar debug info");
+            println!("D          - Alternar debug info y HUD de rendimiento");
             println!("G          - Alternar grid");
+            println!("L          - Mostrar/ocultar medidores de sonoridad (EBU R128)");
+            println!("I          - Reiniciar sonoridad integrada y pico máximo");
+            println!("E          - Exportar la vista a SVG (Mayús+E: sesión completa)");
+            println!("R          - Iniciar/detener la grabación de la sesión (--render para PNG)");
+            println!("B          - Mostrar/ocultar rejilla de compases (/tempo, /beat)");
+            println!("T          - Cambiar de tema visual (themes/*.toml, /theme nombre)");
+            println!("N          - Etiquetas de la regla de alturas (Hz/nota/MIDI)");
+            println!("Q          - Cuantizar eventos a la rejilla de tempo");
             println!("H          - Mostrar esta ayuda");
             println!("ESC        - Salir de la aplicación");
             println!("===============================\n");
         }
         
         Key::Escape => {
+            if model.is_recording() {
+                if let Ok(path) = model.toggle_recording() {
+                    println!("🎙️ Grabación guardada: {}", path.display());
+                }
+            }
             println!("👋 Cerrando visualizador...");
             std::process::exit(0);
         }
@@ -329,6 +655,9 @@ fn cycle_display_mode(current: &DisplayMode) -> DisplayMode {
         DisplayMode::Analysis => DisplayMode::Drones,
         DisplayMode::Drones => DisplayMode::Cluster,
         DisplayMode::Cluster => DisplayMode::Combined,
-        DisplayMode::Combined => DisplayMode::Events,
+        DisplayMode::Combined => DisplayMode::Staff,
+        DisplayMode::Staff => DisplayMode::PianoRoll,
+        DisplayMode::PianoRoll => DisplayMode::Radial,
+        DisplayMode::Radial => DisplayMode::Events,
     }
 }
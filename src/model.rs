@@ -23,6 +23,16 @@ pub enum ScrollMode {
     Page,
 }
 
+impl ScrollMode {
+    /// Interpreta el nombre usado en `config.toml` ("Continuous" / "Page"); por defecto, continuo.
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "page" | "pagina" | "página" => ScrollMode::Page,
+            _ => ScrollMode::Continuous,
+        }
+    }
+}
+
 /// Define el modo de visualización activa (eventos, análisis, drones, etc.).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DisplayMode {
@@ -31,6 +41,86 @@ pub enum DisplayMode {
     Drones,
     Cluster,
     Combined,
+    /// Gran pentagrama con notación tradicional.
+    Staff,
+    /// Piano roll con teclado vertical.
+    PianoRoll,
+    /// Vista polar: el tiempo gira alrededor del centro y la altura es el radio.
+    Radial,
+}
+
+impl DisplayMode {
+    /// Todos los modos, en el orden de las teclas 1-8.
+    pub const ALL: [DisplayMode; 8] = [
+        DisplayMode::Events,
+        DisplayMode::Analysis,
+        DisplayMode::Drones,
+        DisplayMode::Cluster,
+        DisplayMode::Combined,
+        DisplayMode::Staff,
+        DisplayMode::PianoRoll,
+        DisplayMode::Radial,
+    ];
+
+    /// Modo a partir de su nombre en la configuración ("events", "piano_roll", ...).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name.to_ascii_lowercase())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DisplayMode::Events => "events",
+            DisplayMode::Analysis => "analysis",
+            DisplayMode::Drones => "drones",
+            DisplayMode::Cluster => "cluster",
+            DisplayMode::Combined => "combined",
+            DisplayMode::Staff => "staff",
+            DisplayMode::PianoRoll => "piano_roll",
+            DisplayMode::Radial => "radial",
+        }
+    }
+
+    /// Nombre para la interfaz.
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayMode::Events => "Eventos",
+            DisplayMode::Analysis => "Análisis",
+            DisplayMode::Drones => "Drones",
+            DisplayMode::Cluster => "Cluster",
+            DisplayMode::Combined => "Combinado",
+            DisplayMode::Staff => "Partitura",
+            DisplayMode::PianoRoll => "Piano roll",
+            DisplayMode::Radial => "Radial",
+        }
+    }
+
+    /// Notas (fijas y abiertas).
+    pub fn shows_notes(self) -> bool {
+        matches!(self, DisplayMode::Events | DisplayMode::Combined)
+    }
+
+    pub fn shows_drones(self) -> bool {
+        matches!(self, DisplayMode::Drones | DisplayMode::Combined)
+    }
+
+    pub fn shows_clusters(self) -> bool {
+        matches!(self, DisplayMode::Cluster | DisplayMode::Combined)
+    }
+
+    /// Curvas de análisis (`AnalysisData`, `RealtimeData` y bus de características).
+    /// Las bandas de ruido acompañan a las notas en la vista principal y a los clusters.
+    pub fn shows_noise(self) -> bool {
+        self.shows_notes() || self.shows_clusters()
+    }
+
+    pub fn shows_analysis(self) -> bool {
+        matches!(self, DisplayMode::Analysis | DisplayMode::Combined)
+    }
+
+    /// Alguna vista de eventos sobre el eje de alturas.
+    pub fn shows_events(self) -> bool {
+        self.shows_notes() || self.shows_drones() || self.shows_clusters()
+    }
 }
 
 use crate::config::AppConfig;
@@ -43,9 +133,29 @@ use std::sync::{Arc, Mutex};
 use std::sync::mpsc::Receiver;
 use crate::events::ProcessedOscMessage;
 use crate::midi::MidiController;
+use crate::audio_analyzer::{DetectedEvent, UniversalAudioAnalyzer};
+use crate::visual::lanes::LaneLayout;
+use crate::loudness::LoudnessMeter;
+use crate::feature_bus::FeatureBus;
+use crate::visual::uniforms_gpu::UniformsGpu;
+use crate::visual::viewport::Viewport;
+use crate::clock_sync::ClockSync;
+use crate::tempo_map::TempoMap;
+use crate::event_history::EventHistory;
+use crate::clock::SharedClock;
 use std::collections::HashMap;
 use std::time::Instant;
 
+/// Estado de congelación y navegación por el historial de la sesión.
+/// Mientras `frozen_time` tiene valor, la vista muestra ese instante y los eventos
+/// entrantes se siguen registrando.
+#[derive(Debug, Clone, Default)]
+pub struct Transport {
+    pub frozen_time: Option<f32>,
+    /// Posición del ratón (x) y tiempo mostrado al empezar a arrastrar.
+    pub drag_anchor: Option<(f32, f32)>,
+}
+
 /// Información de tiempo del sistema, usada para sincronización visual y lógica.
 #[derive(Debug)]
 pub struct TimeInfo {
@@ -77,6 +187,41 @@ pub struct Model {
     pub drone_events: Vec<crate::events::MusicalEvent>,
     pub current_realtime_data: Option<RealtimeData>,
     pub audio_visual_mapping: AirportVisualMapper,
+    pub audio_analyzer: Option<UniversalAudioAnalyzer>,
+    pub detected_rx: Option<Receiver<DetectedEvent>>,
+    pub lane_layout: LaneLayout,
+    pub loudness_meter: LoudnessMeter,
+    pub show_meters: bool,
+    pub feature_bus: FeatureBus,
+    pub uniforms_gpu: Option<UniformsGpu>,
+    pub transport: Transport,
+    pub viewport: Viewport,
+    pub clock_sync: ClockSync,
+    /// Desfase y latencia medidos con `/viz/sync`, actualizados con cada estimación nueva.
+    pub time_sync: crate::visual::time_sync::TimeSync,
+    pub tempo_map: TempoMap,
+    pub show_beat_grid: bool,
+    pub quantize: bool,
+    pub history: EventHistory,
+    /// Reloj del que se obtiene la hora (tiempo real, manual u offline).
+    pub clock: SharedClock,
+    /// Grabación en curso de los mensajes OSC recibidos (tecla R).
+    pub recorder: Option<crate::session_recording::SessionRecorder>,
+    /// Etiquetas de la regla de alturas (tecla N).
+    pub pitch_labels: crate::visual::rulers::PitchLabelMode,
+    /// HUD de rendimiento (tecla D).
+    pub show_hud: bool,
+    pub frame_meter: crate::visual::hud::FrameMeter,
+    /// Duración máxima de los eventos activos (para buscar los que aún suenan en un intervalo).
+    pub live_max_duration: f32,
+    /// Temas visuales disponibles y tema activo (tecla T, `/theme`).
+    pub themes: crate::visual::styles::ThemeLibrary,
+    /// Capa de partículas (ráfagas en ataques, estelas en drones) con semilla fija.
+    pub particles: crate::visual::particles::ParticleSystem,
+    /// Ventanas de proyección y de control, con el modo propio de la vista de monitorización.
+    pub outputs: crate::visual::outputs::Outputs,
+    /// Últimos mensajes OSC recibidos (registro de la ventana de control).
+    pub message_log: crate::visual::outputs::MessageLog,
 }
 
 impl Model {
@@ -2,17 +2,52 @@ use super::{DisplayMode, AppConfig};
 use std::sync::{mpsc::Receiver, Arc, Mutex};
 use crate::osc_server::OscServer;
 use crate::events::ProcessedOscMessage;
+use crate::audio_analyzer::DetectedEvent;
+use crate::visual::VisualNote;
 
 impl Model {
     pub fn new_with_receiver(
         config: AppConfig,
         osc_rx: Receiver<ProcessedOscMessage>,
         osc_server_handle: Arc<Mutex<OscServer>>,
+    ) -> Self {
+        Self::new_with_clock(config, osc_rx, osc_server_handle, crate::clock::real_time())
+    }
+
+    /// Igual que `new_with_receiver`, tomando la hora de `clock` (p. ej. un reloj manual en
+    /// pruebas o uno de paso fijo para render offline).
+    pub fn new_with_clock(
+        config: AppConfig,
+        osc_rx: Receiver<ProcessedOscMessage>,
+        osc_server_handle: Arc<Mutex<OscServer>>,
+        clock: crate::clock::SharedClock,
     ) -> Self {
         use crate::visual::shader_manager::ShaderManager;
         use crate::osc_server::OscServerStats;
         use std::collections::HashMap;
-        use std::time::Instant;
+
+        let now = clock.now();
+        let (audio_analyzer, detected_rx) = if config.audio.enable_input_capture {
+            match crate::audio_analyzer::UniversalAudioAnalyzer::new(config.analysis.clone()) {
+                Ok((analyzer, rx)) => (Some(analyzer), Some(rx)),
+                Err(e) => {
+                    crate::logging::Logger::log_error(&format!("No se pudo iniciar el análisis de audio: {e}"));
+                    (None, None)
+                }
+            }
+        } else {
+            (None, None)
+        };
+        if let Some(meter) = audio_analyzer.as_ref().and_then(|a| a.loudness()) {
+            if let Ok(mut meter) = meter.lock() {
+                meter.set_history_secs(config.meters.history_seconds);
+            }
+        }
+        let midi_lanes = if config.lanes.include_midi { config.midi.channel_instruments.clone() } else { Vec::new() };
+        let lane_layout = crate::visual::lanes::LaneLayout::from_config(
+            &config.lanes,
+            audio_analyzer.as_ref().map(|a| a.channel_labels().to_vec()).unwrap_or_default().into_iter().chain(midi_lanes),
+        );
 
         Self {
             config: config.clone(),
@@ -20,8 +55,8 @@ impl Model {
             visual_notes: Vec::new(),
             musical_events: Vec::new(),
             time_info: crate::model::TimeInfo {
-                start_time: Instant::now(),
-                last_update_time: Instant::now(),
+                start_time: now,
+                last_update_time: now,
                 elapsed_time: 0.0,
                 frame_counter: 0,
             },
@@ -30,26 +65,102 @@ impl Model {
             osc_stats: OscServerStats::default(),
             midi_controller: None,
             active_notes: HashMap::new(),
-            scroll_mode: super::ScrollMode::Continuous,
+            scroll_mode: super::ScrollMode::from_name(&config.scroll.mode),
             display_mode: super::DisplayMode::Events,
             current_analysis_data: (0.0, 0.0, 0.0),
-            scroll_speed: 100.0,
+            scroll_speed: config.scroll.scroll_speed,
             notes: Vec::new(),
             drone_events: Vec::new(),
             current_realtime_data: None,
             audio_visual_mapping: crate::visual::audio_visual_mapping::AirportVisualMapper::new(
                 config.airport_visual.clone(),
             ),
+            audio_analyzer,
+            detected_rx,
+            lane_layout,
+            loudness_meter: crate::loudness::LoudnessMeter::new(48000, 1, config.meters.history_seconds),
+            show_meters: config.meters.show_on_start,
+            feature_bus: crate::feature_bus::FeatureBus::new(config.features.clone()),
+            uniforms_gpu: None,
+            transport: super::Transport::default(),
+            viewport: crate::visual::viewport::Viewport::new(config.view.clone(), &config.audio),
+            clock_sync: crate::clock_sync::ClockSync::new(config.sync.clone()),
+            time_sync: {
+                let mut time_sync = crate::visual::time_sync::TimeSync::new();
+                time_sync.set_origin(now);
+                time_sync
+            },
+            tempo_map: crate::tempo_map::TempoMap::new(),
+            show_beat_grid: config.tempo.show_grid,
+            quantize: config.tempo.quantize,
+            history: crate::event_history::EventHistory::new(config.retention.history_max_events),
+            clock,
+            recorder: None,
+            pitch_labels: crate::visual::rulers::PitchLabelMode::from_name(&config.view.pitch_labels),
+            show_hud: config.visual.show_debug || config.visual.show_fps,
+            frame_meter: crate::visual::hud::FrameMeter::default(),
+            live_max_duration: 0.0,
+            themes: crate::visual::styles::ThemeLibrary::new(&config.themes, &config.visual.theme),
+            particles: crate::visual::particles::ParticleSystem::from_config(&config.particles),
+            outputs: crate::visual::outputs::Outputs::from_config(&config.outputs),
+            message_log: crate::visual::outputs::MessageLog::new(config.outputs.log_lines),
         }
     }
     pub fn update(&mut self) {
+        let now = self.clock.now();
+        let dt = now.duration_since(self.time_info.last_update_time).as_secs_f32();
+        self.time_info.last_update_time = now;
+        self.time_info.elapsed_time = now.duration_since(self.time_info.start_time).as_secs_f32();
+
         // Consumir todos los eventos OSC recibidos y agregarlos al vector musical_events
+        let mut received = 0;
         loop {
             match self.osc_rx.try_recv() {
-                Ok(processed_msg) => {
+                Ok(mut processed_msg) => {
+                    received += 1;
+                    let arrival = processed_msg.timestamp;
+                    // Los bundles llevan la hora de sclang: se sitúan en el reloj local con el desfase medido
+                    if let Some(at) = processed_msg.remote_time.and_then(|remote| self.clock_sync.remote_to_instant(remote, self.time_info.start_time)) {
+                        processed_msg.timestamp = at;
+                    }
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        let time = processed_msg.timestamp.saturating_duration_since(self.time_info.start_time).as_secs_f64();
+                        recorder.record(time, &processed_msg.addr, &processed_msg.args);
+                    }
+                    let time = processed_msg.timestamp.saturating_duration_since(self.time_info.start_time).as_secs_f32();
+                    self.message_log.push(time, &processed_msg.addr, &processed_msg.args);
                     let event_opt = match processed_msg.addr.as_str() {
+                        // Nota abierta: `/note_on id freq amp` con id entero (la forma de duración fija
+                        // `/note_on freq amp dur` lleva la frecuencia como float); `/note_on_id` es un alias
+                        "/note_on" if matches!(processed_msg.args.first(), Some(nannou_osc::Type::Int(_))) => {
+                            self.note_on_from_args(&processed_msg.args, processed_msg.timestamp);
+                            None
+                        }
+                        "/note_on_id" => {
+                            self.note_on_from_args(&processed_msg.args, processed_msg.timestamp);
+                            None
+                        }
+                        "/note_set" => {
+                            match &processed_msg.args[..] {
+                                [id, nannou_osc::Type::String(param), value] => {
+                                    match (osc_arg_f32(id), osc_arg_f32(value)) {
+                                        (Some(id), Some(value)) => self.note_set(id as u32, param, value, processed_msg.timestamp),
+                                        _ => crate::logging::Logger::log_warn("Argumentos inválidos para /note_set id param value"),
+                                    }
+                                }
+                                _ => crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para /note_set: esperado 3, recibido {}", processed_msg.args.len())),
+                            }
+                            None
+                        }
+                        "/note_off" => {
+                            match processed_msg.args.first().and_then(osc_arg_f32) {
+                                Some(id) => self.note_off(id as u32, processed_msg.timestamp),
+                                None => crate::logging::Logger::log_warn("Argumento inválido para /note_off id"),
+                            }
+                            None
+                        }
                         "/note_on" => {
-                            if processed_msg.args.len() == 3 {
+                            if matches!(processed_msg.args.len(), 3 | 4) {
                                 let freq = match &processed_msg.args[0] {
                                     nannou_osc::Type::Float(f) => *f,
                                     nannou_osc::Type::Int(i) => *i as f32,
@@ -65,15 +176,170 @@ impl Model {
                                     nannou_osc::Type::Int(i) => *i as f32,
                                     _ => { crate::logging::Logger::log_warn("Argumento de duración inválido para /note_on"); return; },
                                 };
+                                // Cuarto argumento opcional: instrumento (carril)
+                                let instrument = match processed_msg.args.get(3) {
+                                    Some(nannou_osc::Type::String(name)) => name.clone(),
+                                    _ => "default".to_string(),
+                                };
                                 Some(crate::events::MusicalEvent::Note {
                                     frequency: freq,
                                     amplitude: amp,
                                     duration: dur,
-                                    instrument: "default".to_string(),
+                                    instrument,
                                     start_time: processed_msg.timestamp,
                                 })
                             } else {
-                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para /note_on: esperado 3, recibido {}", processed_msg.args.len()));
+                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para /note_on: esperado 3 o 4, recibido {}", processed_msg.args.len()));
+                                None
+                            }
+                        }
+                        "/analysis_data" | "/analysis" => {
+                            let values: Vec<f32> = processed_msg.args.iter().filter_map(osc_arg_f32).collect();
+                            if let [amplitude, brightness, noisy, ..] = values[..] {
+                                self.current_analysis_data = (amplitude, brightness, noisy);
+                                self.feature_bus.push_analysis(amplitude, brightness, noisy);
+                                self.loudness_meter.feed_amplitude(amplitude, processed_msg.timestamp);
+                            } else {
+                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para {}: esperado 3, recibido {}", processed_msg.addr, processed_msg.args.len()));
+                            }
+                            None
+                        }
+                        "/realtime" => {
+                            let values: Vec<f32> = processed_msg.args.iter().filter_map(osc_arg_f32).collect();
+                            if let [pitch, amplitude, centroid, ..] = values[..] {
+                                let data = crate::events::RealtimeData { pitch, amplitude, centroid, timestamp: processed_msg.timestamp };
+                                self.feature_bus.push_realtime(data.clone());
+                                self.current_realtime_data = Some(data);
+                            } else {
+                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para /realtime: esperado 3, recibido {}", processed_msg.args.len()));
+                            }
+                            None
+                        }
+                        "/tempo" => {
+                            let values: Vec<f32> = processed_msg.args.iter().filter_map(osc_arg_f32).collect();
+                            if let &[bpm, ref rest @ ..] = values.as_slice() {
+                                let beats_per_bar = rest.first().copied().unwrap_or(4.0);
+                                let time = self.session_time(processed_msg.timestamp);
+                                self.tempo_map.set_tempo(time, bpm, beats_per_bar);
+                                if bpm > 0.0 {
+                                    self.feature_bus.set_beat_period(60.0 / bpm);
+                                }
+                            } else {
+                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para /tempo: esperado 2, recibido {}", processed_msg.args.len()));
+                            }
+                            None
+                        }
+                        "/beat" => {
+                            self.feature_bus.push_beat(processed_msg.timestamp);
+                            if let Some(beat) = processed_msg.args.first().and_then(osc_arg_f32) {
+                                let time = self.session_time(processed_msg.timestamp);
+                                self.tempo_map.mark_beat(time, beat);
+                            }
+                            None
+                        }
+                        "/lane/collapse" => {
+                            match &processed_msg.args[..] {
+                                [nannou_osc::Type::String(name), rest @ ..] => {
+                                    let collapsed = match rest.first().and_then(osc_arg_f32) {
+                                        Some(state) => state != 0.0,
+                                        None => !self.lane_layout.index_of(name).is_some_and(|i| self.lane_layout.lanes[i].collapsed),
+                                    };
+                                    if !self.lane_layout.set_collapsed(name, collapsed) {
+                                        crate::logging::Logger::log_warn(&format!("/lane/collapse: carril desconocido {name}"));
+                                    }
+                                }
+                                _ => crate::logging::Logger::log_warn("Argumentos inválidos para /lane/collapse nombre [0|1]"),
+                            }
+                            None
+                        }
+                        "/theme" => {
+                            match processed_msg.args.first() {
+                                Some(nannou_osc::Type::String(name)) => {
+                                    if let Err(e) = self.set_theme(name) {
+                                        crate::logging::Logger::log_warn(&format!("🎨 {e}"));
+                                    }
+                                }
+                                _ => crate::logging::Logger::log_warn("Argumento inválido para /theme nombre"),
+                            }
+                            None
+                        }
+                        "/viz/sync/reply" => {
+                            let id = match processed_msg.args.first() {
+                                Some(nannou_osc::Type::Int(id)) => Some(*id),
+                                Some(other) => osc_arg_f64(other).map(|id| id as i32),
+                                None => None,
+                            };
+                            let times: Vec<f64> = processed_msg.args.iter().skip(1).filter_map(osc_arg_f64).collect();
+                            // Se admite también la forma `id t1 t2 t3` (t1 devuelto por sclang) y
+                            // `id` a solas dentro de un bundle, cuya marca de tiempo hace de t2 y t3
+                            let remote = match (&times[..], processed_msg.remote_time) {
+                                ([.., t2, t3], _) => Some((*t2, *t3, crate::clock_sync::ClockDomain::Message)),
+                                ([], Some(t)) => Some((t, t, crate::clock_sync::ClockDomain::Bundle)),
+                                _ => None,
+                            };
+                            match (id, remote) {
+                                (Some(id), Some((t2, t3, domain))) => {
+                                    let t4 = arrival.saturating_duration_since(self.time_info.start_time).as_secs_f64();
+                                    if self.clock_sync.handle_reply(id, t2, t3, t4, domain).is_some() {
+                                        if let Some(estimate) = self.clock_sync.estimate() {
+                                            self.time_sync.apply_clock_estimate(&estimate);
+                                        }
+                                    }
+                                }
+                                _ => crate::logging::Logger::log_warn(&format!("Argumentos inválidos para /viz/sync/reply: {:?}", processed_msg.args)),
+                            }
+                            None
+                        }
+                        "/gliss" | "/glissando" => {
+                            let values: Vec<f32> = processed_msg.args.iter().filter_map(osc_arg_f32).collect();
+                            if let &[start_freq, end_freq, amplitude, duration, ref rest @ ..] = values.as_slice() {
+                                Some(crate::events::MusicalEvent::Glissando {
+                                    start_freq,
+                                    end_freq,
+                                    amplitude,
+                                    duration,
+                                    curve: rest.first().copied().unwrap_or(0.0),
+                                    instrument: osc_instrument(&processed_msg.args),
+                                    start_time: processed_msg.timestamp,
+                                })
+                            } else {
+                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para {}: esperado 4 o 5, recibido {}", processed_msg.addr, processed_msg.args.len()));
+                                None
+                            }
+                        }
+                        "/noise" => {
+                            let values: Vec<f32> = processed_msg.args.iter().filter_map(osc_arg_f32).collect();
+                            if let [center_freq, bandwidth, amplitude, duration, ..] = values[..] {
+                                Some(crate::events::MusicalEvent::Noise {
+                                    center_freq,
+                                    bandwidth,
+                                    amplitude,
+                                    duration,
+                                    instrument: osc_instrument(&processed_msg.args),
+                                    start_time: processed_msg.timestamp,
+                                })
+                            } else {
+                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para /noise: esperado 4, recibido {}", processed_msg.args.len()));
+                                None
+                            }
+                        }
+                        "/chord" => {
+                            let values: Vec<f32> = processed_msg.args.iter().filter_map(osc_arg_f32).collect();
+                            if let [amplitude, duration, ref frequencies @ ..] = values[..] {
+                                if frequencies.is_empty() {
+                                    crate::logging::Logger::log_warn("/chord sin alturas: esperado amp dur freq1 freq2 ...");
+                                    None
+                                } else {
+                                    Some(crate::events::MusicalEvent::Chord {
+                                        frequencies: frequencies.to_vec(),
+                                        amplitude,
+                                        duration,
+                                        instrument: osc_instrument(&processed_msg.args),
+                                        start_time: processed_msg.timestamp,
+                                    })
+                                }
+                            } else {
+                                crate::logging::Logger::log_warn(&format!("Número incorrecto de argumentos para /chord: esperado al menos 3, recibido {}", processed_msg.args.len()));
                                 None
                             }
                         }
@@ -81,7 +347,9 @@ impl Model {
                         _ => None
                     };
                     if let Some(event) = event_opt {
-                        self.musical_events.push(event);
+                        self.feed_features_from_event(&event);
+                        self.emit_particles_for(&event);
+                        self.push_event(event);
                     }
                 }
                 Err(std::sync::mpsc::TryRecvError::Empty) => break,
@@ -91,17 +359,550 @@ impl Model {
                 }
             }
         }
+        self.frame_meter.tick(dt, received);
+        // Recarga del tema activo si su archivo ha cambiado
+        match self.themes.poll(self.time_info.elapsed_time) {
+            Ok(true) => crate::logging::Logger::log_info(&format!("🎨 Tema '{}' recargado", self.themes.theme().name)),
+            Ok(false) => {}
+            Err(e) => crate::logging::Logger::log_warn(&format!("🎨 {e}; se mantiene el tema anterior")),
+        }
+        // Estadísticas del hilo OSC (sin bloquear el frame si el servidor tiene el lock)
+        if let Ok(mut server) = self.osc_server_handle.try_lock() {
+            self.osc_stats = server.get_stats();
+        }
+
+        // Eventos detectados en la entrada de audio (uno por canal analizado)
+        if let Some(rx) = &self.detected_rx {
+            let detected: Vec<DetectedEvent> = rx.try_iter().collect();
+            for event in detected {
+                if let crate::audio_analyzer::DetectedEventType::NoiseTexture { freq_center, .. } = event.event_type {
+                    self.feature_bus.push_centroid(freq_center);
+                }
+                if let Some(musical) = self.detected_to_musical(event) {
+                    self.feed_features_from_event(&musical);
+                    self.emit_particles_for(&musical);
+                    self.push_event(musical);
+                }
+            }
+        }
+
+        // Nivel de la entrada capturada (sonoridad momentánea convertida a RMS lineal)
+        if let Some(meter) = self.audio_analyzer.as_ref().and_then(|a| a.loudness()) {
+            if let Ok(meter) = meter.lock() {
+                let momentary = meter.readings().momentary;
+                if momentary.is_finite() {
+                    self.feature_bus.push_rms(10f32.powf((momentary + 0.691) / 20.0));
+                }
+            }
+        }
+
+        let elapsed = self.time_info.elapsed_time;
+        self.feature_bus.update(now, elapsed, dt);
+        self.viewport.update(dt);
+        self.update_particles(elapsed, dt);
+        self.clock_sync.tick(now.duration_since(self.time_info.start_time).as_secs_f64());
+    }
+
+    /// Abre una nota identificada por `id`. Si ya había una con el mismo id, se cierra antes.
+    /// `/note_on id freq amp [instrument]`: valida los argumentos y abre la nota.
+    fn note_on_from_args(&mut self, args: &[nannou_osc::Type], at: std::time::Instant) {
+        let values: Vec<f32> = args.iter().take(3).filter_map(osc_arg_f32).collect();
+        // Cuarto argumento opcional: instrumento (carril), como en la forma de duración fija
+        let instrument = match args.get(3) {
+            None => Some("default"),
+            Some(nannou_osc::Type::String(name)) => Some(name.as_str()),
+            Some(_) => None,
+        };
+        match (&values[..], instrument) {
+            (&[id, freq, amp], Some(instrument)) if args.len() <= 4 => self.note_on(id as u32, freq, amp, instrument, at),
+            _ => crate::logging::Logger::log_warn("Argumentos inválidos para /note_on id freq amp [instrument]"),
+        }
+    }
+
+    pub fn note_on(&mut self, id: u32, frequency: f32, amplitude: f32, instrument: &str, at: std::time::Instant) {
+        self.note_off(id, at);
+        self.feature_bus.push_onset(amplitude.abs());
+        self.lane_layout.observe(instrument, frequency);
+        self.emit_particles_for(&crate::events::MusicalEvent::Note {
+            frequency,
+            amplitude,
+            duration: 0.0,
+            instrument: instrument.to_string(),
+            start_time: at,
+        });
+        self.active_notes.insert(id, VisualNote::open(frequency, amplitude, instrument.to_string(), at));
+    }
+
+    /// Cambia la frecuencia o amplitud de una nota abierta; el cambio queda en su contorno.
+    pub fn note_set(&mut self, id: u32, param: &str, value: f32, at: std::time::Instant) {
+        match self.active_notes.get_mut(&id) {
+            Some(note) => {
+                if !note.set_param(param, value, at) {
+                    crate::logging::Logger::log_warn(&format!("Parámetro desconocido en /note_set: {param}"));
+                }
+            }
+            None => crate::logging::Logger::log_debug(&format!("/note_set para una nota inexistente: {id}")),
+        }
+    }
+
+    /// Cierra la nota `id` en `at` y la pasa a `visual_notes` con su contorno completo.
+    pub fn note_off(&mut self, id: u32, at: std::time::Instant) {
+        if let Some(mut note) = self.active_notes.remove(&id) {
+            note.release(at);
+            self.visual_notes.push(note);
+        }
+    }
+
+    /// Alimenta el bus de características con la llegada de un nuevo evento.
+    fn feed_features_from_event(&mut self, event: &crate::events::MusicalEvent) {
+        use crate::events::MusicalEvent;
+
+        match event {
+            MusicalEvent::Note { amplitude, .. }
+            | MusicalEvent::NoteColored { amplitude, .. }
+            | MusicalEvent::Drone { amplitude, .. }
+            | MusicalEvent::Glissando { amplitude, .. }
+            | MusicalEvent::Chord { amplitude, .. } => self.feature_bus.push_onset(*amplitude),
+            MusicalEvent::Cluster { amplitude, .. } => self.feature_bus.push_onset(*amplitude * 0.5),
+            MusicalEvent::Noise { amplitude, center_freq, .. } => {
+                self.feature_bus.push_onset(*amplitude * 0.5);
+                self.feature_bus.push_centroid(*center_freq);
+            }
+            _ => {}
+        }
+    }
+
+    /// Ráfaga de partículas en el ataque de `event` si su amplitud supera `[particles].onset_threshold`.
+    fn emit_particles_for(&mut self, event: &crate::events::MusicalEvent) {
+        use crate::events::MusicalEvent;
+
+        let config = &self.config.particles;
+        let Some(start) = event.start_time() else { return };
+        let time = self.placement_time_secs(self.session_time(start));
+        let theme = self.themes.theme();
+        let lane = match event {
+            MusicalEvent::Note { instrument, .. }
+            | MusicalEvent::Drone { instrument, .. }
+            | MusicalEvent::Glissando { instrument, .. }
+            | MusicalEvent::Cluster { instrument, .. }
+            | MusicalEvent::Chord { instrument, .. } => self.lane_layout.index_of(instrument),
+            _ => None,
+        };
+        let (amplitude, attacks): (f32, Vec<(f32, crate::visual::scene::Color)>) = match event {
+            MusicalEvent::Note { frequency, amplitude, .. } => (*amplitude, vec![(*frequency, theme.note_color(*frequency, 0.9))]),
+            MusicalEvent::NoteColored { frequency, amplitude, r, g, b, .. } => (*amplitude, vec![(*frequency, [*r, *g, *b, 0.9])]),
+            MusicalEvent::Glissando { start_freq, amplitude, .. } => (*amplitude, vec![(*start_freq, theme.note_color(*start_freq, 0.9))]),
+            MusicalEvent::Cluster { center_freq, amplitude, .. } => (*amplitude, vec![(*center_freq, theme.events.cluster)]),
+            MusicalEvent::Chord { frequencies, amplitude, .. } => {
+                (*amplitude, frequencies.iter().map(|f| (*f, theme.note_color(*f, 0.9))).collect())
+            }
+            _ => return,
+        };
+        let amplitude = amplitude.abs().min(1.0);
+        if !config.enabled || attacks.is_empty() || amplitude < config.onset_threshold {
+            return;
+        }
+        // Las notas de un acorde se reparten la ráfaga
+        let count = ((config.burst_size as f32 * amplitude / attacks.len() as f32).ceil() as usize).max(1);
+        for (freq, color) in attacks {
+            self.particles.emit_burst(crate::visual::particles::Anchor { time, freq, lane }, count, color);
+        }
+    }
+
+    /// Los drones que suenan en `elapsed` desprenden partículas lentas (de media
+    /// `drone_rate · amplitud` por segundo) y el sistema avanza `dt` segundos.
+    fn update_particles(&mut self, elapsed: f32, dt: f32) {
+        if !self.config.particles.enabled {
+            self.particles.clear();
+            return;
+        }
+        let rate = self.config.particles.drone_rate;
+        let color = self.themes.theme().events.drone;
+        let mut sheds = Vec::new();
+        for event in self.live_events_in(elapsed, elapsed) {
+            if let crate::events::MusicalEvent::Drone { frequency, amplitude, duration, start_time, instrument } = event {
+                let start = self.session_time(*start_time);
+                if start <= elapsed && elapsed <= start + duration {
+                    let spread = (duration.abs() * 60.0 + 30.0) / 2.0;
+                    let lane = self.lane_layout.index_of(instrument);
+                    sheds.push((self.placement_time_secs(start), *frequency, lane, amplitude.abs().min(1.0), spread));
+                }
+            }
+        }
+        for (time, freq, lane, amplitude, spread) in sheds {
+            if self.particles.chance() < rate * amplitude * dt {
+                self.particles.shed(crate::visual::particles::Anchor { time, freq, lane }, spread, [color[0], color[1], color[2], 0.8]);
+            }
+        }
+        self.particles.update(dt);
+    }
+
+    /// Convierte un evento detectado por el analizador de audio en un evento musical.
+    /// Los eventos de cada canal llevan como instrumento la etiqueta de su carril.
+    fn detected_to_musical(&self, event: DetectedEvent) -> Option<crate::events::MusicalEvent> {
+        use crate::audio_analyzer::{DetectedEventType, UniversalAudioAnalyzer};
+        use crate::events::MusicalEvent;
+
+        let instrument = match event.channel {
+            Some(ch) => UniversalAudioAnalyzer::label_for_channel(&self.config.analysis, ch),
+            None => "input".to_string(),
+        };
+        // El analizador fecha cada evento con la hora del sistema al detectarlo: se resta la
+        // espera hasta este fotograma para no retrasarlo respecto al resto de la escena.
+        let now = self.clock.now();
+        let wall_now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let age = std::time::Duration::from_secs_f64((wall_now - event.timestamp).clamp(0.0, 60.0));
+        let start_time = now.checked_sub(age).unwrap_or(now);
+        let duration = event.duration as f32;
+
+        match event.event_type {
+            DetectedEventType::Onset { frequency, .. } => Some(MusicalEvent::Note {
+                frequency,
+                amplitude: event.amplitude,
+                duration,
+                instrument,
+                start_time,
+            }),
+            // Un seguimiento de altura que cambia más de un cuarto de tono es un glissando
+            DetectedEventType::PitchTrack { start_freq, end_freq, .. }
+                if start_freq > 0.0 && end_freq > 0.0 && (end_freq / start_freq).log2().abs() * 24.0 > 1.0 =>
+            {
+                Some(MusicalEvent::Glissando {
+                    start_freq,
+                    end_freq,
+                    amplitude: event.amplitude,
+                    duration,
+                    curve: 0.0,
+                    instrument,
+                    start_time,
+                })
+            }
+            DetectedEventType::PitchTrack { start_freq, .. } => Some(MusicalEvent::Note {
+                frequency: start_freq,
+                amplitude: event.amplitude,
+                duration,
+                instrument,
+                start_time,
+            }),
+            DetectedEventType::SpectralCluster { center_freq, spread, density } => Some(MusicalEvent::Cluster {
+                center_freq,
+                freq_width: spread,
+                density: density as f32,
+                amplitude: event.amplitude,
+                duration,
+                instrument,
+                start_time,
+            }),
+            DetectedEventType::NoiseTexture { freq_center, bandwidth, .. } => Some(MusicalEvent::Noise {
+                center_freq: freq_center,
+                bandwidth,
+                amplitude: event.amplitude,
+                duration,
+                instrument,
+                start_time,
+            }),
+            DetectedEventType::Silence => None,
+        }
+    }
+    /// Lecturas de sonoridad y tira de dinámica actuales. Se prefiere la entrada de audio
+    /// capturada; si no hay captura real se usa la amplitud recibida por OSC.
+    pub fn loudness_snapshot(&self) -> (crate::loudness::LoudnessReadings, std::collections::VecDeque<crate::loudness::DynamicsPoint>) {
+        if let Some(meter) = self.audio_analyzer.as_ref().and_then(|a| a.loudness()) {
+            if let Ok(meter) = meter.lock() {
+                return (meter.readings(), meter.history().clone());
+            }
+        }
+        (self.loudness_meter.readings(), self.loudness_meter.history().clone())
+    }
+
+    /// Reinicia la sonoridad integrada, el LRA y el pico máximo de todos los medidores.
+    pub fn reset_loudness(&mut self) {
+        self.loudness_meter.reset_integrated();
+        if let Some(meter) = self.audio_analyzer.as_ref().and_then(|a| a.loudness()) {
+            if let Ok(mut meter) = meter.lock() {
+                meter.reset_integrated();
+            }
+        }
+    }
+
+    /// Aplica la política de retención (`[retention]`): los eventos que terminaron hace más de
+    /// `max_age_secs` fuera de la línea de tiempo visible, y los más antiguos que excedan
+    /// `max_events` o `max_memory_mb`, pasan al historial compacto.
+    pub fn cleanup_expired_events(&mut self) {
+        let retention = self.config.retention.clone();
+        let now = self.time_info.elapsed_time;
+        let cutoff = now - self.live_visible_span() - retention.max_age_secs.max(0.0);
+        let start = self.time_info.start_time;
+
+        let mut kept = Vec::with_capacity(self.musical_events.len());
+        let mut expired = Vec::new();
+        // Los datos sin tiempo no se pueden situar en la línea de tiempo: se conservan tal cual
+        let mut untimed = Vec::new();
+        for event in self.musical_events.drain(..) {
+            match event.start_time() {
+                Some(t) => {
+                    let t = t.saturating_duration_since(start).as_secs_f32();
+                    if t + event.duration() < cutoff {
+                        expired.push((t, event));
+                    } else {
+                        kept.push((t, event));
+                    }
+                }
+                None => untimed.push(event),
+            }
+        }
+
+        // Límites de cantidad y memoria: se archivan primero los más antiguos
+        let max_bytes = (retention.max_memory_mb.max(0.0) * 1024.0 * 1024.0) as usize;
+        let mut bytes: usize = kept.iter().map(|(_, e)| e.approx_size()).sum();
+        if kept.len() > retention.max_events || bytes > max_bytes {
+            kept.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut excess = 0;
+            while excess < kept.len() && (kept.len() - excess > retention.max_events || bytes > max_bytes) {
+                bytes -= kept[excess].1.approx_size();
+                excess += 1;
+            }
+            expired.extend(kept.drain(..excess));
+        }
+
+        // Notas abiertas ya cerradas: se archivan como notas de duración fija (sin contorno)
+        let (old_notes, recent_notes): (Vec<VisualNote>, Vec<VisualNote>) = self
+            .visual_notes
+            .drain(..)
+            .partition(|n| n.end_time.saturating_duration_since(start).as_secs_f32() < cutoff);
+        self.visual_notes = recent_notes;
+        for note in old_notes {
+            let t = note.start_time.saturating_duration_since(start).as_secs_f32();
+            let event = crate::events::MusicalEvent::Note {
+                frequency: note.frequency,
+                amplitude: note.amplitude,
+                duration: note.duration,
+                instrument: note.instrument,
+                start_time: note.start_time,
+            };
+            expired.push((t, event));
+        }
+
+        expired.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (t, event) in &expired {
+            self.history.push(event, *t);
+        }
+        self.musical_events = untimed.into_iter().chain(kept.into_iter().map(|(_, e)| e)).collect();
+        self.live_max_duration = self.musical_events.iter().map(|e| e.duration()).fold(0.0, f32::max);
+    }
+
+    /// Tema visual activo.
+    pub fn theme(&self) -> &crate::visual::styles::VisualTheme {
+        self.themes.theme()
+    }
+
+    pub fn set_theme(&mut self, name: &str) -> crate::errors::VisualizerResult<()> {
+        self.themes.select(name)?;
+        crate::logging::Logger::log_info(&format!("🎨 Tema: {name}"));
+        Ok(())
+    }
+
+    /// Activa el siguiente tema disponible (integrados y archivos de `[themes].directory`).
+    pub fn next_theme(&mut self) -> crate::errors::VisualizerResult<String> {
+        self.themes.select_next()
+    }
+
+    /// Añade un evento manteniendo `musical_events` ordenado por inicio. Normalmente llegan en
+    /// orden y basta con añadirlo al final; los que llegan tarde se insertan en su sitio.
+    pub fn push_event(&mut self, event: crate::events::MusicalEvent) {
+        use crate::events::MusicalEvent;
+        match &event {
+            MusicalEvent::Note { frequency, instrument, .. }
+            | MusicalEvent::Drone { frequency, instrument, .. }
+            | MusicalEvent::Cluster { center_freq: frequency, instrument, .. }
+            | MusicalEvent::Noise { center_freq: frequency, instrument, .. } => self.lane_layout.observe(instrument, *frequency),
+            MusicalEvent::Glissando { start_freq, end_freq, instrument, .. } => {
+                self.lane_layout.observe(instrument, *start_freq);
+                self.lane_layout.observe(instrument, *end_freq);
+            }
+            MusicalEvent::Chord { frequencies, instrument, .. } => {
+                for frequency in frequencies {
+                    self.lane_layout.observe(instrument, *frequency);
+                }
+            }
+            _ => {}
+        }
+        self.live_max_duration = self.live_max_duration.max(event.duration());
+        let start = event.start_time();
+        if self.musical_events.last().is_some_and(|last| last.start_time() > start) {
+            let index = self.musical_events.partition_point(|e| e.start_time() <= start);
+            self.musical_events.insert(index, event);
+        } else {
+            self.musical_events.push(event);
+        }
+    }
+
+    /// Eventos activos que suenan en algún momento de `[from, to]` (segundos de sesión),
+    /// por búsqueda binaria sobre `musical_events`. Incluye un margen para la cuantización.
+    pub fn live_events_in(&self, from: f32, to: f32) -> &[crate::events::MusicalEvent] {
+        let margin = if self.quantize { 1.0 } else { 0.0 };
+        let session_time = |e: &crate::events::MusicalEvent| e.start_time().map_or(f32::MIN, |t| self.session_time(t));
+        let first = self.musical_events.partition_point(|e| session_time(e) < from - self.live_max_duration - margin);
+        let last = self.musical_events.partition_point(|e| session_time(e) <= to + margin);
+        &self.musical_events[first..last.max(first)]
+    }
+
+    /// Segundos visibles en directo: el mayor de la duración de página y del ancho de ventana
+    /// configurado recorrido a la velocidad de scroll, según el zoom actual.
+    fn live_visible_span(&self) -> f32 {
+        let continuous = self.config.window.width as f32 / self.scroll_speed.max(1.0);
+        continuous.max(self.config.visual.timeline_duration) / self.viewport.time_zoom().max(0.01)
+    }
+
+    /// Eventos archivados que suenan en `[from, to]` (segundos de sesión), reconstruidos
+    /// para dibujarlos al navegar por el historial.
+    pub fn history_events_in(&self, from: f32, to: f32) -> Vec<crate::events::MusicalEvent> {
+        self.history
+            .range(from, to)
+            .map(|e| self.history.to_musical(e, self.time_info.start_time))
+            .collect()
+    }
+    pub fn toggle_scroll_mode(&mut self) {
+        self.scroll_mode = match self.scroll_mode {
+            super::ScrollMode::Continuous => super::ScrollMode::Page,
+            super::ScrollMode::Page => super::ScrollMode::Continuous,
+        };
+    }
+
+    /// Tiempo de sesión que se está mostrando: el instante congelado o el tiempo real.
+    pub fn display_time(&self) -> f32 {
+        self.transport.frozen_time.unwrap_or(self.time_info.elapsed_time)
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.transport.frozen_time.is_some()
+    }
+
+    /// Congela la vista en el instante actual o vuelve al directo si ya estaba congelada.
+    pub fn toggle_freeze(&mut self) {
+        if self.is_frozen() {
+            self.go_live();
+        } else {
+            self.transport.frozen_time = Some(self.time_info.elapsed_time);
+        }
+    }
+
+    /// Desplaza el instante mostrado `delta` segundos (congela la vista si estaba en directo).
+    pub fn scrub_by(&mut self, delta: f32) {
+        self.scrub_to(self.display_time() + delta);
+    }
+
+    /// Muestra el instante `time` de la sesión, limitado entre el inicio y el presente.
+    pub fn scrub_to(&mut self, time: f32) {
+        self.transport.frozen_time = Some(time.clamp(0.0, self.time_info.elapsed_time));
+    }
+
+    /// Vuelve a la vista en directo.
+    pub fn go_live(&mut self) {
+        self.transport.frozen_time = None;
+        self.transport.drag_anchor = None;
+    }
+
+    /// Correspondencia tiempo → pantalla para el instante mostrado y el modo de scroll actual.
+    pub fn timeline_view(&self, win: nannou::geom::Rect) -> crate::visual::timeline::TimelineView {
+        crate::visual::timeline::TimelineView::new(
+            self.scroll_mode,
+            self.display_time(),
+            win,
+            self.scroll_speed * self.viewport.time_zoom(),
+            self.config.visual.timeline_duration / self.viewport.time_zoom(),
+            &self.config.scroll,
+        )
+    }
+
+    /// Tiempo de sesión en el que se dibuja un evento que empezó en `instant`:
+    /// ajustado a la rejilla de tempo si la cuantización está activa.
+    pub fn placement_time(&self, instant: std::time::Instant) -> f32 {
+        self.placement_time_secs(self.session_time(instant))
+    }
+
+    /// Igual que `placement_time` para un tiempo de sesión en segundos.
+    pub fn placement_time_secs(&self, t: f32) -> f32 {
+        if self.quantize {
+            self.tempo_map.quantize(t, self.config.tempo.quantize_division)
+        } else {
+            t
+        }
+    }
+
+    /// Segundos transcurridos entre el inicio de la sesión y `instant`.
+    pub fn session_time(&self, instant: std::time::Instant) -> f32 {
+        instant.saturating_duration_since(self.time_info.start_time).as_secs_f32()
+    }
+
+    /// Exporta a SVG la vista actual (instante mostrado, zoom y modo de display) con el
+    /// tamaño de `[export]`. Devuelve la ruta del archivo escrito.
+    pub fn export_view_svg(&self) -> crate::errors::VisualizerResult<std::path::PathBuf> {
+        let bounds = self.export_bounds();
+        let timeline = self.timeline_view(bounds);
+        self.write_scene_svg(bounds, &timeline, &format!("vista_{:.1}s", self.display_time()))
+    }
+
+    /// Exporta a SVG el intervalo `[from, to]` de la sesión (segundos) a lo ancho del lienzo,
+    /// incluyendo los eventos ya archivados en el historial.
+    pub fn export_range_svg(&self, from: f32, to: f32) -> crate::errors::VisualizerResult<std::path::PathBuf> {
+        let bounds = self.export_bounds();
+        let timeline = crate::visual::timeline::TimelineView::range(from, to.max(from + 0.1), bounds);
+        self.write_scene_svg(bounds, &timeline, &format!("rango_{from:.1}-{to:.1}s"))
+    }
+
+    /// Empieza a grabar los mensajes OSC en un archivo nuevo de `[recording].directory`
+    /// o, si ya se estaba grabando, cierra la grabación. Devuelve la ruta del archivo.
+    pub fn toggle_recording(&mut self) -> crate::errors::VisualizerResult<std::path::PathBuf> {
+        if let Some(recorder) = self.recorder.take() {
+            return recorder.finish();
+        }
+        let name = format!("sesion_{}.tsv", chrono::Local::now().format("%Y%m%d_%H%M%S"));
+        let path = std::path::Path::new(&self.config.recording.directory).join(name);
+        let recorder = crate::session_recording::SessionRecorder::create(&path)?;
+        self.recorder = Some(recorder);
+        Ok(path)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    fn export_bounds(&self) -> Rect {
+        Rect::from_w_h(self.config.export.width.max(1.0), self.config.export.height.max(1.0))
+    }
+
+    fn write_scene_svg(
+        &self,
+        bounds: Rect,
+        timeline: &crate::visual::timeline::TimelineView,
+        name: &str,
+    ) -> crate::errors::VisualizerResult<std::path::PathBuf> {
+        let options = crate::visual::scene::SceneOptions { legend: self.config.export.legend, transport: true, ..Default::default() };
+        let scene = crate::visual::scene::build_scene(self, bounds, timeline, options);
+        let path = std::path::Path::new(&self.config.export.directory).join(format!("timeline_{name}.svg"));
+        scene.write_svg(&path)?;
+        Ok(path)
     }
-    pub fn cleanup_expired_events(&mut self) {}
-    pub fn toggle_scroll_mode(&mut self) {}
     pub fn get_scroll_mode(&self) -> super::ScrollMode { self.scroll_mode }
     pub fn get_scroll_speed(&self) -> f32 { self.scroll_speed }
     pub fn set_scroll_speed(&mut self, speed: f32) { self.scroll_speed = speed; }
     pub fn set_display_mode(&mut self, mode: DisplayMode) { self.display_mode = mode; }
-    pub fn clear_events(&mut self) { self.musical_events.clear(); }
+    pub fn clear_events(&mut self) {
+        self.musical_events.clear();
+        self.live_max_duration = 0.0;
+        self.history.clear();
+        self.particles.clear();
+    }
     pub fn clear_visual_notes(&mut self) { self.visual_notes.clear(); }
     pub fn display_config(&self) -> &super::AppConfig { &self.config }
-    pub fn set_display_config(&mut self, _show_debug: bool, _show_grid: bool) {}
+    pub fn set_display_config(&mut self, show_debug: bool, show_grid: bool) {
+        self.config.visual.show_debug = show_debug;
+        self.config.visual.show_grid = show_grid;
+    }
 }
 // Implementación mínima para evitar errores de compilación y permitir integración OSC/MIDI
 use nannou::geom::Rect;
@@ -122,3 +923,340 @@ impl Model {
         // TODO: Implementar lógica de animación y visualización
     }
 }
+
+/// Convierte un argumento OSC numérico (Float, Int o Double) a `f32`.
+pub(crate) fn osc_arg_f32(arg: &nannou_osc::Type) -> Option<f32> {
+    match arg {
+        nannou_osc::Type::Float(f) => Some(*f),
+        nannou_osc::Type::Int(i) => Some(*i as f32),
+        nannou_osc::Type::Double(d) => Some(*d as f32),
+        _ => None,
+    }
+}
+
+/// Instrumento (carril) de un mensaje: su primer argumento de texto, o "default".
+pub(crate) fn osc_instrument(args: &[nannou_osc::Type]) -> String {
+    args.iter()
+        .find_map(|arg| match arg {
+            nannou_osc::Type::String(name) => Some(name.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// Igual que `osc_arg_f32`, conservando la precisión de los `Double` (marcas de tiempo).
+pub(crate) fn osc_arg_f64(arg: &nannou_osc::Type) -> Option<f64> {
+    match arg {
+        nannou_osc::Type::Double(d) => Some(*d),
+        nannou_osc::Type::Float(f) => Some(*f as f64),
+        nannou_osc::Type::Int(i) => Some(*i as f64),
+        nannou_osc::Type::Long(l) => Some(*l as f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+impl Model {
+    /// Modelo sin ventana, sockets ni audio para las pruebas: configuración de `config.toml`,
+    /// hora de `clock` y mensajes inyectados con el `Sender` devuelto.
+    pub(crate) fn for_tests(clock: crate::clock::SharedClock) -> (Self, std::sync::mpsc::Sender<ProcessedOscMessage>) {
+        let mut config: AppConfig = toml::from_str(include_str!("../../config.toml")).expect("config.toml válido");
+        config.audio.enable_input_capture = false;
+        config.sync.enabled = false;
+        config.recording.enabled = false;
+        let (server, sender, osc_rx) = OscServer::detached(config.osc.clone(), config.audio.clone(), clock.clone());
+        (Self::new_with_clock(config, osc_rx, server, clock), sender)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, ManualClock};
+    use crate::events::MusicalEvent;
+    use nannou_osc::Type;
+    use std::time::{Duration, Instant};
+
+    fn message(addr: &str, args: Vec<Type>, timestamp: Instant) -> ProcessedOscMessage {
+        ProcessedOscMessage { addr: addr.to_string(), args, timestamp, remote_time: None }
+    }
+
+    #[test]
+    fn test_sync_reply_updates_time_sync() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, sender) = Model::for_tests(clock.clone());
+        assert_eq!(model.time_sync.time_offset, 0.0);
+
+        // Sonda enviada en el segundo 1; sclang va 100 s por delante y la red tarda 10 ms por sentido
+        clock.set_elapsed(Duration::from_secs(1));
+        let id = model.clock_sync.start_probe(1.0);
+        clock.advance(Duration::from_millis(20));
+        sender.send(message("/viz/sync/reply", vec![Type::Int(id), Type::Double(101.01), Type::Double(101.01)], clock.now())).unwrap();
+        model.update();
+
+        assert!((model.time_sync.time_offset - 100.0).abs() < 1e-3);
+        assert!((model.time_sync.latency - 0.01).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_note_on_forms_are_told_apart_by_id_type() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, sender) = Model::for_tests(clock.clone());
+        let now = clock.now();
+        sender.send(message("/note_on", vec![Type::Int(7), Type::Float(440.0), Type::Float(0.5)], now)).unwrap();
+        sender.send(message("/note_on_id", vec![Type::Int(8), Type::Float(220.0), Type::Float(0.5)], now)).unwrap();
+        sender.send(message("/note_on", vec![Type::Float(330.0), Type::Float(0.5), Type::Float(1.0)], now)).unwrap();
+        model.update();
+
+        assert!(model.active_notes.contains_key(&7));
+        assert!(model.active_notes.contains_key(&8));
+        assert_eq!(model.musical_events.len(), 1);
+        assert!(matches!(model.musical_events[0], MusicalEvent::Note { frequency, duration, .. } if frequency == 330.0 && duration == 1.0));
+    }
+
+    #[test]
+    fn test_open_note_takes_instrument_lane() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, sender) = Model::for_tests(clock.clone());
+        model.lane_layout.auto_add = true;
+        let args = vec![Type::Int(3), Type::Float(440.0), Type::Float(0.5), Type::String("violin".to_string())];
+        sender.send(message("/note_on", args, clock.now())).unwrap();
+        model.update();
+
+        assert_eq!(model.active_notes[&3].instrument, "violin");
+        assert!(model.lane_layout.index_of("violin").is_some());
+    }
+
+    #[test]
+    fn test_detected_events_keep_channel_label_and_detection_time() {
+        use crate::audio_analyzer::{DetectedEvent, DetectedEventType};
+        let clock = Arc::new(ManualClock::new());
+        clock.advance(Duration::from_secs(10));
+        let (model, _sender) = Model::for_tests(clock.clone());
+        let detected_ago = |event_type, seconds: f64| DetectedEvent {
+            event_type,
+            timestamp: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs_f64() - seconds,
+            duration: 0.5,
+            amplitude: 0.4,
+            confidence: 0.9,
+            channel: Some(1),
+        };
+
+        let kinds = [
+            DetectedEventType::PitchTrack { start_freq: 220.0, end_freq: 440.0, stability: 0.9 },
+            DetectedEventType::SpectralCluster { center_freq: 800.0, spread: 200.0, density: 4 },
+            DetectedEventType::NoiseTexture { freq_center: 3000.0, bandwidth: 1000.0, roughness: 0.5 },
+        ];
+        for kind in kinds {
+            let event = model.detected_to_musical(detected_ago(kind, 0.25)).expect("evento visible");
+            let instrument = match &event {
+                MusicalEvent::Glissando { instrument, .. } | MusicalEvent::Cluster { instrument, .. } | MusicalEvent::Noise { instrument, .. } => instrument,
+                other => panic!("tipo inesperado: {other:?}"),
+            };
+            assert_eq!(instrument, "mic_2");
+            let lag = clock.now() - event.start_time().unwrap();
+            assert!((lag.as_secs_f64() - 0.25).abs() < 0.05, "retraso {lag:?}");
+        }
+    }
+
+    #[test]
+    fn test_note_scrolls_with_manual_clock() {
+        use crate::visual::scene::{EventLayout, Shape};
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, sender) = Model::for_tests(clock.clone());
+        model.scroll_mode = super::super::ScrollMode::Continuous;
+        clock.advance(Duration::from_secs(3));
+        sender.send(message("/note_on", vec![Type::Float(440.0), Type::Float(0.5), Type::Float(2.0)], clock.now())).unwrap();
+        model.update();
+
+        let bounds = Rect::from_w_h(1000.0, 500.0);
+        let note_x = |model: &Model| {
+            let timeline = model.timeline_view(bounds);
+            let layout = EventLayout {
+                pane: bounds,
+                timeline: &timeline,
+                audio_view: &model.config.audio,
+                lanes: &model.lane_layout,
+                theme: model.themes.theme(),
+                reactive_boost: 0.0,
+            };
+            let event = &model.musical_events[0];
+            match layout.shape_for(event, model.placement_time(event.start_time().unwrap()), DisplayMode::Events) {
+                Some(Shape::Ellipse { center, .. }) => (center.x, timeline.time_to_x(timeline.now)),
+                other => panic!("forma inesperada: {other:?}"),
+            }
+        };
+
+        // Al llegar, la nota está en la cabeza de lectura
+        let (x_at_onset, playhead) = note_x(&model);
+        assert!((x_at_onset - playhead).abs() < 1e-3, "{x_at_onset} vs {playhead}");
+
+        // Medio segundo después se ha desplazado scroll_speed / 2 píxeles hacia la izquierda
+        clock.advance(Duration::from_millis(500));
+        model.update();
+        let (x_later, _) = note_x(&model);
+        assert!((x_at_onset - x_later - model.scroll_speed * 0.5).abs() < 1e-2, "{x_at_onset} -> {x_later}");
+    }
+
+    fn note(frequency: f32, duration: f32, start_time: Instant) -> MusicalEvent {
+        MusicalEvent::Note { frequency, amplitude: 0.5, duration, instrument: "default".to_string(), start_time }
+    }
+
+    fn history_frequencies(model: &Model) -> Vec<f32> {
+        model.history.iter().map(|e| e.freq).collect()
+    }
+
+    #[test]
+    fn test_cleanup_archives_events_older_than_max_age_and_keeps_untimed() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, _sender) = Model::for_tests(clock.clone());
+        model.config.retention.max_age_secs = 1.0;
+        model.push_event(note(220.0, 0.5, clock.now()));
+        model.push_event(MusicalEvent::AnalysisData { amplitude: 0.5, brightness: 0.5, noisy: 0.0 });
+
+        // Justo antes del límite (visible + max_age) la nota sigue activa
+        let limit = model.live_visible_span() + 1.0 + 0.5;
+        clock.advance(Duration::from_secs_f32(limit - 0.1));
+        model.push_event(note(440.0, 0.5, clock.now()));
+        model.update();
+        model.cleanup_expired_events();
+        assert_eq!(model.musical_events.len(), 3);
+        assert!(model.history.is_empty());
+
+        clock.advance(Duration::from_millis(200));
+        model.update();
+        model.cleanup_expired_events();
+        assert_eq!(history_frequencies(&model), vec![220.0]);
+        assert_eq!(model.musical_events.len(), 2);
+        assert!(model.musical_events.iter().any(|e| matches!(e, MusicalEvent::AnalysisData { .. })), "los datos sin tiempo se conservan");
+        assert!(model.musical_events.iter().any(|e| matches!(e, MusicalEvent::Note { frequency, .. } if *frequency == 440.0)));
+    }
+
+    #[test]
+    fn test_cleanup_archives_oldest_events_over_max_events() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, _sender) = Model::for_tests(clock.clone());
+        model.config.retention.max_events = 2;
+        for frequency in [220.0, 330.0, 440.0] {
+            model.push_event(note(frequency, 0.5, clock.now()));
+            clock.advance(Duration::from_millis(100));
+        }
+        model.update();
+        model.cleanup_expired_events();
+
+        assert_eq!(history_frequencies(&model), vec![220.0]);
+        let kept: Vec<f32> = model.musical_events.iter().filter_map(|e| match e { MusicalEvent::Note { frequency, .. } => Some(*frequency), _ => None }).collect();
+        assert_eq!(kept, vec![330.0, 440.0]);
+    }
+
+    #[test]
+    fn test_cleanup_keeps_active_events_within_memory_budget() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, _sender) = Model::for_tests(clock.clone());
+        let chord = |low: f32, start_time| MusicalEvent::Chord {
+            frequencies: (0..256).map(|i| low + i as f32).collect(),
+            amplitude: 0.5,
+            duration: 0.5,
+            instrument: "default".to_string(),
+            start_time,
+        };
+        let size = chord(0.0, clock.now()).approx_size();
+        // Cabe justo en dos acordes: el tercero obliga a archivar el más antiguo
+        model.config.retention.max_memory_mb = (2 * size) as f32 / (1024.0 * 1024.0);
+        for low in [100.0, 200.0, 300.0] {
+            model.push_event(chord(low, clock.now()));
+            clock.advance(Duration::from_millis(100));
+        }
+        model.update();
+        model.cleanup_expired_events();
+
+        assert_eq!(history_frequencies(&model), vec![100.0]);
+        assert_eq!(model.musical_events.len(), 2);
+        assert!(model.musical_events.iter().map(|e| e.approx_size()).sum::<usize>() <= 2 * size);
+    }
+
+    #[test]
+    fn test_cleanup_archives_closed_open_notes() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, sender) = Model::for_tests(clock.clone());
+        model.config.retention.max_age_secs = 0.0;
+        let args = vec![Type::Int(5), Type::Float(440.0), Type::Float(0.5), Type::String("violin".to_string())];
+        sender.send(message("/note_on", args, clock.now())).unwrap();
+        model.update();
+        clock.advance(Duration::from_secs(1));
+        sender.send(message("/note_off", vec![Type::Int(5)], clock.now())).unwrap();
+        model.update();
+        assert_eq!(model.visual_notes.len(), 1);
+
+        // Una nota abierta todavía sonando no se archiva aunque haya empezado hace mucho
+        sender.send(message("/note_on", vec![Type::Int(6), Type::Float(220.0), Type::Float(0.5)], clock.now())).unwrap();
+        clock.advance(Duration::from_secs_f32(model.live_visible_span() + 1.0));
+        model.update();
+        model.cleanup_expired_events();
+
+        assert!(model.visual_notes.is_empty());
+        assert!(model.active_notes.contains_key(&6));
+        let archived: Vec<MusicalEvent> = model.history.iter().map(|e| model.history.to_musical(e, model.time_info.start_time)).collect();
+        match archived.as_slice() {
+            [MusicalEvent::Note { frequency, duration, instrument, .. }] => {
+                assert_eq!(*frequency, 440.0);
+                assert!((duration - 1.0).abs() < 1e-3, "duración {duration}");
+                assert_eq!(instrument, "violin");
+            }
+            other => panic!("historial inesperado: {other:?}"),
+        }
+    }
+
+    /// Notas (círculos de radio de nota) en la escena del instante mostrado.
+    fn drawn_notes(model: &Model) -> usize {
+        use crate::visual::scene::{build_scene, SceneOptions, Shape};
+        let bounds = Rect::from_w_h(1000.0, 500.0);
+        let scene = build_scene(model, bounds, &model.timeline_view(bounds), SceneOptions { mode: Some(DisplayMode::Events), ..Default::default() });
+        scene.shapes.iter().filter(|shape| matches!(shape, Shape::Ellipse { radius, .. } if *radius >= 20.0)).count()
+    }
+
+    #[test]
+    fn test_events_received_while_frozen_are_kept() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, sender) = Model::for_tests(clock.clone());
+        model.config.particles.enabled = false;
+        clock.advance(Duration::from_secs(2));
+        model.update();
+        model.toggle_freeze();
+
+        clock.advance(Duration::from_secs(1));
+        sender.send(message("/note_on", vec![Type::Float(440.0), Type::Float(0.5), Type::Float(1.0)], clock.now())).unwrap();
+        model.update();
+        model.cleanup_expired_events();
+        assert_eq!(model.display_time(), 2.0, "la vista sigue congelada");
+        assert_eq!(model.musical_events.len(), 1);
+
+        clock.advance(Duration::from_millis(500));
+        model.update();
+        model.go_live();
+        assert_eq!(drawn_notes(&model), 1);
+    }
+
+    #[test]
+    fn test_scrubbing_back_shows_archived_events() {
+        let clock = Arc::new(ManualClock::new());
+        let (mut model, sender) = Model::for_tests(clock.clone());
+        model.config.particles.enabled = false;
+        model.config.retention.max_age_secs = 0.0;
+        clock.advance(Duration::from_secs(1));
+        sender.send(message("/note_on", vec![Type::Float(440.0), Type::Float(0.5), Type::Float(1.0)], clock.now())).unwrap();
+        model.update();
+
+        clock.advance(Duration::from_secs_f32(model.live_visible_span() + 5.0));
+        model.update();
+        model.cleanup_expired_events();
+        assert!(model.musical_events.is_empty());
+        assert_eq!(model.history.len(), 1);
+        assert_eq!(drawn_notes(&model), 0);
+
+        model.scrub_to(1.5);
+        assert_eq!(drawn_notes(&model), 1, "la nota archivada vuelve a verse");
+        model.go_live();
+        assert_eq!(drawn_notes(&model), 0);
+    }
+}
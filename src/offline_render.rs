@@ -0,0 +1,206 @@
+// src/offline_render.rs
+
+//! 🎬 Render offline de sesiones grabadas
+//!
+//! Reproduce un archivo de sesión (ver `session_recording`) con un `OfflineClock` de paso fijo
+//! y escribe un PNG numerado por frame (`frame_000000.png`, ...). No abre ventanas ni usa la
+//! GPU: la escena de cada frame es la misma que dibuja `view()` y se rasteriza por software,
+//! por lo que funciona en máquinas sin pantalla. Los frames se pueden montar después, p. ej.:
+//! `ffmpeg -framerate 30 -i frames/frame_%06d.png video.mp4`.
+//!
+//! `export_session_svg` usa la misma reproducción para exportar un intervalo de la sesión a SVG.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use nannou::geom::Rect;
+use crate::clock::{OfflineClock, SharedClock};
+use crate::config::AppConfig;
+use crate::errors::{VisualizerError, VisualizerResult};
+use crate::events::ProcessedOscMessage;
+use crate::model::Model;
+use crate::osc_server::OscServer;
+use crate::session_recording::{load_session, RecordedMessage};
+use crate::visual::scene::{build_scene, SceneOptions};
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub session: PathBuf,
+    pub out_dir: PathBuf,
+    pub fps: f64,
+    pub width: f32,
+    pub height: f32,
+    /// Segundos renderizados después del último mensaje (para que los eventos terminen de pasar).
+    pub tail_secs: f64,
+}
+
+/// Renderiza la sesión completa y devuelve el número de frames escritos.
+pub fn render_session(config: AppConfig, options: &RenderOptions) -> VisualizerResult<u64> {
+    let messages = load_session(&options.session)?;
+    let fps = options.fps.max(1.0);
+    let bounds = Rect::from_w_h(options.width.max(1.0), options.height.max(1.0));
+    let end = messages.last().map_or(0.0, |m| m.time) + options.tail_secs.max(0.0);
+    let total_frames = (end * fps).ceil() as u64 + 1;
+    std::fs::create_dir_all(&options.out_dir)?;
+
+    crate::logging::Logger::log_info(&format!(
+        "🎬 Render offline: {} mensajes, {total_frames} frames a {fps} fps ({}x{})",
+        messages.len(),
+        bounds.w(),
+        bounds.h()
+    ));
+
+    replay(config, messages, fps, total_frames, |model, frame| {
+        let timeline = model.timeline_view(bounds);
+        let scene = build_scene(model, bounds, &timeline, SceneOptions::default());
+        let path = options.out_dir.join(format!("frame_{frame:06}.png"));
+        scene.to_image().save(&path).map_err(|e| VisualizerError::GenericError {
+            message: format!("No se pudo guardar {}: {e}", path.display()),
+        })?;
+
+        if frame % (fps.round() as u64 * 10).max(1) == 0 {
+            crate::logging::Logger::log_info(&format!("🎬 Frame {frame}/{total_frames}"));
+        }
+        Ok(())
+    })?;
+    Ok(total_frames)
+}
+
+/// Reproduce la sesión hasta `to` y exporta a SVG el intervalo `[from, to]` (segundos de
+/// sesión) con `Model::export_range_svg`, en `[export].directory`. Sin `to`, hasta el final
+/// de la sesión. Devuelve la ruta del archivo escrito.
+pub fn export_session_svg(config: AppConfig, session: &Path, from: f32, to: Option<f32>) -> VisualizerResult<PathBuf> {
+    let messages = load_session(session)?;
+    let to = to.unwrap_or_else(|| messages.last().map_or(0.0, |m| m.time as f32)).max(from);
+    // A 30 fps la limpieza periódica archiva los eventos igual que en directo
+    let fps = 30.0;
+    let total_frames = (to as f64 * fps).ceil() as u64 + 1;
+
+    crate::logging::Logger::log_info(&format!("🖼️ Exportando {from:.1}-{to:.1}s de {} mensajes a SVG", messages.len()));
+    let model = replay(config, messages, fps, total_frames, |_, _| Ok(()))?;
+    model.export_range_svg(from, to)
+}
+
+/// Reproduce `messages` durante `total_frames` frames con un `OfflineClock` de `fps` y llama a
+/// `on_frame` con el modelo ya actualizado de cada frame. Devuelve el modelo final.
+fn replay(
+    mut config: AppConfig,
+    messages: Vec<RecordedMessage>,
+    fps: f64,
+    total_frames: u64,
+    mut on_frame: impl FnMut(&Model, u64) -> VisualizerResult<()>,
+) -> VisualizerResult<Model> {
+    // Solo el contenido de la sesión: sin captura de audio, sincronización ni grabación
+    config.audio.enable_input_capture = false;
+    config.sync.enabled = false;
+    config.recording.enabled = false;
+
+    let clock = Arc::new(OfflineClock::new(fps));
+    let shared: SharedClock = clock.clone();
+    let (server, sender, osc_rx) = OscServer::detached(config.osc.clone(), config.audio.clone(), shared.clone());
+    let mut model = Model::new_with_clock(config, osc_rx, server, shared);
+    let cleanup_interval = (model.config.performance.cleanup_interval_frames as u64).max(1);
+
+    let mut pending = messages.into_iter().peekable();
+    for frame in 0..total_frames {
+        clock.set_frame(frame);
+        let frame_time = frame as f64 / fps;
+        // Los mensajes llegan con su marca de tiempo exacta, aunque se entreguen en el frame siguiente
+        while let Some(message) = pending.next_if(|m| m.time <= frame_time) {
+            let _ = sender.send(ProcessedOscMessage {
+                addr: message.addr,
+                args: message.args,
+                timestamp: clock.origin() + Duration::from_secs_f64(message.time.max(0.0)),
+                remote_time: None,
+            });
+        }
+
+        model.update();
+        model.time_info.frame_counter += 1;
+        if model.time_info.frame_counter.is_multiple_of(cleanup_interval) {
+            model.cleanup_expired_events();
+        }
+        on_frame(&model, frame)?;
+    }
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_recording::SessionRecorder;
+    use nannou_osc::Type;
+
+    /// Carpeta temporal propia de cada prueba, con una sesión corta de dos notas (0.5 s y 5 s).
+    fn session_dir(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let session = dir.join("sesion.tsv");
+        let mut recorder = SessionRecorder::create(&session).unwrap();
+        recorder.record(0.5, "/note_on", &[Type::Float(440.0), Type::Float(0.5), Type::Float(1.0)]);
+        recorder.record(5.0, "/note_on", &[Type::Float(880.0), Type::Float(0.5), Type::Float(1.0)]);
+        recorder.finish().unwrap();
+        (dir, session)
+    }
+
+    /// Configuración de `config.toml` exportando a `dir`, sin leyenda ni partículas (solo las notas).
+    fn test_config(dir: &Path) -> AppConfig {
+        let mut config: AppConfig = toml::from_str(include_str!("../config.toml")).expect("config.toml válido");
+        config.export.directory = dir.join("exports").to_string_lossy().into_owned();
+        config.export.legend = false;
+        config.particles.enabled = false;
+        config
+    }
+
+    #[test]
+    fn test_export_session_svg_draws_only_the_requested_range() {
+        let (dir, session) = session_dir("svg_sesion");
+        let config = test_config(&dir);
+
+        let path = export_session_svg(config.clone(), &session, 0.0, Some(2.0)).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        assert!(path.starts_with(dir.join("exports")));
+        assert_eq!(svg.matches("<circle").count(), 1, "solo la nota de 0.5 s");
+
+        let empty = std::fs::read_to_string(export_session_svg(config, &session, 2.0, Some(4.0)).unwrap()).unwrap();
+        assert_eq!(empty.matches("<circle").count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render_session_writes_every_frame_with_the_note() {
+        use crate::visual::scene::Shape;
+        let (dir, session) = session_dir("render_sesion");
+        let config = test_config(&dir);
+        let options = RenderOptions { session: session.clone(), out_dir: dir.join("frames"), fps: 10.0, width: 320.0, height: 180.0, tail_secs: 0.5 };
+
+        // Último mensaje a los 5 s + 0.5 s de cola a 10 fps, contando el frame 0
+        let frames = render_session(config.clone(), &options).unwrap();
+        assert_eq!(frames, 56);
+        assert_eq!(std::fs::read_dir(&options.out_dir).unwrap().count(), 56);
+
+        // La nota de 0.5 s aparece en el frame de 1 s con su color, donde la sitúa la escena
+        let bounds = Rect::from_w_h(options.width, options.height);
+        let model = replay(config, load_session(&session).unwrap(), options.fps, 11, |_, _| Ok(())).unwrap();
+        let scene = build_scene(&model, bounds, &model.timeline_view(bounds), SceneOptions::default());
+        let (center, color) = scene
+            .shapes
+            .iter()
+            .find_map(|shape| match shape {
+                Shape::Ellipse { center, radius, color } if *radius >= 20.0 => Some((*center, *color)),
+                _ => None,
+            })
+            .expect("la nota está en la escena");
+        let pixel_at_note = |frame: u64| {
+            let image = nannou::image::open(options.out_dir.join(format!("frame_{frame:06}.png"))).unwrap().to_rgba8();
+            image.get_pixel((center.x - bounds.left()).round() as u32, (bounds.top() - center.y).round() as u32).0
+        };
+        let pixel = pixel_at_note(10);
+        for channel in 0..3 {
+            let expected = (color[channel] * color[3] + scene.background[channel] * (1.0 - color[3])) * 255.0;
+            assert!((pixel[channel] as f32 - expected).abs() < 8.0, "píxel {pixel:?}, color {color:?}");
+        }
+        assert_ne!(pixel_at_note(0), pixel, "antes de la nota el píxel es fondo");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -317,6 +317,7 @@ pub fn setup_osc_receiver(listen_port: u16) -> VisualizerResult<Receiver<Musical
                                     density: *density,
                                     amplitude: *amplitude,
                                     duration: *duration,
+                                    instrument: "default".to_string(),
                                     start_time: std::time::Instant::now(),
                                 })
                             } else { None }
@@ -329,6 +330,7 @@ pub fn setup_osc_receiver(listen_port: u16) -> VisualizerResult<Receiver<Musical
                                     density: *density,
                                     amplitude: *amplitude,
                                     duration: *duration,
+                                    instrument: "default".to_string(),
                                     start_time: std::time::Instant::now(),
                                 })
                             } else { None }
@@ -6,6 +6,7 @@ use crate::config::{OscConfig, AudioConfig};
 use crate::errors::{VisualizerError, VisualizerResult};
 use crate::logging::Logger;
 use crate::events::{MusicalEvent, ProcessedOscMessage}; // Asegúrate de importar MusicalEvent y RealtimeData
+use crate::clock::SharedClock;
 
 // La definición de ProcessedOscMessage se mueve a events.rs y se documenta allí.
 
@@ -18,6 +19,8 @@ pub struct OscServerStats {
     pub last_message_time: f64,
     pub messages_per_second: f64,
     pub is_connected: bool,
+    /// Dirección (ip:puerto) del último paquete recibido.
+    pub last_sender: Option<String>,
 }
 
 impl Default for OscServerStats {
@@ -29,6 +32,7 @@ impl Default for OscServerStats {
             last_message_time: 0.0,
             messages_per_second: 0.0,
             is_connected: false,
+            last_sender: None,
         }
     }
 }
@@ -45,11 +49,17 @@ pub struct OscServer {
     stats_receiver: mpsc::Receiver<OscServerStats>,
     is_running: bool,
     last_stats: OscServerStats,
+    clock: SharedClock,
 }
 
 impl OscServer {
     /// Crea una nueva instancia del servidor OSC.
     pub fn new(config: OscConfig, audio_config: AudioConfig) -> VisualizerResult<(Arc<Mutex<OscServer>>, Receiver<ProcessedOscMessage>)> {
+        Self::new_with_clock(config, audio_config, crate::clock::real_time())
+    }
+
+    /// Igual que `new`, marcando los mensajes recibidos con la hora de `clock`.
+    pub fn new_with_clock(config: OscConfig, audio_config: AudioConfig, clock: SharedClock) -> VisualizerResult<(Arc<Mutex<OscServer>>, Receiver<ProcessedOscMessage>)> {
         // Canal principal para mensajes procesados
         let (sender, receiver) = mpsc::channel();
         let (stats_sender, stats_receiver) = mpsc::channel();
@@ -65,6 +75,7 @@ impl OscServer {
             stats_receiver,
             is_running: false,
             last_stats: OscServerStats::default(),
+            clock,
         };
 
         let server_arc = Arc::new(Mutex::new(server));
@@ -78,6 +89,29 @@ impl OscServer {
         Ok((server_arc, receiver))
     }
 
+    /// Servidor sin socket ni hilo de escucha: los mensajes se inyectan con el `Sender`
+    /// devuelto (reproducción de sesiones grabadas y render offline).
+    pub fn detached(
+        config: OscConfig,
+        audio_config: AudioConfig,
+        clock: SharedClock,
+    ) -> (Arc<Mutex<OscServer>>, mpsc::Sender<ProcessedOscMessage>, Receiver<ProcessedOscMessage>) {
+        let (sender, receiver) = mpsc::channel();
+        let (stats_sender, stats_receiver) = mpsc::channel();
+        let server = OscServer {
+            config,
+            audio_config,
+            sender: sender.clone(),
+            receiver: mpsc::channel().1,
+            stats_sender,
+            stats_receiver,
+            is_running: false,
+            last_stats: OscServerStats::default(),
+            clock,
+        };
+        (Arc::new(Mutex::new(server)), sender, receiver)
+    }
+
     /// Permite acceder al Receiver interno de mensajes OSC procesados.
     pub fn receiver(&self) -> &Receiver<ProcessedOscMessage> {
         &self.receiver
@@ -94,6 +128,7 @@ impl OscServer {
         let sender_clone = self.sender.clone();
         let stats_sender_clone = self.stats_sender.clone();
         let _audio_config_clone = self.audio_config.clone();
+        let clock = self.clock.clone();
 
         Logger::log_info(&format!("🔧 Preparando para iniciar OscServer en 127.0.0.1:{listen_port}"));
 
@@ -122,17 +157,19 @@ impl OscServer {
             let mut msg_count_since_last_update = 0;
             current_stats.is_connected = true;
 
-            for (packet, _) in receiver.iter() {
+            for (packet, from) in receiver.iter() {
                 current_stats.total_received += 1;
+                current_stats.last_sender = Some(from.to_string());
                 msg_count_since_last_update += 1;
                 let start_time = Instant::now();
 
-                for msg in packet.into_msgs() {
+                for (msg, remote_time) in timed_messages(packet) {
                     Logger::log_info(&format!("🎵 OSC recibido: {} {:?}", msg.addr, msg.args));
                     let processed_msg = ProcessedOscMessage {
                         addr: msg.addr.clone(),
                         args: msg.args.clone(),
-                        timestamp: Instant::now(),
+                        timestamp: clock.now(),
+                        remote_time,
                     };
 
                     // Enviar todos los mensajes procesados al canal para que main.rs los reciba
@@ -491,6 +528,28 @@ impl OscServer {
 
 }
 
+/// Mensajes de un paquete junto con la marca de tiempo del bundle que los contiene.
+fn timed_messages(packet: osc::Packet) -> Vec<(osc::Message, Option<f64>)> {
+    fn unfold(packet: osc::Packet, time: Option<f64>, out: &mut Vec<(osc::Message, Option<f64>)>) {
+        match packet {
+            osc::Packet::Message(msg) => out.push((msg, time)),
+            osc::Packet::Bundle(bundle) => {
+                // La marca (0, 1) significa «inmediatamente»: se conserva la del bundle exterior
+                let time = match (bundle.timetag.seconds, bundle.timetag.fractional) {
+                    (0, 1) => time,
+                    (seconds, fractional) => Some(seconds as f64 + fractional as f64 / 4_294_967_296.0),
+                };
+                for inner in bundle.content {
+                    unfold(inner.into(), time, out);
+                }
+            }
+        }
+    }
+    let mut messages = Vec::new();
+    unfold(packet, None, &mut messages);
+    messages
+}
+
 pub fn map_processed_to_musical(rx: std::sync::mpsc::Receiver<ProcessedOscMessage>) -> impl Iterator<Item = crate::events::MusicalEvent> {
     rx.into_iter().filter_map(|processed| {
         let timestamp = processed.timestamp; // Captura el timestamp una vez
@@ -617,6 +676,7 @@ pub fn map_processed_to_musical(rx: std::sync::mpsc::Receiver<ProcessedOscMessag
                         density: *density,
                         amplitude: amp,
                         duration: dur,
+                        instrument: "default".to_string(), // Podrías añadir un argumento para esto
                         start_time: timestamp,
                     })
                 } else {
@@ -0,0 +1,108 @@
+// src/pitch.rs
+
+//! 🎼 Conversión entre frecuencia, número MIDI y nombre de nota
+//!
+//! Todas las funciones toman la referencia de afinación del La4 (`a4`, normalmente 440 Hz)
+//! para poder trabajar con afinaciones históricas (415, 432...).
+
+/// Número MIDI del La4.
+pub const A4_MIDI: f32 = 69.0;
+
+/// Nombres de las doce clases de altura (con sostenidos), empezando en Do.
+pub const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// Número MIDI (fraccionario) de `freq`.
+pub fn freq_to_midi(freq: f32, a4: f32) -> f32 {
+    A4_MIDI + 12.0 * (freq.max(f32::MIN_POSITIVE) / a4).log2()
+}
+
+pub fn midi_to_freq(midi: f32, a4: f32) -> f32 {
+    a4 * ((midi - A4_MIDI) / 12.0).exp2()
+}
+
+/// Nombre con número de octava en notación científica (60 → "C4", 69 → "A4").
+pub fn note_name(midi: i32) -> String {
+    format!("{}{}", NOTE_NAMES[midi.rem_euclid(12) as usize], midi.div_euclid(12) - 1)
+}
+
+/// Nota sin alteración (teclas blancas).
+pub fn is_natural(midi: i32) -> bool {
+    !NOTE_NAMES[midi.rem_euclid(12) as usize].contains('#')
+}
+
+/// Alteración de una nota escrita, incluidos los cuartos de tono.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accidental {
+    Natural,
+    Sharp,
+    Flat,
+    QuarterSharp,
+    ThreeQuarterSharp,
+    QuarterFlat,
+    ThreeQuarterFlat,
+}
+
+/// Letra (0 = Do ... 6 = Si) de cada clase de altura natural.
+const LETTERS: [Option<i32>; 12] = [Some(0), None, Some(1), None, Some(2), Some(3), None, Some(4), None, Some(5), None, Some(6)];
+
+/// Posición diatónica de una nota natural: un paso por línea o espacio del pentagrama (Do4 = 28).
+fn natural_step(midi: i32) -> Option<i32> {
+    LETTERS[midi.rem_euclid(12) as usize].map(|letter| (midi.div_euclid(12) - 1) * 7 + letter)
+}
+
+/// Escritura de `midi` ya cuantizado a semitonos o cuartos de tono: posición diatónica
+/// (Do4 = 28) y alteración. Las teclas negras se escriben con sostenidos o, con
+/// `prefer_flats`, con bemoles; los cuartos de tono siguen la misma preferencia.
+pub fn spell(midi: f32, prefer_flats: bool) -> (i32, Accidental) {
+    let semitone = midi.floor() as i32;
+    let quarter = midi - midi.floor() >= 0.25;
+    match (quarter, prefer_flats) {
+        (false, false) => match natural_step(semitone) {
+            Some(step) => (step, Accidental::Natural),
+            None => (natural_step(semitone - 1).unwrap_or_default(), Accidental::Sharp),
+        },
+        (false, true) => match natural_step(semitone) {
+            Some(step) => (step, Accidental::Natural),
+            None => (natural_step(semitone + 1).unwrap_or_default(), Accidental::Flat),
+        },
+        (true, false) => match natural_step(semitone) {
+            Some(step) => (step, Accidental::QuarterSharp),
+            None => (natural_step(semitone - 1).unwrap_or_default(), Accidental::ThreeQuarterSharp),
+        },
+        (true, true) => match natural_step(semitone + 1) {
+            Some(step) => (step, Accidental::QuarterFlat),
+            None => (natural_step(semitone + 2).unwrap_or_default(), Accidental::ThreeQuarterFlat),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_and_reference_tuning() {
+        assert_eq!(note_name(60), "C4");
+        assert_eq!(note_name(69), "A4");
+        assert_eq!(note_name(36), "C2");
+        assert_eq!(note_name(-1), "B-2");
+        assert!((freq_to_midi(440.0, 440.0) - 69.0).abs() < 1e-4);
+        // Con La4 = 415 Hz, 440 Hz queda aproximadamente un semitono por encima
+        assert!((freq_to_midi(440.0, 415.0) - 70.01).abs() < 0.01);
+        assert!((midi_to_freq(60.0, 440.0) - 261.63).abs() < 0.01);
+        assert!(is_natural(60) && !is_natural(61));
+    }
+
+    #[test]
+    fn test_spelling_with_sharps_flats_and_quarter_tones() {
+        assert_eq!(spell(60.0, false), (28, Accidental::Natural));
+        assert_eq!(spell(61.0, false), (28, Accidental::Sharp));
+        assert_eq!(spell(61.0, true), (29, Accidental::Flat));
+        assert_eq!(spell(69.0, false), (33, Accidental::Natural));
+        assert_eq!(spell(60.5, false), (28, Accidental::QuarterSharp));
+        assert_eq!(spell(61.5, false), (28, Accidental::ThreeQuarterSharp));
+        // Si3 + ¼ se escribe como Do4 - ¼ con bemoles
+        assert_eq!(spell(59.5, true), (28, Accidental::QuarterFlat));
+        assert_eq!(spell(21.0, false), (5, Accidental::Natural));
+    }
+}
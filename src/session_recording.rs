@@ -0,0 +1,180 @@
+// src/session_recording.rs
+
+//! 🎙️ Grabación de sesiones
+//!
+//! Guarda los mensajes OSC recibidos con su tiempo de sesión en un archivo de texto
+//! (una línea por mensaje, campos separados por tabuladores, aquí `⇥`) para poder
+//! reproducirlos después, p. ej. en el render offline:
+//!
+//! ```text
+//! 1.250000⇥/note_on⇥f:440⇥f:0.5⇥f:1
+//! 2.000000⇥/note_set⇥i:3⇥s:freq⇥f:660
+//! ```
+
+use nannou_osc as osc;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::errors::{VisualizerError, VisualizerResult};
+
+const HEADER: &str = "# sc-score-visualizer sesion v1";
+
+/// Mensaje grabado: `time` en segundos desde el inicio de la sesión.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedMessage {
+    pub time: f64,
+    pub addr: String,
+    pub args: Vec<osc::Type>,
+}
+
+impl RecordedMessage {
+    /// Línea del archivo de sesión, o `None` si algún argumento no es grabable (blobs, MIDI...).
+    pub fn to_line(&self) -> Option<String> {
+        let mut line = format!("{:.6}\t{}", self.time, escape(&self.addr));
+        for arg in &self.args {
+            let field = match arg {
+                osc::Type::Int(v) => format!("i:{v}"),
+                osc::Type::Float(v) => format!("f:{v}"),
+                osc::Type::Long(v) => format!("h:{v}"),
+                osc::Type::Double(v) => format!("d:{v}"),
+                osc::Type::Bool(v) => format!("b:{v}"),
+                osc::Type::String(v) => format!("s:{}", escape(v)),
+                _ => return None,
+            };
+            line.push('\t');
+            line.push_str(&field);
+        }
+        Some(line)
+    }
+
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let time = fields.next()?.trim().parse().ok()?;
+        let addr = unescape(fields.next()?);
+        let args = fields
+            .map(|field| {
+                let (tag, value) = field.split_once(':')?;
+                Some(match tag {
+                    "i" => osc::Type::Int(value.parse().ok()?),
+                    "f" => osc::Type::Float(value.parse().ok()?),
+                    "h" => osc::Type::Long(value.parse().ok()?),
+                    "d" => osc::Type::Double(value.parse().ok()?),
+                    "b" => osc::Type::Bool(value.parse().ok()?),
+                    "s" => osc::Type::String(unescape(value)),
+                    _ => return None,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { time, addr, args })
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Escribe los mensajes de la sesión en curso a medida que llegan.
+#[derive(Debug)]
+pub struct SessionRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    messages: u64,
+}
+
+impl SessionRecorder {
+    /// Crea (o sobrescribe) el archivo de sesión en `path`.
+    pub fn create(path: &Path) -> VisualizerResult<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{HEADER}")?;
+        Ok(Self { path: path.to_path_buf(), writer, messages: 0 })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    pub fn record(&mut self, time: f64, addr: &str, args: &[osc::Type]) {
+        let message = RecordedMessage { time, addr: addr.to_string(), args: args.to_vec() };
+        match message.to_line() {
+            Some(line) => {
+                if let Err(e) = writeln!(self.writer, "{line}") {
+                    crate::logging::Logger::log_error(&format!("Error al grabar la sesión: {e}"));
+                    return;
+                }
+                self.messages += 1;
+            }
+            None => crate::logging::Logger::log_warn(&format!("Mensaje {addr} no grabado: tipo de argumento no soportado")),
+        }
+    }
+
+    /// Vacía el búfer y cierra el archivo.
+    pub fn finish(mut self) -> VisualizerResult<PathBuf> {
+        self.writer.flush()?;
+        Ok(self.path)
+    }
+}
+
+/// Lee un archivo de sesión y devuelve sus mensajes ordenados por tiempo.
+/// Las líneas vacías y los comentarios (`#`) se ignoran.
+pub fn load_session(path: &Path) -> VisualizerResult<Vec<RecordedMessage>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut messages = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let message = RecordedMessage::parse_line(&line).ok_or_else(|| VisualizerError::ValidationError {
+            field: format!("{}:{}", path.display(), number + 1),
+            expected: "tiempo<TAB>dirección<TAB>tipo:valor...".to_string(),
+            actual: line.clone(),
+            details: "Línea de sesión inválida".to_string(),
+        })?;
+        messages.push(message);
+    }
+    messages.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_round_trip() {
+        let message = RecordedMessage {
+            time: 1.25,
+            addr: "/note_set".to_string(),
+            args: vec![osc::Type::Int(3), osc::Type::String("tab\there".to_string()), osc::Type::Float(660.5)],
+        };
+        let line = message.to_line().unwrap();
+        assert_eq!(line, "1.250000\t/note_set\ti:3\ts:tab\\there\tf:660.5");
+        assert_eq!(RecordedMessage::parse_line(&line), Some(message));
+        assert!(RecordedMessage::parse_line("1.0\t/x\tq:1").is_none());
+    }
+}
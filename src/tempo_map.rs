@@ -0,0 +1,195 @@
+// src/tempo_map.rs
+
+//! 🥁 Mapa de tempo (tiempo de sesión ↔ pulsos y compases)
+//!
+//! Se construye a partir de los mensajes de un `TempoClock` de SuperCollider:
+//!
+//! - `/tempo bpm beatsPerBar`: cambio de tempo y/o de compás a partir de ese instante.
+//! - `/beat beatNumber`: el pulso `beatNumber` suena ahora; corrige la deriva acumulada.
+//!
+//! El mapa es una lista de tramos de tempo constante. Cada tramo guarda el instante, el
+//! pulso y el compás en los que empieza, así que las conversiones son lineales por tramos.
+
+/// Tramo de tempo constante.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoSegment {
+    /// Inicio del tramo en segundos de sesión.
+    pub time: f32,
+    /// Pulso (en unidades de `TempoClock`) al inicio del tramo.
+    pub beat: f32,
+    /// Compás (contando desde 0, puede ser fraccionario) al inicio del tramo.
+    pub bar: f32,
+    pub bpm: f32,
+    pub beats_per_bar: f32,
+}
+
+impl TempoSegment {
+    fn beats_per_sec(&self) -> f32 {
+        self.bpm / 60.0
+    }
+}
+
+/// Deriva (en pulsos) a partir de la cual un `/beat` crea un nuevo tramo.
+const BEAT_DRIFT_TOLERANCE: f32 = 0.02;
+
+#[derive(Debug, Clone, Default)]
+pub struct TempoMap {
+    segments: Vec<TempoSegment>,
+    /// Último `/beat` recibido, para estimar el tempo si no llega `/tempo`.
+    last_beat: Option<(f32, f32)>,
+    explicit_tempo: bool,
+}
+
+impl TempoMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn segments(&self) -> &[TempoSegment] {
+        &self.segments
+    }
+
+    /// Tramo vigente en `time` (el primero si `time` es anterior a todos).
+    fn segment_at(&self, time: f32) -> Option<&TempoSegment> {
+        let index = self.segments.partition_point(|s| s.time <= time);
+        self.segments.get(index.saturating_sub(1))
+    }
+
+    fn segment_at_beat(&self, beat: f32) -> Option<&TempoSegment> {
+        let index = self.segments.partition_point(|s| s.beat <= beat);
+        self.segments.get(index.saturating_sub(1))
+    }
+
+    /// Tempo vigente (bpm, pulsos por compás) en `time`.
+    pub fn tempo_at(&self, time: f32) -> Option<(f32, f32)> {
+        self.segment_at(time).map(|s| (s.bpm, s.beats_per_bar))
+    }
+
+    pub fn beat_at(&self, time: f32) -> Option<f32> {
+        self.segment_at(time).map(|s| s.beat + (time - s.time) * s.beats_per_sec())
+    }
+
+    pub fn time_at(&self, beat: f32) -> Option<f32> {
+        self.segment_at_beat(beat).map(|s| s.time + (beat - s.beat) / s.beats_per_sec())
+    }
+
+    /// Compás (desde 0, fraccionario) correspondiente a `beat`.
+    pub fn bar_at_beat(&self, beat: f32) -> Option<f32> {
+        self.segment_at_beat(beat).map(|s| s.bar + (beat - s.beat) / s.beats_per_bar)
+    }
+
+    /// Pulsos por compás vigentes en `beat`.
+    pub fn beats_per_bar_at_beat(&self, beat: f32) -> Option<f32> {
+        self.segment_at_beat(beat).map(|s| s.beats_per_bar)
+    }
+
+    /// `/tempo bpm beatsPerBar` recibido en `time`.
+    pub fn set_tempo(&mut self, time: f32, bpm: f32, beats_per_bar: f32) {
+        if !(bpm.is_finite() && bpm > 0.0) {
+            return;
+        }
+        let beats_per_bar = if beats_per_bar.is_finite() && beats_per_bar > 0.0 { beats_per_bar } else { 4.0 };
+        self.explicit_tempo = true;
+        let (beat, bar) = match self.segment_at(time).copied() {
+            Some(s) => {
+                let beat = s.beat + (time - s.time) * s.beats_per_sec();
+                // El nuevo compás empieza en el siguiente pulso entero si el tipo de compás cambia
+                let bar = s.bar + (beat - s.beat) / s.beats_per_bar;
+                (beat, if (beats_per_bar - s.beats_per_bar).abs() > f32::EPSILON { bar.ceil() } else { bar })
+            }
+            None => (0.0, 0.0),
+        };
+        self.push_segment(TempoSegment { time, beat, bar, bpm, beats_per_bar });
+    }
+
+    /// `/beat beatNumber` recibido en `time`: el pulso `beat` suena ahora.
+    pub fn mark_beat(&mut self, time: f32, beat: f32) {
+        let estimated_bpm = match self.last_beat {
+            Some((last_time, last_beat)) if beat > last_beat && time > last_time => {
+                Some((beat - last_beat) * 60.0 / (time - last_time))
+            }
+            _ => None,
+        };
+        self.last_beat = Some((time, beat));
+
+        let Some(current) = self.segment_at(time).copied() else {
+            // Sin tempo conocido: se ancla el pulso con un tempo provisional
+            let bpm = estimated_bpm.unwrap_or(60.0);
+            self.push_segment(TempoSegment { time, beat, bar: beat / 4.0, bpm, beats_per_bar: 4.0 });
+            return;
+        };
+
+        let bpm = match estimated_bpm {
+            Some(estimated) if !self.explicit_tempo => current.bpm * 0.7 + estimated * 0.3,
+            _ => current.bpm,
+        };
+        let predicted = current.beat + (time - current.time) * current.beats_per_sec();
+        if (predicted - beat).abs() < BEAT_DRIFT_TOLERANCE && (bpm - current.bpm).abs() < 0.01 {
+            return;
+        }
+        let bar = current.bar + (beat - current.beat) / current.beats_per_bar;
+        self.push_segment(TempoSegment { time, beat, bar, bpm, beats_per_bar: current.beats_per_bar });
+    }
+
+    fn push_segment(&mut self, segment: TempoSegment) {
+        // Los mensajes llegan en orden; un tramo en el mismo instante reemplaza al anterior
+        while self.segments.last().is_some_and(|s| s.time >= segment.time) {
+            self.segments.pop();
+        }
+        self.segments.push(segment);
+    }
+
+    /// Ajusta `time` a la subdivisión más cercana (`division` partes por pulso).
+    pub fn quantize(&self, time: f32, division: u32) -> f32 {
+        let division = division.max(1) as f32;
+        match self.beat_at(time) {
+            Some(beat) => self.time_at((beat * division).round() / division).unwrap_or(time),
+            None => time,
+        }
+    }
+
+    /// Elimina los tramos que terminan antes de `time`, conservando el vigente en ese instante.
+    pub fn prune_before(&mut self, time: f32) {
+        let index = self.segments.partition_point(|s| s.time <= time);
+        if index > 1 {
+            self.segments.drain(..index - 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tempo_changes_are_piecewise_linear() {
+        let mut map = TempoMap::new();
+        map.set_tempo(0.0, 120.0, 4.0);
+        assert_eq!(map.beat_at(1.0), Some(2.0));
+        map.set_tempo(2.0, 60.0, 3.0);
+        // 4 pulsos a 120 bpm, después 1 pulso por segundo
+        assert_eq!(map.beat_at(3.0), Some(5.0));
+        assert_eq!(map.time_at(5.0), Some(3.0));
+        assert_eq!(map.bar_at_beat(4.0), Some(1.0));
+        assert_eq!(map.bar_at_beat(7.0), Some(2.0));
+        assert_eq!(map.tempo_at(2.5), Some((60.0, 3.0)));
+    }
+
+    #[test]
+    fn test_beat_messages_correct_drift_and_quantize() {
+        let mut map = TempoMap::new();
+        map.set_tempo(0.0, 120.0, 4.0);
+        // El pulso 8 llega 50 ms tarde respecto a lo previsto (4.0 s)
+        map.mark_beat(4.05, 8.0);
+        assert!((map.time_at(8.0).unwrap() - 4.05).abs() < 1e-4);
+        assert_eq!(map.segments().len(), 2);
+        // Sin deriva no se crean tramos nuevos
+        map.mark_beat(4.55, 9.0);
+        assert_eq!(map.segments().len(), 2);
+        assert!((map.quantize(4.17, 4) - 4.175).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,74 @@
+// src/visual/analysis_view.rs
+
+use nannou::prelude::*;
+use std::collections::VecDeque;
+use crate::config::{AudioConfig, FeatureBusConfig};
+use crate::feature_bus::AnalysisSample;
+use crate::visual::scene::{Shape, TextAlign};
+use crate::visual::timeline::TimelineView;
+
+/// Valor normalizado (0.0 - 1.0) de una curva; el segundo argumento es el centroide máximo.
+type CurveValue = fn(&AnalysisSample, f32) -> f32;
+
+/// Curvas del modo Análisis, con su color y el valor que representan.
+const CURVES: [(&str, [f32; 3], CurveValue); 5] = [
+    ("RMS", [0.3, 1.0, 0.5], |s, _| s.rms),
+    ("Ataque", [1.0, 0.6, 0.2], |s, _| s.onset_strength),
+    ("Centroide", [0.4, 0.6, 1.0], |s, max| s.centroid / max),
+    ("Brillo", [1.0, 0.9, 0.3], |s, _| s.brightness),
+    ("Ruido", [0.7, 0.7, 0.7], |s, _| s.noisy),
+];
+
+/// Curvas de análisis en `pane` sobre la misma línea de tiempo que los eventos.
+/// La altura de `/realtime` se dibuja como puntos con escala logarítmica de frecuencia.
+pub fn analysis_pane_shapes(
+    pane: Rect,
+    timeline: &TimelineView,
+    history: &VecDeque<AnalysisSample>,
+    features: &FeatureBusConfig,
+    audio_view: &AudioConfig,
+) -> Vec<Shape> {
+    let mut shapes = vec![
+        Shape::rect(pane, [0.0, 0.0, 0.0, 0.35]),
+        Shape::line(pt2(pane.left(), pane.top()), pt2(pane.right(), pane.top()), 1.0, [0.5, 0.5, 0.7, 0.5]),
+    ];
+
+    let (from, to) = timeline.visible_range();
+    let visible: Vec<&AnalysisSample> = history.iter().filter(|s| s.time >= from && s.time <= to).collect();
+    let inner = pane.pad(8.0);
+    let centroid_max = features.centroid_max.max(1.0);
+
+    if visible.len() >= 2 {
+        for (_, [r, g, b], value) in CURVES.iter() {
+            let points = visible.iter().map(|s| {
+                let v = value(s, centroid_max).clamp(0.0, 1.0);
+                pt2(timeline.time_to_x(s.time), map_range(v, 0.0, 1.0, inner.bottom(), inner.top()))
+            });
+            shapes.push(Shape::Polyline { points: points.collect(), weight: 1.5, color: [*r, *g, *b, 0.85] });
+        }
+    }
+
+    let log_min = audio_view.freq_min.max(1.0).log2();
+    let log_max = audio_view.freq_max.max(2.0).log2();
+    for sample in &visible {
+        if let Some(pitch) = sample.pitch {
+            let y = map_range(pitch.max(1.0).log2(), log_min, log_max, inner.bottom(), inner.top());
+            if y >= inner.bottom() && y <= inner.top() {
+                shapes.push(Shape::Ellipse {
+                    center: pt2(timeline.time_to_x(sample.time), y),
+                    radius: 1.5,
+                    color: [1.0, 1.0, 1.0, 0.7],
+                });
+            }
+        }
+    }
+
+    // Leyenda
+    let mut x = inner.left() + 70.0;
+    for (name, [r, g, b], _) in CURVES.iter() {
+        shapes.push(Shape::text(pt2(x, inner.top() - 6.0), *name, 10, [*r, *g, *b, 1.0], TextAlign::Left));
+        x += 80.0;
+    }
+    shapes.push(Shape::text(pt2(x, inner.top() - 6.0), "Altura", 10, [1.0, 1.0, 1.0, 1.0], TextAlign::Left));
+    shapes
+}
@@ -0,0 +1,78 @@
+// src/visual/beat_grid.rs
+
+use nannou::prelude::*;
+use crate::config::TempoConfig;
+use crate::tempo_map::TempoMap;
+use crate::visual::scene::{Shape, TextAlign};
+use crate::visual::styles::VisualTheme;
+use crate::visual::timeline::TimelineView;
+
+/// Separación mínima en píxeles para dibujar pulsos y subdivisiones.
+const MIN_BEAT_PX: f32 = 12.0;
+const MIN_SUBDIVISION_PX: f32 = 6.0;
+
+/// Rejilla musical: líneas de compás con su número, pulsos y subdivisiones,
+/// siguiendo los cambios de tempo del mapa. Las densidades que no caben se omiten.
+pub fn beat_grid_shapes(win: Rect, timeline: &TimelineView, tempo: &TempoMap, config: &TempoConfig, theme: &VisualTheme) -> Vec<Shape> {
+    let mut shapes = Vec::new();
+    let [r, g, b, _] = theme.accent;
+    let (from, to) = timeline.visible_range();
+    let (Some(first_beat), Some(last_beat)) = (tempo.beat_at(from), tempo.beat_at(to)) else {
+        return shapes;
+    };
+    let px_per_sec = 1.0 / timeline.seconds_per_pixel();
+    let subdivisions = config.subdivisions.max(1);
+
+    let mut beat = first_beat.floor();
+    while beat <= last_beat.ceil() {
+        let (Some(bpm), Some(beats_per_bar)) = (
+            tempo.time_at(beat).and_then(|t| tempo.tempo_at(t)).map(|(bpm, _)| bpm),
+            tempo.beats_per_bar_at_beat(beat),
+        ) else {
+            break;
+        };
+        let beat_px = px_per_sec * 60.0 / bpm;
+        let bar = tempo.bar_at_beat(beat).unwrap_or(0.0);
+        let is_bar = (bar - bar.round()).abs() * beats_per_bar < 1e-3;
+
+        if is_bar || beat_px >= MIN_BEAT_PX {
+            if let Some(x) = tempo.time_at(beat).map(|t| timeline.time_to_x(t)) {
+                let (alpha, weight) = if is_bar { (0.45, 1.5) } else { (0.18, 1.0) };
+                shapes.push(Shape::line(pt2(x, win.bottom()), pt2(x, win.top()), weight, [r, g, b, alpha]));
+                if is_bar {
+                    shapes.push(Shape::text(
+                        pt2(x + 3.0, win.top() - 32.0),
+                        format!("{}", bar.round() as i64 + 1),
+                        theme.fonts.label_size + 1,
+                        theme.text,
+                        TextAlign::Left,
+                    ));
+                }
+            }
+        }
+
+        if subdivisions > 1 && beat_px / subdivisions as f32 >= MIN_SUBDIVISION_PX {
+            for i in 1..subdivisions {
+                let sub_beat = beat + i as f32 / subdivisions as f32;
+                if let Some(x) = tempo.time_at(sub_beat).map(|t| timeline.time_to_x(t)) {
+                    shapes.push(Shape::line(pt2(x, win.bottom()), pt2(x, win.top()), 1.0, [r, g, b, 0.07]));
+                }
+            }
+        }
+        beat += 1.0;
+    }
+
+    // Tempo y compás vigentes en la cabeza de lectura
+    if let (Some((bpm, beats_per_bar)), Some(beat)) = (tempo.tempo_at(timeline.now), tempo.beat_at(timeline.now)) {
+        let bar = tempo.bar_at_beat(beat).unwrap_or(0.0);
+        let beat_in_bar = (bar.fract() * beats_per_bar).floor() as i64 + 1;
+        shapes.push(Shape::text(
+            pt2(win.right() - 15.0, win.top() - 32.0),
+            format!("♩ = {bpm:.1}  {beats_per_bar}/4  compás {}.{beat_in_bar}", bar.floor() as i64 + 1),
+            theme.fonts.legend_size,
+            theme.text,
+            TextAlign::Right,
+        ));
+    }
+    shapes
+}
@@ -0,0 +1,102 @@
+// src/visual/density.rs
+
+//! 🔬 Rejilla de densidad para el nivel de detalle
+//!
+//! Cuando hay demasiados eventos puntuales a la vista (patrones granulares de miles de eventos
+//! por segundo), cada uno se suma a una celda de `cell` píxeles en lugar de dibujarse. La
+//! rejilla produce una primitiva por celda ocupada: el coste de dibujo queda acotado por el
+//! tamaño del panel, no por el número de eventos.
+
+use nannou::prelude::*;
+use crate::visual::scene::Shape;
+use crate::visual::styles::VisualTheme;
+
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    area: Rect,
+    cell: f32,
+    cols: usize,
+    rows: usize,
+    /// Peso acumulado por celda (opacidad de cada evento tras el fundido).
+    weight: Vec<f32>,
+    /// Suma de log2(frecuencia) ponderada, para colorear la celda con la altura media.
+    log_freq: Vec<f32>,
+    count: usize,
+}
+
+impl DensityGrid {
+    pub fn new(area: Rect, cell: f32) -> Self {
+        let cell = cell.max(1.0);
+        let cols = (area.w() / cell).ceil().max(1.0) as usize;
+        let rows = (area.h() / cell).ceil().max(1.0) as usize;
+        Self { area, cell, cols, rows, weight: vec![0.0; cols * rows], log_freq: vec![0.0; cols * rows], count: 0 }
+    }
+
+    /// Suma un evento en `position`; los que caen fuera del área se descartan.
+    pub fn add(&mut self, position: Point2, frequency: f32, weight: f32) {
+        if !self.area.contains(position) || weight <= 0.0 {
+            return;
+        }
+        let col = (((position.x - self.area.left()) / self.cell) as usize).min(self.cols - 1);
+        let row = (((position.y - self.area.bottom()) / self.cell) as usize).min(self.rows - 1);
+        let index = row * self.cols + col;
+        self.weight[index] += weight;
+        self.log_freq[index] += frequency.max(1.0).log2() * weight;
+        self.count += 1;
+    }
+
+    /// Eventos sumados.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Un rectángulo por celda ocupada, más opaco cuanto más denso (escala logarítmica).
+    pub fn shapes(&self, theme: &VisualTheme) -> Vec<Shape> {
+        let max = self.weight.iter().copied().fold(0.0, f32::max);
+        if max <= 0.0 {
+            return Vec::new();
+        }
+        let norm = (1.0 + max).ln();
+        let mut shapes = Vec::new();
+        for (index, weight) in self.weight.iter().enumerate().filter(|(_, w)| **w > 0.0) {
+            let (row, col) = (index / self.cols, index % self.cols);
+            let frequency = (self.log_freq[index] / weight).exp2();
+            let alpha = 0.2 + 0.75 * (1.0 + weight).ln() / norm;
+            shapes.push(Shape::Rect {
+                center: pt2(
+                    self.area.left() + (col as f32 + 0.5) * self.cell,
+                    self.area.bottom() + (row as f32 + 0.5) * self.cell,
+                ),
+                size: vec2(self.cell, self.cell),
+                color: theme.note_color(frequency, alpha.min(0.95)),
+            });
+        }
+        shapes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dense_stream_is_bounded_by_cells() {
+        let area = Rect::from_w_h(120.0, 60.0);
+        let mut grid = DensityGrid::new(area, 6.0);
+        // 100 000 granos repartidos por el panel y uno fuera
+        for i in 0..100_000 {
+            let x = area.left() + (i % 997) as f32 / 997.0 * area.w();
+            let y = area.bottom() + (i % 101) as f32 / 101.0 * area.h();
+            grid.add(pt2(x, y), 440.0, 1.0);
+        }
+        grid.add(pt2(1000.0, 0.0), 440.0, 1.0);
+        assert_eq!(grid.len(), 100_000);
+        let shapes = grid.shapes(&VisualTheme::dark_theme());
+        assert!(shapes.len() <= 20 * 10);
+        assert!(shapes.iter().all(|s| matches!(s, Shape::Rect { color, .. } if color[3] <= 0.95)));
+    }
+}
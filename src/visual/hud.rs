@@ -0,0 +1,173 @@
+// src/visual/hud.rs
+
+//! 📟 HUD de rendimiento en pantalla
+//!
+//! Tiempo de frame y FPS, caudal OSC, fallos y descartes, último emisor, eventos activos por
+//! tipo y memoria. Se muestra con la tecla D (o `show_debug` / `show_fps` en `config.toml`)
+//! para no depender de la terminal durante un concierto.
+
+use nannou::prelude::*;
+use crate::events::MusicalEvent;
+use crate::model::Model;
+use crate::visual::rulers::TIME_RULER_H;
+use crate::visual::scene::{Shape, TextAlign};
+
+const HUD_W: f32 = 250.0;
+const MARGIN: f32 = 10.0;
+/// Peso de cada frame nuevo en la media móvil del tiempo de frame.
+const FRAME_SMOOTHING: f32 = 0.1;
+
+/// Tiempo de frame suavizado, mensajes por segundo y memoria residente del proceso.
+/// Se mide con el reloj del modelo, así que también funciona con relojes manuales u offline.
+#[derive(Debug, Clone, Default)]
+pub struct FrameMeter {
+    frame_secs: f32,
+    window_secs: f32,
+    window_messages: u32,
+    messages_per_sec: f32,
+    resident_bytes: Option<usize>,
+}
+
+impl FrameMeter {
+    /// Registra un frame de `dt` segundos en el que se consumieron `messages` mensajes OSC.
+    pub fn tick(&mut self, dt: f32, messages: u32) {
+        self.frame_secs = if self.frame_secs <= 0.0 { dt } else { self.frame_secs + (dt - self.frame_secs) * FRAME_SMOOTHING };
+        self.window_secs += dt;
+        self.window_messages += messages;
+        // Ventanas de un segundo: el caudal baja a cero en cuanto dejan de llegar mensajes
+        if self.window_secs >= 1.0 {
+            self.messages_per_sec = self.window_messages as f32 / self.window_secs;
+            self.window_secs = 0.0;
+            self.window_messages = 0;
+            self.resident_bytes = resident_memory_bytes();
+        }
+    }
+
+    pub fn frame_ms(&self) -> f32 {
+        self.frame_secs * 1000.0
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.frame_secs > 0.0 { 1.0 / self.frame_secs } else { 0.0 }
+    }
+
+    pub fn messages_per_sec(&self) -> f32 {
+        self.messages_per_sec
+    }
+
+    /// Memoria residente del proceso (solo Linux, leída una vez por segundo).
+    pub fn resident_bytes(&self) -> Option<usize> {
+        self.resident_bytes
+    }
+}
+
+fn resident_memory_bytes() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+fn kind_name(event: &MusicalEvent) -> &'static str {
+    match event {
+        MusicalEvent::Note { .. } | MusicalEvent::NoteColored { .. } => "notas",
+        MusicalEvent::Drone { .. } => "drones",
+        MusicalEvent::Cluster { .. } => "clusters",
+        MusicalEvent::Glissando { .. } => "glissandi",
+        MusicalEvent::Noise { .. } => "ruido",
+        MusicalEvent::Chord { .. } => "acordes",
+        MusicalEvent::Realtime(_) | MusicalEvent::AnalysisData { .. } => "análisis",
+    }
+}
+
+/// Número de eventos por tipo, en orden de primera aparición.
+pub fn event_counts(events: &[MusicalEvent]) -> Vec<(&'static str, usize)> {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for event in events {
+        let name = kind_name(event);
+        match counts.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((name, 1)),
+        }
+    }
+    counts
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1 << 20 {
+        format!("{:.1} MB", bytes as f32 / (1 << 20) as f32)
+    } else {
+        format!("{:.1} KB", bytes as f32 / 1024.0)
+    }
+}
+
+/// Panel del HUD en la esquina superior derecha de `win`.
+pub fn hud_shapes(win: Rect, model: &Model) -> Vec<Shape> {
+    let meter = &model.frame_meter;
+    let stats = &model.osc_stats;
+    let tracked_bytes = model.musical_events.iter().map(MusicalEvent::approx_size).sum::<usize>() + model.history.memory_bytes();
+
+    let mut lines = vec![
+        format!("{:.0} FPS  |  {:.1} ms/frame", meter.fps(), meter.frame_ms()),
+        format!(
+            "OSC {:.0} msg/s  |  {}",
+            meter.messages_per_sec(),
+            if stats.is_connected { "conectado" } else { "sin conexión" }
+        ),
+        format!("Recibidos {}  |  fallos {}", stats.total_received, stats.failed_messages),
+        format!("Descartados del historial: {}", model.history.dropped()),
+        format!("Último emisor: {}", stats.last_sender.as_deref().unwrap_or("-")),
+        format!("Eventos activos: {}  |  abiertos {}", model.musical_events.len(), model.active_notes.len()),
+    ];
+    let counts = event_counts(&model.musical_events);
+    if !counts.is_empty() {
+        let parts: Vec<String> = counts.iter().map(|(name, count)| format!("{name} {count}")).collect();
+        lines.push(format!("  {}", parts.join(", ")));
+    }
+    lines.push(format!("Historial: {} eventos", model.history.len()));
+    lines.push(match meter.resident_bytes() {
+        Some(rss) => format!("Memoria: {} (eventos {})", format_bytes(rss), format_bytes(tracked_bytes)),
+        None => format!("Memoria de eventos: {}", format_bytes(tracked_bytes)),
+    });
+
+    let theme = model.theme();
+    let line_h = theme.fonts.hud_size as f32 * 1.4;
+    let height = lines.len() as f32 * line_h + MARGIN;
+    let top = win.top() - TIME_RULER_H - MARGIN;
+    let left = win.right() - MARGIN - HUD_W;
+    let mut shapes = vec![Shape::rect(Rect::from_x_y_w_h(left + HUD_W / 2.0, top - height / 2.0, HUD_W, height), [0.0, 0.0, 0.0, 0.6])];
+    for (i, line) in lines.into_iter().enumerate() {
+        let y = top - MARGIN / 2.0 - line_h * (i as f32 + 0.5);
+        shapes.push(Shape::text(pt2(left + 8.0, y), line, theme.fonts.hud_size, theme.text, TextAlign::Left));
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_frame_meter_smooths_and_counts_per_second() {
+        let mut meter = FrameMeter::default();
+        meter.tick(0.25, 3);
+        assert!((meter.fps() - 4.0).abs() < 1e-3);
+        for _ in 0..3 {
+            meter.tick(0.25, 1);
+        }
+        // 3 + 3 mensajes en un segundo
+        assert!((meter.messages_per_sec() - 6.0).abs() < 1e-3);
+        meter.tick(1.0, 0);
+        assert_eq!(meter.messages_per_sec(), 0.0);
+        assert!(meter.frame_ms() > 250.0);
+    }
+
+    #[test]
+    fn test_event_counts_by_kind() {
+        let now = Instant::now();
+        let note = MusicalEvent::Note { frequency: 440.0, amplitude: 0.5, duration: 1.0, instrument: "default".to_string(), start_time: now };
+        let noise = MusicalEvent::Noise { center_freq: 1000.0, bandwidth: 200.0, amplitude: 0.5, duration: 1.0, instrument: "default".to_string(), start_time: now };
+        assert_eq!(event_counts(&[note.clone(), noise, note]), vec![("notas", 2), ("ruido", 1)]);
+    }
+}
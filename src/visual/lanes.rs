@@ -0,0 +1,266 @@
+// src/visual/lanes.rs
+
+use nannou::prelude::*;
+use crate::config::{AudioConfig, LanesConfig};
+use crate::visual::scene::{Shape, TextAlign};
+use crate::visual::styles::VisualTheme;
+
+/// Altura en píxeles de un carril plegado (solo la cabecera con el nombre).
+pub const COLLAPSED_LANE_H: f32 = 18.0;
+/// Rango mínimo del ajuste automático de alturas de un carril, en octavas.
+const MIN_AUTO_OCTAVES: f32 = 1.0;
+
+/// Carril de un instrumento o fuente de sonido.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lane {
+    pub name: String,
+    /// Altura relativa frente a los demás carriles desplegados.
+    pub weight: f32,
+    /// Rango de alturas fijo (Hz); sin él se ajusta a las notas recibidas.
+    pub range: Option<(f32, f32)>,
+    /// Frecuencias mínima y máxima observadas en el carril.
+    pub observed: Option<(f32, f32)>,
+    pub collapsed: bool,
+}
+
+impl Lane {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), weight: 1.0, range: None, observed: None, collapsed: false }
+    }
+}
+
+/// Distribución del área de dibujo en carriles horizontales, uno por fuente de sonido.
+/// El primer carril ocupa la franja superior de la ventana.
+#[derive(Debug, Clone)]
+pub struct LaneLayout {
+    pub lanes: Vec<Lane>,
+    /// Crear un carril para cada instrumento nuevo que aparezca.
+    pub auto_add: bool,
+    /// Ajustar el rango de alturas de cada carril a las notas que recibe.
+    pub auto_range: bool,
+    default_weight: f32,
+}
+
+impl Default for LaneLayout {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl LaneLayout {
+    pub fn new(labels: Vec<String>) -> Self {
+        Self { lanes: labels.into_iter().map(Lane::new).collect(), auto_add: false, auto_range: false, default_weight: 1.0 }
+    }
+
+    /// Carriles de `[lanes]`: primero los de `order`, después los de `[[lanes.lane]]` que no estén
+    /// en él y por último `extra` (canales de captura, instrumentos MIDI) en su orden.
+    pub fn from_config(config: &LanesConfig, extra: impl IntoIterator<Item = String>) -> Self {
+        let mut layout = Self {
+            lanes: Vec::new(),
+            auto_add: config.auto_add,
+            auto_range: config.auto_range,
+            default_weight: config.lane_height.max(0.05),
+        };
+        let names = config.order.iter().cloned().chain(config.lane.iter().map(|spec| spec.name.clone())).chain(extra);
+        for name in names {
+            layout.ensure(&name);
+        }
+        for spec in &config.lane {
+            if let Some(lane) = layout.lanes.iter_mut().find(|lane| lane.name == spec.name) {
+                lane.weight = spec.height.unwrap_or(lane.weight).max(0.05);
+                lane.range = spec.freq_min.zip(spec.freq_max).filter(|(min, max)| *min > 0.0 && max > min);
+                lane.collapsed = spec.collapsed;
+            }
+        }
+        layout
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lanes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lanes.len()
+    }
+
+    /// Índice del carril asignado a un instrumento, si existe.
+    pub fn index_of(&self, instrument: &str) -> Option<usize> {
+        self.lanes.iter().position(|lane| lane.name == instrument)
+    }
+
+    /// Índice del carril de `name`, creándolo al final si no existe.
+    fn ensure(&mut self, name: &str) -> usize {
+        self.index_of(name).unwrap_or_else(|| {
+            self.lanes.push(Lane { weight: self.default_weight, ..Lane::new(name) });
+            self.lanes.len() - 1
+        })
+    }
+
+    /// Registra una nota de `instrument`: crea su carril (con `auto_add`) y amplía el rango observado.
+    pub fn observe(&mut self, instrument: &str, freq: f32) {
+        let index = match self.index_of(instrument) {
+            Some(index) => index,
+            None if self.auto_add => self.ensure(instrument),
+            None => return,
+        };
+        if freq > 0.0 && freq.is_finite() {
+            let lane = &mut self.lanes[index];
+            lane.observed = Some(match lane.observed {
+                Some((min, max)) => (min.min(freq), max.max(freq)),
+                None => (freq, freq),
+            });
+        }
+    }
+
+    /// Pliega o despliega el carril `index`.
+    pub fn toggle_collapsed(&mut self, index: usize) {
+        if let Some(lane) = self.lanes.get_mut(index) {
+            lane.collapsed = !lane.collapsed;
+        }
+    }
+
+    /// Pliega o despliega el carril de `name`. Devuelve `false` si no existe.
+    pub fn set_collapsed(&mut self, name: &str, collapsed: bool) -> bool {
+        match self.index_of(name) {
+            Some(index) => {
+                self.lanes[index].collapsed = collapsed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rectángulo que ocupa el carril `index` dentro de `win`. Los plegados ocupan
+    /// `COLLAPSED_LANE_H` y el resto se reparte según el peso de cada carril.
+    pub fn lane_rect(&self, index: usize, win: Rect) -> Rect {
+        let collapsed = self.lanes.iter().filter(|lane| lane.collapsed).count() as f32;
+        let total_weight: f32 = self.lanes.iter().filter(|lane| !lane.collapsed).map(|lane| lane.weight).sum();
+        let free_h = (win.h() - collapsed * COLLAPSED_LANE_H).max(0.0);
+        let height = |lane: &Lane| {
+            if lane.collapsed {
+                COLLAPSED_LANE_H
+            } else if total_weight > 0.0 {
+                free_h * lane.weight / total_weight
+            } else {
+                win.h()
+            }
+        };
+        let top = win.top() - self.lanes.iter().take(index).map(height).sum::<f32>();
+        let lane_h = self.lanes.get(index).map_or(win.h(), height);
+        Rect::from_corners(pt2(win.left(), top - lane_h), pt2(win.right(), top))
+    }
+
+    /// Carril cuya cabecera (la franja del nombre) contiene `point`, para plegarlo con un clic.
+    pub fn header_at(&self, win: Rect, point: Point2) -> Option<usize> {
+        (0..self.lanes.len()).find(|&index| {
+            let lane = self.lane_rect(index, win);
+            lane.x.contains(point.x) && point.y <= lane.top() && point.y >= lane.top() - COLLAPSED_LANE_H
+        })
+    }
+
+    /// Rango de alturas (Hz) del carril: el fijo de la configuración, el observado (con un
+    /// semitono de margen y al menos una octava) o, en su defecto, el de la vista.
+    pub fn freq_range(&self, index: usize, audio_config: &AudioConfig) -> (f32, f32) {
+        let Some(lane) = self.lanes.get(index) else {
+            return (audio_config.freq_min, audio_config.freq_max);
+        };
+        match (lane.range, lane.observed) {
+            (Some(range), _) => range,
+            (None, Some((min, max))) if self.auto_range => {
+                let (mut lo, mut hi) = (min.log2() - 1.0 / 12.0, max.log2() + 1.0 / 12.0);
+                let missing = MIN_AUTO_OCTAVES - (hi - lo);
+                if missing > 0.0 {
+                    lo -= missing / 2.0;
+                    hi += missing / 2.0;
+                }
+                (lo.exp2(), hi.exp2())
+            }
+            _ => (audio_config.freq_min, audio_config.freq_max),
+        }
+    }
+
+    /// Posición vertical de una frecuencia dentro del carril, en escala logarítmica sobre el
+    /// rango del carril y por debajo de su cabecera. En un carril plegado todo cae en su centro.
+    pub fn map_freq_in_lane(&self, index: usize, freq: f32, audio_config: &AudioConfig, win: Rect) -> f32 {
+        let lane = self.lane_rect(index, win);
+        if self.lanes.get(index).is_some_and(|lane| lane.collapsed) {
+            return lane.y();
+        }
+        let margin = lane.h() * 0.1;
+        let (freq_min, freq_max) = self.freq_range(index, audio_config);
+        let log_freq = freq.max(freq_min).log2();
+        let top = (lane.top() - margin.max(COLLAPSED_LANE_H)).max(lane.bottom() + margin);
+        map_range(log_freq, freq_min.log2(), freq_max.log2(), lane.bottom() + margin, top)
+    }
+
+    /// Separadores, nombre de cada carril (▾ desplegado, ▸ plegado) y su rango de alturas.
+    pub fn shapes(&self, win: Rect, audio_config: &AudioConfig, theme: &VisualTheme) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        for (index, lane_info) in self.lanes.iter().enumerate() {
+            let lane = self.lane_rect(index, win);
+            let [r, g, b, _] = theme.text;
+            if index % 2 == 1 || lane_info.collapsed {
+                shapes.push(Shape::rect(lane, [r, g, b, if lane_info.collapsed { 0.06 } else { 0.03 }]));
+            }
+            shapes.push(Shape::line(pt2(lane.left(), lane.bottom()), pt2(lane.right(), lane.bottom()), theme.lines.grid, theme.grid));
+            let label = if lane_info.collapsed {
+                format!("▸ {}", lane_info.name)
+            } else {
+                let (min, max) = self.freq_range(index, audio_config);
+                format!("▾ {}   {:.0}–{:.0} Hz", lane_info.name, min, max)
+            };
+            let y = lane.top() - COLLAPSED_LANE_H / 2.0;
+            shapes.push(Shape::text(pt2(lane.left() + 10.0, y), label, theme.fonts.legend_size, theme.text, TextAlign::Left));
+        }
+        shapes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LaneSpec;
+
+    #[test]
+    fn test_lane_heights_follow_weights_and_collapse() {
+        let config = LanesConfig {
+            order: vec!["violin".to_string(), "cello".to_string()],
+            lane: vec![LaneSpec { name: "cello".to_string(), height: Some(3.0), ..LaneSpec::default() }],
+            ..LanesConfig::default()
+        };
+        let mut layout = LaneLayout::from_config(&config, vec!["perc".to_string(), "violin".to_string()]);
+        assert_eq!(layout.lanes.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(), ["violin", "cello", "perc"]);
+
+        let win = Rect::from_x_y_w_h(0.0, 0.0, 500.0, 500.0);
+        assert_eq!(layout.lane_rect(0, win).h(), 100.0);
+        assert_eq!(layout.lane_rect(1, win).h(), 300.0);
+        assert_eq!(layout.lane_rect(2, win).top(), win.bottom() + 100.0);
+
+        layout.toggle_collapsed(1);
+        assert_eq!(layout.lane_rect(1, win).h(), COLLAPSED_LANE_H);
+        assert_eq!(layout.lane_rect(0, win).h(), (500.0 - COLLAPSED_LANE_H) / 2.0);
+        assert_eq!(layout.header_at(win, pt2(0.0, layout.lane_rect(1, win).y())), Some(1));
+    }
+
+    #[test]
+    fn test_auto_lanes_fit_their_observed_range() {
+        let config = LanesConfig { auto_add: true, auto_range: true, ..LanesConfig::default() };
+        let mut layout = LaneLayout::from_config(&config, Vec::new());
+        layout.observe("flute", 440.0);
+        layout.observe("flute", 1760.0);
+        layout.observe("bass", 55.0);
+        assert_eq!(layout.len(), 2);
+
+        let audio = AudioConfig { freq_min: 20.0, freq_max: 20000.0, ..AudioConfig::default() };
+        let (min, max) = layout.freq_range(0, &audio);
+        assert!((min - 440.0 / 2f32.powf(1.0 / 12.0)).abs() < 0.1 && (max - 1760.0 * 2f32.powf(1.0 / 12.0)).abs() < 0.5);
+        // Una sola nota: una octava centrada en ella
+        let (min, max) = layout.freq_range(1, &audio);
+        assert!(((max / min).log2() - 1.0).abs() < 1e-4);
+
+        let win = Rect::from_w_h(400.0, 400.0);
+        let lane = layout.lane_rect(0, win);
+        let (low, high) = (layout.map_freq_in_lane(0, 440.0, &audio, win), layout.map_freq_in_lane(0, 1760.0, &audio, win));
+        assert!(lane.bottom() < low && low < high && high < lane.top() - COLLAPSED_LANE_H + 1e-3);
+    }
+}
@@ -0,0 +1,163 @@
+// src/visual/meters.rs
+
+use nannou::prelude::*;
+use std::collections::VecDeque;
+use crate::config::MetersConfig;
+use crate::loudness::{DynamicsPoint, LoudnessReadings};
+
+/// Rango vertical de las barras y de la tira de dinámica (LUFS / dBTP).
+const METER_FLOOR: f32 = -60.0;
+const METER_CEIL: f32 = 0.0;
+
+const PANEL_W: f32 = 360.0;
+const PANEL_H: f32 = 220.0;
+const MARGIN: f32 = 16.0;
+
+fn format_level(value: f32) -> String {
+    if value.is_finite() {
+        format!("{value:.1}")
+    } else {
+        "-inf".to_string()
+    }
+}
+
+fn level_to_y(value: f32, bottom: f32, top: f32) -> f32 {
+    let v = if value.is_finite() { value.clamp(METER_FLOOR, METER_CEIL) } else { METER_FLOOR };
+    map_range(v, METER_FLOOR, METER_CEIL, bottom, top)
+}
+
+/// Panel superpuesto con sonoridad EBU R128 (M, S, I, LRA), pico verdadero
+/// y una tira con la evolución de la dinámica.
+pub fn draw_loudness_panel(
+    draw: &Draw,
+    win: Rect,
+    readings: &LoudnessReadings,
+    history: &VecDeque<DynamicsPoint>,
+    config: &MetersConfig,
+) {
+    let panel = Rect::from_w_h(PANEL_W, PANEL_H)
+        .bottom_right_of(win.pad(MARGIN));
+    draw.rect()
+        .xy(panel.xy())
+        .wh(panel.wh())
+        .color(rgba(0.02, 0.02, 0.05, 0.85))
+        .stroke(rgba(0.4, 0.5, 0.7, 0.6))
+        .stroke_weight(1.0);
+
+    let title = if readings.from_amplitude { "EBU R128 (amplitud OSC)" } else { "EBU R128" };
+    draw.text(title)
+        .font_size(12)
+        .color(rgba(0.9, 0.9, 1.0, 1.0))
+        .left_justify()
+        .no_line_wrap()
+        .w(panel.w() - 20.0)
+        .x(panel.x())
+        .y(panel.top() - 12.0);
+
+    // --- Barras verticales: M, S, I y pico verdadero ---
+    let bars_area = Rect::from_corners(
+        pt2(panel.left() + 10.0, panel.bottom() + 30.0),
+        pt2(panel.left() + 150.0, panel.top() - 28.0),
+    );
+    let bars = [
+        ("M", readings.momentary),
+        ("S", readings.short_term),
+        ("I", readings.integrated),
+        ("TP", readings.true_peak_db),
+    ];
+    let bar_w = bars_area.w() / bars.len() as f32;
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let x = bars_area.left() + bar_w * (i as f32 + 0.5);
+        let y_top = level_to_y(*value, bars_area.bottom(), bars_area.top());
+        let over = if *label == "TP" { *value > config.max_true_peak } else { *value > config.target_lufs + config.tolerance_lu };
+        let color = if over { rgba(1.0, 0.3, 0.2, 0.9) } else { rgba(0.3, 0.8, 0.5, 0.9) };
+        draw.rect()
+            .x_y(x, bars_area.bottom())
+            .w(bar_w * 0.6)
+            .h(1.0)
+            .color(rgba(0.4, 0.4, 0.5, 0.5));
+        draw.rect()
+            .x_y(x, (bars_area.bottom() + y_top) / 2.0)
+            .w(bar_w * 0.6)
+            .h((y_top - bars_area.bottom()).max(0.0))
+            .color(color);
+        draw.text(label)
+            .font_size(10)
+            .color(WHITE)
+            .x_y(x, bars_area.bottom() - 10.0);
+        draw.text(&format_level(*value))
+            .font_size(9)
+            .color(rgba(0.8, 0.8, 0.9, 1.0))
+            .x_y(x, bars_area.bottom() - 22.0);
+    }
+
+    // Objetivo de sonoridad (p. ej. -23 LUFS)
+    let target_y = level_to_y(config.target_lufs, bars_area.bottom(), bars_area.top());
+    draw.line()
+        .points(pt2(bars_area.left(), target_y), pt2(bars_area.left() + bar_w * 3.0, target_y))
+        .color(rgba(1.0, 0.9, 0.3, 0.8))
+        .stroke_weight(1.0);
+
+    // --- Resumen numérico ---
+    let summary = format!(
+        "LRA {:.1} LU\nTP máx {} dBTP\nObjetivo {:.0} LUFS",
+        readings.loudness_range,
+        format_level(readings.max_true_peak_db),
+        config.target_lufs,
+    );
+    draw.text(&summary)
+        .font_size(10)
+        .color(rgba(0.8, 0.8, 0.9, 1.0))
+        .left_justify()
+        .no_line_wrap()
+        .w(190.0)
+        .x(panel.right() - 105.0)
+        .y(panel.top() - 50.0);
+
+    // --- Tira de dinámica en el tiempo ---
+    let strip = Rect::from_corners(
+        pt2(panel.left() + 160.0, panel.bottom() + 12.0),
+        pt2(panel.right() - 10.0, panel.top() - 90.0),
+    );
+    draw.rect()
+        .xy(strip.xy())
+        .wh(strip.wh())
+        .color(rgba(0.0, 0.0, 0.0, 0.4));
+    draw.line()
+        .points(
+            pt2(strip.left(), level_to_y(config.target_lufs, strip.bottom(), strip.top())),
+            pt2(strip.right(), level_to_y(config.target_lufs, strip.bottom(), strip.top())),
+        )
+        .color(rgba(1.0, 0.9, 0.3, 0.4))
+        .stroke_weight(1.0);
+
+    if history.len() >= 2 {
+        let step = strip.w() / (history.len() - 1) as f32;
+        for (i, point) in history.iter().enumerate() {
+            let x = strip.left() + i as f32 * step;
+            if point.peak_db.is_finite() {
+                draw.line()
+                    .points(
+                        pt2(x, strip.bottom()),
+                        pt2(x, level_to_y(point.peak_db, strip.bottom(), strip.top())),
+                    )
+                    .color(rgba(0.5, 0.5, 0.9, 0.25))
+                    .stroke_weight(step.max(1.0));
+            }
+        }
+        let momentary = history.iter().enumerate().map(|(i, p)| {
+            pt2(strip.left() + i as f32 * step, level_to_y(p.momentary, strip.bottom(), strip.top()))
+        });
+        draw.polyline()
+            .weight(1.0)
+            .points(momentary)
+            .color(rgba(0.6, 0.9, 0.7, 0.5));
+        let short_term = history.iter().enumerate().map(|(i, p)| {
+            pt2(strip.left() + i as f32 * step, level_to_y(p.short_term, strip.bottom(), strip.top()))
+        });
+        draw.polyline()
+            .weight(2.0)
+            .points(short_term)
+            .color(rgba(0.3, 1.0, 0.5, 0.9));
+    }
+}
@@ -5,5 +5,26 @@ pub mod shader_manager;
 pub mod visual_note; // Asegúrate de que este archivo exista
 pub mod visuals;
 pub mod audio_visual_mapping;
+pub mod lanes;
+pub mod meters;
+pub mod analysis_view;
+pub mod timeline;
+pub mod rulers;
+pub mod beat_grid;
+pub mod viewport;
+pub mod scene;
+pub mod svg;
+pub mod raster;
+pub mod hud;
+pub mod density;
+pub mod particles;
+pub mod staff;
+pub mod piano_roll;
+pub mod radial;
+pub mod outputs;
+pub mod styles;
+pub mod uniforms;
+pub mod uniforms_gpu;
+pub mod time_sync;
 
 pub use visual_note::VisualNote; // Exportar VisualNote
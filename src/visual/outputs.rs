@@ -0,0 +1,250 @@
+// src/visual/outputs.rs
+
+//! 🖥️ Ventanas de concierto
+//!
+//! Con `[outputs].projection` se abren dos ventanas sobre el mismo modelo. La proyección va a
+//! pantalla completa en el monitor elegido y solo muestra la escena, sin HUD, depuración ni
+//! barra de transporte. La ventana de control (la de `[window]`) queda en el portátil con los
+//! selectores de modo, una vista previa de la proyección, una vista de monitorización con su
+//! propio modo, el HUD y el registro de mensajes OSC. El teclado y los selectores de
+//! «Proyección» cambian el modo proyectado, y los de «Monitor» solo la vista del operador.
+
+use std::collections::VecDeque;
+use nannou::prelude::*;
+use nannou::window;
+use crate::config::OutputsConfig;
+use crate::model::{DisplayMode, Model};
+use crate::visual::scene::{build_scene, Shape, SceneOptions, TextAlign};
+use crate::visual::styles::VisualTheme;
+
+/// Ancho de la columna de selectores de la ventana de control.
+const SIDEBAR_W: f32 = 170.0;
+const BUTTON_H: f32 = 22.0;
+const HEADER_H: f32 = 26.0;
+const PAD: f32 = 8.0;
+
+/// Papel de una ventana.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowRole {
+    /// Ventana única (sin `[outputs].projection`): escena, HUD y depuración juntos.
+    Main,
+    /// Salida limpia para el proyector.
+    Projection,
+    /// Ventana del operador.
+    Control,
+}
+
+/// Qué vista cambia un selector de modo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeTarget {
+    Projection,
+    Monitor,
+}
+
+/// Ventanas abiertas y modo de la vista de monitorización.
+#[derive(Debug, Clone)]
+pub struct Outputs {
+    pub projection: Option<window::Id>,
+    pub control: Option<window::Id>,
+    pub control_mode: DisplayMode,
+}
+
+impl Outputs {
+    pub fn from_config(config: &OutputsConfig) -> Self {
+        Self { projection: None, control: None, control_mode: DisplayMode::from_name(&config.control_mode).unwrap_or(DisplayMode::Events) }
+    }
+
+    pub fn role(&self, id: window::Id) -> WindowRole {
+        if self.projection == Some(id) {
+            WindowRole::Projection
+        } else if self.projection.is_some() && self.control == Some(id) {
+            WindowRole::Control
+        } else {
+            WindowRole::Main
+        }
+    }
+}
+
+/// Últimos mensajes OSC recibidos, del más reciente al más antiguo.
+#[derive(Debug, Clone)]
+pub struct MessageLog {
+    entries: VecDeque<(f32, String)>,
+    capacity: usize,
+}
+
+impl MessageLog {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Añade un mensaje recibido a los `time` segundos de sesión.
+    pub fn push(&mut self, time: f32, addr: &str, args: &[nannou_osc::Type]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_back();
+        }
+        let args: Vec<String> = args.iter().map(format_arg).collect();
+        self.entries.push_front((time, format!("{addr} {}", args.join(" ")).trim_end().to_string()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(f32, String)> {
+        self.entries.iter()
+    }
+}
+
+fn format_arg(arg: &nannou_osc::Type) -> String {
+    match arg {
+        nannou_osc::Type::Int(i) => i.to_string(),
+        nannou_osc::Type::Long(l) => l.to_string(),
+        nannou_osc::Type::Float(f) => format!("{f:.3}"),
+        nannou_osc::Type::Double(d) => format!("{d:.3}"),
+        nannou_osc::Type::String(s) => s.clone(),
+        nannou_osc::Type::Bool(b) => b.to_string(),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Disposición de la ventana de control: selectores a la izquierda, monitorización arriba y
+/// vista previa de la proyección y registro de mensajes abajo.
+#[derive(Debug, Clone, Copy)]
+pub struct ControlLayout {
+    pub sidebar: Rect,
+    pub monitor: Rect,
+    pub preview: Rect,
+    pub log: Rect,
+}
+
+impl ControlLayout {
+    pub fn new(win: Rect) -> Self {
+        let sidebar = Rect::from_corners(win.bottom_left(), pt2(win.left() + SIDEBAR_W.min(win.w() / 3.0), win.top()));
+        let split_y = win.bottom() + win.h() * 0.4;
+        let middle_x = (sidebar.right() + win.right()) / 2.0;
+        Self {
+            sidebar,
+            monitor: Rect::from_corners(pt2(sidebar.right(), split_y), win.top_right()),
+            preview: Rect::from_corners(pt2(sidebar.right(), win.bottom()), pt2(middle_x, split_y)).pad(PAD),
+            log: Rect::from_corners(pt2(middle_x, win.bottom()), pt2(win.right(), split_y)).pad(PAD),
+        }
+    }
+
+    /// Selectores de modo: primero los de la proyección y después los de la monitorización.
+    pub fn buttons(&self) -> Vec<(Rect, ModeTarget, DisplayMode)> {
+        let mut buttons = Vec::new();
+        let mut top = self.sidebar.top() - HEADER_H;
+        for target in [ModeTarget::Projection, ModeTarget::Monitor] {
+            for mode in DisplayMode::ALL {
+                let button = Rect::from_corners(pt2(self.sidebar.left() + PAD, top - BUTTON_H), pt2(self.sidebar.right() - PAD, top));
+                buttons.push((button, target, mode));
+                top -= BUTTON_H + 2.0;
+            }
+            top -= HEADER_H;
+        }
+        buttons
+    }
+
+    /// Selector bajo `point`, para cambiar de modo con un clic.
+    pub fn button_at(&self, point: Point2) -> Option<(ModeTarget, DisplayMode)> {
+        self.buttons().into_iter().find(|(rect, _, _)| rect.contains(point)).map(|(_, target, mode)| (target, mode))
+    }
+}
+
+/// Selectores de modo con el activo resaltado (número de tecla en los de la proyección).
+fn selector_shapes(layout: &ControlLayout, projection_mode: DisplayMode, monitor_mode: DisplayMode, theme: &VisualTheme) -> Vec<Shape> {
+    let [r, g, b, _] = theme.text;
+    let mut shapes = vec![Shape::rect(layout.sidebar, [r, g, b, 0.05])];
+    let buttons = layout.buttons();
+    for (i, (title, target)) in [("PROYECCIÓN", ModeTarget::Projection), ("MONITOR", ModeTarget::Monitor)].into_iter().enumerate() {
+        let first = buttons[i * DisplayMode::ALL.len()].0;
+        shapes.push(Shape::text(pt2(first.left(), first.top() + HEADER_H / 2.0), title, theme.fonts.legend_size, theme.accent, TextAlign::Left));
+        for (key, (rect, _, mode)) in buttons.iter().filter(|(_, t, _)| *t == target).enumerate() {
+            let active = *mode == if target == ModeTarget::Projection { projection_mode } else { monitor_mode };
+            let [ar, ag, ab, _] = theme.accent;
+            shapes.push(Shape::rect(*rect, if active { [ar, ag, ab, 0.35] } else { [r, g, b, 0.06] }));
+            let label = match target {
+                ModeTarget::Projection => format!("{}  {}", key + 1, mode.label()),
+                ModeTarget::Monitor => mode.label().to_string(),
+            };
+            shapes.push(Shape::text(pt2(rect.left() + 6.0, rect.y()), label, theme.fonts.legend_size, theme.text, TextAlign::Left));
+        }
+    }
+    shapes
+}
+
+/// Registro de mensajes: del más reciente (arriba) hacia atrás, hasta llenar `area`.
+fn log_shapes(log: &MessageLog, area: Rect, theme: &VisualTheme) -> Vec<Shape> {
+    let mut shapes = vec![Shape::rect(area, [0.0, 0.0, 0.0, 0.35])];
+    let line_h = theme.fonts.legend_size as f32 * 1.5;
+    shapes.push(Shape::text(pt2(area.left() + PAD, area.top() - line_h / 2.0), "Mensajes OSC", theme.fonts.legend_size, theme.accent, TextAlign::Left));
+    let rows = ((area.h() / line_h) as usize).saturating_sub(1);
+    for (i, (time, message)) in log.iter().take(rows).enumerate() {
+        let y = area.top() - line_h * (i as f32 + 1.5);
+        shapes.push(Shape::text(pt2(area.left() + PAD, y), format!("{time:8.2}  {message}"), theme.fonts.legend_size, theme.text, TextAlign::Left));
+    }
+    shapes
+}
+
+/// Contenido de la ventana de control. `projection_bounds` es el tamaño de la ventana de
+/// proyección: la vista previa es su misma escena reducida, no una recomposición.
+pub fn control_shapes(model: &Model, layout: &ControlLayout, projection_bounds: Rect) -> Vec<Shape> {
+    let theme = model.theme();
+    let monitor_options = SceneOptions { mode: Some(model.outputs.control_mode), transport: true, ..Default::default() };
+    let mut shapes = build_scene(model, layout.monitor, &model.timeline_view(layout.monitor), monitor_options).fit_into(layout.monitor);
+
+    let projection = build_scene(model, projection_bounds, &model.timeline_view(projection_bounds), SceneOptions::default());
+    shapes.extend(projection.fit_into(layout.preview));
+    let [r, g, b, _] = theme.accent;
+    let frame = Rect::from_xy_wh(layout.preview.xy(), projection_bounds.wh() * (layout.preview.w() / projection_bounds.w().max(1.0)).min(layout.preview.h() / projection_bounds.h().max(1.0)));
+    shapes.push(Shape::Polyline {
+        points: vec![frame.top_left(), frame.top_right(), frame.bottom_right(), frame.bottom_left(), frame.top_left()],
+        weight: 1.0,
+        color: [r, g, b, 0.8],
+    });
+    shapes.push(Shape::text(pt2(frame.left() + 6.0, frame.top() - 10.0), format!("Proyección: {}", model.display_mode.label()), theme.fonts.legend_size, theme.text, TextAlign::Left));
+
+    shapes.extend(selector_shapes(layout, model.display_mode, model.outputs.control_mode, theme));
+    shapes.extend(log_shapes(&model.message_log, layout.log, theme));
+    shapes.extend(crate::visual::hud::hud_shapes(layout.monitor, model));
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual::scene::Scene;
+
+    #[test]
+    fn test_control_layout_and_preview_fit() {
+        let layout = ControlLayout::new(Rect::from_w_h(1200.0, 800.0));
+        assert_eq!(layout.sidebar.w(), SIDEBAR_W);
+        assert_eq!(layout.monitor.bottom(), layout.preview.top() + PAD);
+        let buttons = layout.buttons();
+        assert_eq!(buttons.len(), 2 * DisplayMode::ALL.len());
+        let (rect, _, _) = buttons[DisplayMode::ALL.len() + 2];
+        assert_eq!(layout.button_at(rect.xy()), Some((ModeTarget::Monitor, DisplayMode::Drones)));
+        assert_eq!(layout.button_at(layout.monitor.xy()), None);
+
+        // Una escena de 1920x1080 en un hueco de 480x480: escala 1/4, centrada
+        let mut scene = Scene::new(Rect::from_w_h(1920.0, 1080.0), [0.0, 0.0, 0.0, 1.0]);
+        scene.extend([Shape::line(pt2(960.0, 0.0), pt2(960.0, 540.0), 4.0, [1.0; 4])]);
+        let fitted = scene.fit_into(Rect::from_x_y_w_h(100.0, 0.0, 480.0, 480.0));
+        assert_eq!(fitted[0], Shape::rect(Rect::from_x_y_w_h(100.0, 0.0, 480.0, 270.0), [0.0, 0.0, 0.0, 1.0]));
+        assert_eq!(fitted[1], Shape::line(pt2(340.0, 0.0), pt2(340.0, 135.0), 1.0, [1.0; 4]));
+
+        let mut log = MessageLog::new(2);
+        log.push(0.5, "/note", &[nannou_osc::Type::Float(440.0), nannou_osc::Type::Int(1)]);
+        log.push(1.0, "/clear", &[]);
+        log.push(1.5, "/theme", &[nannou_osc::Type::String("dark".to_string())]);
+        let lines: Vec<&str> = log.iter().map(|(_, line)| line.as_str()).collect();
+        assert_eq!(lines, ["/theme dark", "/clear"]);
+    }
+}
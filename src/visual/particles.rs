@@ -0,0 +1,180 @@
+// src/visual/particles.rs
+
+//! ✨ Capa de partículas
+//!
+//! Los ataques fuertes emiten ráfagas y los drones desprenden partículas lentas. Cada
+//! partícula está anclada a un instante de sesión y una frecuencia, de modo que se desplaza
+//! con la línea de tiempo y el zoom; su desplazamiento propio se mide en píxeles.
+//! El generador aleatorio tiene semilla fija (`[particles].seed`): con el mismo reloj, los
+//! renders offline y los tests producen exactamente las mismas partículas.
+
+use nannou::prelude::*;
+use crate::config::ParticlesConfig;
+use crate::events::map_freq_to_y;
+use crate::visual::scene::{Color, EventLayout, Shape};
+
+/// Generador SplitMix64: rápido, sin dependencias y con la misma secuencia en cualquier plataforma.
+#[derive(Debug, Clone)]
+pub struct SeededRng(u64);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Valor uniforme en `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+/// Punto de la partitura al que se ancla una partícula: instante de sesión (s), frecuencia (Hz)
+/// y carril del instrumento, si lo tiene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchor {
+    pub time: f32,
+    pub freq: f32,
+    pub lane: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Particle {
+    pub anchor: Anchor,
+    /// Desplazamiento y velocidad respecto al ancla, en píxeles.
+    pub offset: Vec2,
+    pub velocity: Vec2,
+    pub size: f32,
+    pub color: Color,
+    pub life: f32,
+    pub max_life: f32,
+}
+
+/// Partículas vivas con un máximo fijo; las que no caben se descartan.
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+    pub particles: Vec<Particle>,
+    pub max_particles: usize,
+    /// Fracción de velocidad que se conserva por cada 1/60 s.
+    pub friction: f32,
+    rng: SeededRng,
+}
+
+impl ParticleSystem {
+    pub fn new(max_particles: usize, seed: u64) -> Self {
+        Self {
+            particles: Vec::with_capacity(max_particles.min(4096)),
+            max_particles,
+            friction: 0.98,
+            rng: SeededRng::new(seed),
+        }
+    }
+
+    pub fn from_config(config: &ParticlesConfig) -> Self {
+        Self { friction: config.friction.clamp(0.0, 1.0), ..Self::new(config.max_particles, config.seed) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Avanza `dt` segundos: mueve, frena y elimina las partículas que han agotado su vida.
+    pub fn update(&mut self, dt: f32) {
+        let damping = self.friction.powf(dt * 60.0);
+        for particle in &mut self.particles {
+            particle.offset += particle.velocity * dt;
+            particle.velocity *= damping;
+            particle.life -= dt;
+        }
+        self.particles.retain(|p| p.life > 0.0);
+    }
+
+    pub fn emit_particle(&mut self, anchor: Anchor, offset: Vec2, velocity: Vec2, color: Color, life: f32) {
+        if self.particles.len() < self.max_particles {
+            self.particles.push(Particle { anchor, offset, velocity, size: 2.0, color, life, max_life: life });
+        }
+    }
+
+    /// Ráfaga radial de `count` partículas (ataques).
+    pub fn emit_burst(&mut self, anchor: Anchor, count: usize, color: Color) {
+        for _ in 0..count {
+            let angle = self.rng.range(0.0, TAU);
+            let speed = self.rng.range(20.0, 100.0);
+            let life = self.rng.range(0.5, 2.0);
+            self.emit_particle(anchor, Vec2::ZERO, vec2(angle.cos(), angle.sin()) * speed, color, life);
+        }
+    }
+
+    /// Partícula lenta que asciende desde un punto al azar de `[-spread, spread]` px (drones).
+    pub fn shed(&mut self, anchor: Anchor, spread: f32, color: Color) {
+        let offset = vec2(self.rng.range(-spread, spread), self.rng.range(-3.0, 3.0));
+        let velocity = vec2(self.rng.range(-6.0, 6.0), self.rng.range(8.0, 25.0));
+        let life = self.rng.range(2.0, 4.0);
+        self.emit_particle(anchor, offset, velocity, color, life);
+    }
+
+    /// Valor uniforme en `[0, 1)` del mismo generador (para decidir emisiones).
+    pub fn chance(&mut self) -> f32 {
+        self.rng.next_f32()
+    }
+
+    /// Primitivas de las partículas visibles en el panel de `layout`, atenuadas según la vida restante.
+    pub fn shapes(&self, layout: &EventLayout) -> Vec<Shape> {
+        self.particles
+            .iter()
+            .filter_map(|p| {
+                let Anchor { time, freq, lane } = p.anchor;
+                let y = match lane {
+                    Some(lane) => layout.lanes.map_freq_in_lane(lane, freq, layout.audio_view, layout.pane),
+                    None => map_freq_to_y(freq, layout.audio_view, layout.pane),
+                };
+                let center = pt2(layout.timeline.time_to_x(time), y) + p.offset;
+                let fade = (p.life / p.max_life).clamp(0.0, 1.0);
+                let shape = Shape::Ellipse { center, radius: p.size, color: [p.color[0], p.color[1], p.color[2], p.color[3] * fade] };
+                shape.overlaps(layout.pane).then_some(shape)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_emission_is_reproducible_and_capped() {
+        let run = |seed: u64| {
+            let mut system = ParticleSystem::new(50, seed);
+            for i in 0..10 {
+                let anchor = Anchor { time: i as f32, freq: 440.0, lane: None };
+                system.emit_burst(anchor, 8, [1.0; 4]);
+                system.shed(anchor, 20.0, [1.0; 4]);
+                system.update(1.0 / 30.0);
+            }
+            system
+        };
+        let (a, b) = (run(7), run(7));
+        assert_eq!(a.particles, b.particles);
+        assert_eq!(a.len(), 50);
+        assert_ne!(a.particles, run(8).particles);
+    }
+}
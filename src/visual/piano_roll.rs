@@ -0,0 +1,232 @@
+// src/visual/piano_roll.rs
+
+//! 🎹 Modo piano roll (tecla 7)
+//!
+//! Un teclado vertical a la izquierda ilumina las teclas que suenan en el instante mostrado.
+//! Cada nota es un rectángulo en la fila de su semitono más cercano, de su inicio a su final,
+//! tanto más opaco cuanto mayor es su amplitud (velocidad). El rango de teclas se configura en
+//! `[piano_roll]`; lo que queda fuera no ocupa ninguna fila y se marca con una raya fina en el
+//! borde superior o inferior, durante el tiempo que suena.
+
+use nannou::prelude::*;
+use crate::config::PianoRollConfig;
+use crate::events::MusicalEvent;
+use crate::model::Model;
+use crate::pitch::{freq_to_midi, is_natural, note_name};
+use crate::visual::scene::{Color, Shape, TextAlign};
+use crate::visual::styles::VisualTheme;
+use crate::visual::timeline::TimelineView;
+
+/// Alto (px) de la raya que marca las notas por encima o por debajo del rango de teclas.
+const OVERFLOW_MARK_H: f32 = 3.0;
+
+/// Geometría del piano roll dentro de `pane`: una fila por tecla, de `low` (abajo) a `high`.
+#[derive(Debug, Clone, Copy)]
+pub struct PianoRoll {
+    pub pane: Rect,
+    pub low: i32,
+    pub high: i32,
+    pub keyboard_w: f32,
+}
+
+impl PianoRoll {
+    pub fn new(pane: Rect, config: &PianoRollConfig) -> Self {
+        let low = config.low_note.clamp(0, 127);
+        let high = config.high_note.clamp(low, 127);
+        Self { pane, low, high, keyboard_w: config.keyboard_width.clamp(0.0, pane.w() / 2.0) }
+    }
+
+    pub fn row_h(&self) -> f32 {
+        self.pane.h() / (self.high - self.low + 1) as f32
+    }
+
+    /// Tecla (número MIDI) de una frecuencia: semitono más cercano, aunque quede fuera del rango.
+    pub fn key_for(&self, freq: f32, a4: f32) -> i32 {
+        freq_to_midi(freq, a4).round() as i32
+    }
+
+    pub fn contains(&self, key: i32) -> bool {
+        (self.low..=self.high).contains(&key)
+    }
+
+    /// Centro vertical de la fila de `key`.
+    pub fn row_y(&self, key: i32) -> f32 {
+        self.pane.bottom() + ((key - self.low) as f32 + 0.5) * self.row_h()
+    }
+
+    /// Borde izquierdo de la zona de notas.
+    pub fn roll_left(&self) -> f32 {
+        self.pane.left() + self.keyboard_w
+    }
+
+    /// Filas de las teclas negras sombreadas y una línea en cada Do.
+    pub fn background_shapes(&self, theme: &VisualTheme) -> Vec<Shape> {
+        let [r, g, b, _] = theme.text;
+        let mut shapes = Vec::new();
+        for key in self.low..=self.high {
+            let y = self.row_y(key);
+            if !is_natural(key) {
+                let row = Rect::from_x_y_w_h((self.roll_left() + self.pane.right()) / 2.0, y, self.pane.right() - self.roll_left(), self.row_h());
+                shapes.push(Shape::rect(row, [r, g, b, 0.04]));
+            }
+            if key.rem_euclid(12) == 0 {
+                let bottom = y - self.row_h() / 2.0;
+                shapes.push(Shape::line(pt2(self.roll_left(), bottom), pt2(self.pane.right(), bottom), theme.lines.grid, theme.grid));
+            }
+        }
+        shapes
+    }
+
+    /// Teclado vertical; las teclas de `lit` (tecla, color) se iluminan.
+    pub fn keyboard_shapes(&self, lit: &[(i32, Color)], theme: &VisualTheme) -> Vec<Shape> {
+        let area = Rect::from_corners(pt2(self.pane.left(), self.pane.bottom()), pt2(self.roll_left(), self.pane.top()));
+        let mut shapes = vec![Shape::rect(area, [0.92, 0.92, 0.9, 1.0])];
+        let row_h = self.row_h();
+        let lit_color = |key: i32| lit.iter().rev().find(|(k, _)| *k == key).map(|(_, color)| *color);
+        for key in self.low..=self.high {
+            let y = self.row_y(key);
+            if is_natural(key) {
+                if let Some(color) = lit_color(key) {
+                    shapes.push(Shape::Rect { center: pt2(area.x(), y), size: vec2(area.w(), row_h), color });
+                }
+                // Separación entre teclas blancas adyacentes (Mi-Fa y Si-Do)
+                if matches!(key.rem_euclid(12), 4 | 11) {
+                    let top = y + row_h / 2.0;
+                    shapes.push(Shape::line(pt2(area.left(), top), pt2(area.right(), top), 1.0, [0.5, 0.5, 0.5, 1.0]));
+                }
+            } else {
+                let width = area.w() * 0.6;
+                let color = lit_color(key).unwrap_or([0.1, 0.1, 0.12, 1.0]);
+                shapes.push(Shape::Rect { center: pt2(area.left() + width / 2.0, y), size: vec2(width, row_h * 0.9), color });
+            }
+            if key.rem_euclid(12) == 0 && row_h * 12.0 >= theme.fonts.label_size as f32 * 1.5 {
+                shapes.push(Shape::text(pt2(area.right() - 4.0, y), note_name(key), theme.fonts.label_size, [0.2, 0.2, 0.2, 1.0], TextAlign::Right));
+            }
+        }
+        shapes.push(Shape::line(pt2(area.right(), area.bottom()), pt2(area.right(), area.top()), 1.0, theme.grid));
+        shapes
+    }
+
+    /// Rectángulo de una nota de `key` entre los instantes `start` y `end` (segundos de sesión),
+    /// recortado a la zona de notas. `velocity` (0-1) da la opacidad. Una tecla fuera del rango
+    /// se marca con una raya fina en el borde superior o inferior.
+    pub fn note_shape(&self, timeline: &TimelineView, key: i32, start: f32, end: f32, velocity: f32, color: Color) -> Option<Shape> {
+        let x0 = timeline.time_to_x(start).max(self.roll_left());
+        let x1 = timeline.time_to_x(end).min(self.pane.right());
+        if x1 < x0 {
+            return None;
+        }
+        let alpha = color[3] * (0.25 + 0.75 * velocity.clamp(0.0, 1.0));
+        let (y, height) = match key {
+            k if k > self.high => (self.pane.top() - OVERFLOW_MARK_H / 2.0, OVERFLOW_MARK_H),
+            k if k < self.low => (self.pane.bottom() + OVERFLOW_MARK_H / 2.0, OVERFLOW_MARK_H),
+            k => (self.row_y(k), (self.row_h() * 0.85).max(1.0)),
+        };
+        Some(Shape::Rect {
+            center: pt2((x0 + x1) / 2.0, y),
+            size: vec2((x1 - x0).max(2.0), height),
+            color: [color[0], color[1], color[2], alpha],
+        })
+    }
+}
+
+/// Escena del modo piano roll: filas, notas (fijas, acordes y abiertas) y teclado.
+pub fn piano_roll_shapes(model: &Model, pane: Rect, timeline: &TimelineView, theme: &VisualTheme) -> Vec<Shape> {
+    let roll = PianoRoll::new(pane, &model.config.piano_roll);
+    let a4 = model.config.view.a4_reference;
+    let now = timeline.now;
+    let mut shapes = roll.background_shapes(theme);
+    let mut lit: Vec<(i32, Color)> = Vec::new();
+
+    let (visible_from, visible_to) = timeline.visible_range();
+    let lookback = visible_from - timeline.page_duration;
+    let archived = if model.history.is_empty() { Vec::new() } else { model.history_events_in(lookback, visible_to) };
+    for event in model.live_events_in(lookback, visible_to).iter().chain(archived.iter()) {
+        let Some(start_time) = event.start_time() else { continue };
+        let start = model.placement_time(start_time);
+        let (tones, velocity, duration, color): (Vec<f32>, f32, f32, Option<Color>) = match event {
+            MusicalEvent::Note { frequency, amplitude, duration, .. } => (vec![*frequency], *amplitude, *duration, None),
+            MusicalEvent::NoteColored { frequency, amplitude, duration, r, g, b, .. } => (vec![*frequency], *amplitude, *duration, Some([*r, *g, *b, 1.0])),
+            MusicalEvent::Chord { frequencies, amplitude, duration, .. } => (frequencies.clone(), *amplitude, *duration, None),
+            _ => continue,
+        };
+        let sounding = start <= now && now <= start + duration;
+        for freq in tones {
+            let key = roll.key_for(freq, a4);
+            let color = color.unwrap_or_else(|| theme.note_color(freq, 1.0));
+            shapes.extend(roll.note_shape(timeline, key, start, start + duration, velocity.abs(), color));
+            if sounding && roll.contains(key) {
+                lit.push((key, color));
+            }
+        }
+    }
+
+    // Notas abiertas (/note_on id ...): cada tramo del contorno en su fila
+    let clock_now = model.clock.now();
+    for note in model.visual_notes.iter().chain(model.active_notes.values()) {
+        let note_start = model.session_time(note.start_time);
+        for (segment_start, segment_end, frequency, amplitude) in note.segments(clock_now) {
+            let (from, to) = (note_start + segment_start, note_start + segment_end);
+            let key = roll.key_for(frequency, a4);
+            let color = theme.note_color(frequency, 1.0);
+            shapes.extend(roll.note_shape(timeline, key, from, to, amplitude.abs(), color));
+            if from <= now && now <= to && roll.contains(key) {
+                lit.push((key, color));
+            }
+        }
+    }
+
+    shapes.extend(roll.keyboard_shapes(&lit, theme));
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_snap_to_semitones_within_range() {
+        let config = PianoRollConfig { low_note: 48, high_note: 71, keyboard_width: 40.0 };
+        let roll = PianoRoll::new(Rect::from_x_y_w_h(0.0, 0.0, 400.0, 240.0), &config);
+        assert_eq!(roll.row_h(), 10.0);
+        // 445 Hz queda más cerca del La4 que del La#4
+        assert_eq!(roll.key_for(445.0, 440.0), 69);
+        assert!(roll.contains(69));
+        assert!(!roll.contains(roll.key_for(5000.0, 440.0)));
+        assert_eq!(roll.row_y(48), -115.0);
+
+        let timeline = TimelineView::range(0.0, 10.0, Rect::from_x_y_w_h(0.0, 0.0, 400.0, 240.0));
+        let loud = roll.note_shape(&timeline, 60, 2.0, 4.0, 1.0, [1.0; 4]).unwrap();
+        let soft = roll.note_shape(&timeline, 60, 2.0, 4.0, 0.0, [1.0; 4]).unwrap();
+        match (loud, soft) {
+            (Shape::Rect { size, color: loud, .. }, Shape::Rect { color: soft, .. }) => {
+                assert_eq!(size.x, 80.0);
+                assert!(loud[3] > soft[3]);
+            }
+            _ => panic!("se esperaban rectángulos"),
+        }
+        // Antes del teclado no se dibuja nada
+        assert!(roll.note_shape(&timeline, 60, 0.0, 0.5, 1.0, [1.0; 4]).is_none());
+    }
+
+    #[test]
+    fn test_out_of_range_notes_are_marked_at_the_edges() {
+        let config = PianoRollConfig { low_note: 48, high_note: 71, keyboard_width: 40.0 };
+        let pane = Rect::from_x_y_w_h(0.0, 0.0, 400.0, 240.0);
+        let roll = PianoRoll::new(pane, &config);
+        let timeline = TimelineView::range(0.0, 10.0, pane);
+        let edge = |freq: f32| match roll.note_shape(&timeline, roll.key_for(freq, 440.0), 2.0, 4.0, 1.0, [1.0; 4]) {
+            Some(Shape::Rect { center, size, .. }) => (center.y, size.y),
+            other => panic!("forma inesperada: {other:?}"),
+        };
+
+        // Ni la primera ni la última fila: una raya fina pegada al borde del panel
+        let (above, height) = edge(5000.0);
+        assert_eq!(height, OVERFLOW_MARK_H);
+        assert_eq!(above, pane.top() - OVERFLOW_MARK_H / 2.0);
+        let (below, _) = edge(30.0);
+        assert_eq!(below, pane.bottom() + OVERFLOW_MARK_H / 2.0);
+        // Una nota dentro del rango ocupa su fila
+        assert_eq!(edge(130.81).0, roll.row_y(48));
+    }
+}
@@ -0,0 +1,220 @@
+// src/visual/radial.rs
+
+//! 🕰️ Modo radial (tecla 8)
+//!
+//! Vista polar para piezas cíclicas e instalaciones. El radio es la altura, con la misma
+//! escala logarítmica que `map_freq_to_y`. En la variante de reloj el ángulo es la fase dentro
+//! del ciclo (un compás según `/tempo`, o `[radial].cycle_seconds`), empezando arriba y en el
+//! sentido de las agujas; una aguja marca el instante mostrado. En la rueda cromática el
+//! ángulo es la clase de altura (Do arriba). En ambas los eventos de los últimos
+//! `trail_cycles` ciclos se atenúan con la edad.
+
+use nannou::prelude::*;
+use crate::config::{AudioConfig, RadialConfig};
+use crate::events::{glissando_freq, MusicalEvent};
+use crate::model::Model;
+use crate::pitch::{freq_to_midi, NOTE_NAMES};
+use crate::tempo_map::TempoMap;
+use crate::visual::scene::{Color, Shape, TextAlign};
+use crate::visual::styles::VisualTheme;
+use crate::visual::timeline::TimelineView;
+
+/// Qué representa el ángulo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadialLayout {
+    /// Fase dentro del ciclo (reloj).
+    Clock,
+    /// Clase de altura (rueda cromática).
+    Chroma,
+}
+
+impl RadialLayout {
+    pub fn from_name(name: &str) -> Self {
+        if name.eq_ignore_ascii_case("chroma") { RadialLayout::Chroma } else { RadialLayout::Clock }
+    }
+}
+
+/// Geometría polar dentro de `bounds`.
+#[derive(Debug, Clone, Copy)]
+pub struct Polar {
+    pub center: Point2,
+    pub inner: f32,
+    pub outer: f32,
+}
+
+impl Polar {
+    pub fn new(bounds: Rect, inner_ratio: f32) -> Self {
+        let outer = (bounds.w().min(bounds.h()) / 2.0 - 40.0).max(10.0);
+        Self { center: bounds.xy(), inner: outer * inner_ratio.clamp(0.0, 0.9), outer }
+    }
+
+    /// Radio de una frecuencia: escala logarítmica entre el anillo interior y el exterior.
+    pub fn radius(&self, freq: f32, audio: &AudioConfig) -> f32 {
+        let log_freq = freq.max(audio.freq_min).log2();
+        map_range(log_freq, audio.freq_min.log2(), audio.freq_max.log2(), self.inner, self.outer).clamp(self.inner, self.outer)
+    }
+
+    /// Punto a `radius` con la fase `phase` (0-1): 0 arriba, en el sentido de las agujas.
+    pub fn point(&self, phase: f32, radius: f32) -> Point2 {
+        let angle = PI / 2.0 - phase * TAU;
+        self.center + vec2(angle.cos(), angle.sin()) * radius
+    }
+
+    /// Circunferencia de `radius` como polilínea cerrada.
+    fn circle(&self, radius: f32, weight: f32, color: Color) -> Shape {
+        let points = (0..=72).map(|i| self.point(i as f32 / 72.0, radius)).collect();
+        Shape::Polyline { points, weight, color }
+    }
+}
+
+/// Ciclo (en segundos) y fase de `t`: un compás si se conoce el tempo, si no `cycle_seconds`.
+/// La fase es absoluta (compás fraccionario o segundos divididos por el ciclo).
+pub fn cycle_position(tempo_map: &TempoMap, config: &RadialConfig, t: f32) -> (f32, f32) {
+    let bar = if config.cycle.eq_ignore_ascii_case("bar") {
+        tempo_map.beat_at(t).and_then(|beat| Some((tempo_map.bar_at_beat(beat)?, tempo_map.tempo_at(t)?)))
+    } else {
+        None
+    };
+    match bar {
+        Some((bar, (bpm, beats_per_bar))) => (beats_per_bar * 60.0 / bpm.max(1.0), bar),
+        None => {
+            let cycle = config.cycle_seconds.max(0.1);
+            (cycle, t / cycle)
+        }
+    }
+}
+
+/// Clase de altura (0-1, Do = 0) de una frecuencia.
+pub fn chroma_phase(freq: f32, a4: f32) -> f32 {
+    (freq_to_midi(freq, a4) / 12.0).rem_euclid(1.0)
+}
+
+fn with_alpha(color: Color, alpha: f32) -> Color {
+    [color[0], color[1], color[2], color[3] * alpha]
+}
+
+/// Escena del modo radial: anillos de octava, radios de pulso o de clase de altura, eventos y aguja.
+pub fn radial_shapes(model: &Model, bounds: Rect, timeline: &TimelineView, theme: &VisualTheme) -> Vec<Shape> {
+    let config = &model.config.radial;
+    let layout = RadialLayout::from_name(&config.layout);
+    let polar = Polar::new(bounds, config.inner_radius);
+    let audio = model.viewport.apply_to(&model.config.audio);
+    let a4 = model.config.view.a4_reference;
+    let now = timeline.now;
+    let (cycle, now_phase) = cycle_position(&model.tempo_map, config, now);
+    let trail = cycle * config.trail_cycles.max(0.1);
+    let mut shapes = Vec::new();
+
+    // Anillos en cada Do del rango visible
+    let grid = theme.grid;
+    let mut c = 16.352 * a4 / 440.0;
+    while c <= audio.freq_max {
+        if c >= audio.freq_min {
+            shapes.push(polar.circle(polar.radius(c, &audio), theme.lines.grid, grid));
+        }
+        c *= 2.0;
+    }
+    shapes.push(polar.circle(polar.outer, theme.lines.octave, grid));
+    let spokes: Vec<(f32, Option<String>)> = match layout {
+        RadialLayout::Chroma => (0..12).map(|i| (i as f32 / 12.0, Some(NOTE_NAMES[i].to_string()))).collect(),
+        RadialLayout::Clock => {
+            let beats = model.tempo_map.tempo_at(now).map_or(4, |(_, beats_per_bar)| beats_per_bar.round().max(1.0) as usize);
+            (0..beats).map(|i| (i as f32 / beats as f32, Some((i + 1).to_string()))).collect()
+        }
+    };
+    for (phase, label) in spokes {
+        shapes.push(Shape::line(polar.point(phase, polar.inner), polar.point(phase, polar.outer), theme.lines.grid, grid));
+        if let Some(label) = label {
+            shapes.push(Shape::text(polar.point(phase, polar.outer + 16.0), label, theme.fonts.label_size, theme.text, TextAlign::Center));
+        }
+    }
+
+    let phase_of = |t: f32, freq: f32| match layout {
+        RadialLayout::Clock => cycle_position(&model.tempo_map, config, t).1.rem_euclid(1.0),
+        RadialLayout::Chroma => chroma_phase(freq, a4),
+    };
+    let archived = if model.history.is_empty() { Vec::new() } else { model.history_events_in(now - trail, now) };
+    for event in model.live_events_in(now - trail, now).iter().chain(archived.iter()) {
+        let Some(start_time) = event.start_time() else { continue };
+        let t = model.placement_time(start_time);
+        if t > now || now - t > trail + event.duration() {
+            continue;
+        }
+        let fade = 1.0 - ((now - t - event.duration().max(0.0)).max(0.0) / trail).clamp(0.0, 1.0);
+        match event {
+            MusicalEvent::Note { frequency, amplitude, .. } | MusicalEvent::NoteColored { frequency, amplitude, .. } => {
+                let color = match event {
+                    MusicalEvent::NoteColored { r, g, b, .. } => [*r, *g, *b, 0.85 * fade],
+                    _ => theme.note_color(*frequency, 0.85 * fade),
+                };
+                let center = polar.point(phase_of(t, *frequency), polar.radius(*frequency, &audio));
+                shapes.push(Shape::Ellipse { center, radius: amplitude.abs() * 14.0 + 3.0, color });
+            }
+            MusicalEvent::Chord { frequencies, amplitude, .. } => {
+                for frequency in frequencies {
+                    let center = polar.point(phase_of(t, *frequency), polar.radius(*frequency, &audio));
+                    shapes.push(Shape::Ellipse { center, radius: amplitude.abs() * 10.0 + 3.0, color: theme.note_color(*frequency, 0.85 * fade) });
+                }
+            }
+            MusicalEvent::Cluster { center_freq, amplitude, .. } => {
+                let center = polar.point(phase_of(t, *center_freq), polar.radius(*center_freq, &audio));
+                shapes.push(Shape::Ellipse { center, radius: amplitude.abs() * 18.0 + 5.0, color: with_alpha(theme.events.cluster, fade) });
+            }
+            // Drones y glissandos: arco desde su inicio hasta su final (o el instante mostrado)
+            MusicalEvent::Drone { frequency, duration, .. } => {
+                let end = (t + duration).min(now);
+                let points = arc_points(t, end, cycle, |time| polar.point(phase_of(time, *frequency), polar.radius(*frequency, &audio)));
+                shapes.push(Shape::Polyline { points, weight: 4.0, color: with_alpha(theme.events.drone, fade) });
+            }
+            MusicalEvent::Glissando { start_freq, end_freq, duration, curve, .. } => {
+                let end = (t + duration).min(now);
+                let freq_at = |time: f32| glissando_freq(*start_freq, *end_freq, *curve, ((time - t) / duration.max(1e-3)).clamp(0.0, 1.0));
+                let points = arc_points(t, end, cycle, |time| polar.point(phase_of(time, freq_at(time)), polar.radius(freq_at(time), &audio)));
+                shapes.push(Shape::Polyline { points, weight: 2.0, color: theme.note_color(*start_freq, 0.85 * fade) });
+            }
+            _ => {}
+        }
+    }
+
+    if layout == RadialLayout::Clock {
+        let [r, g, b, _] = theme.accent;
+        shapes.push(Shape::line(polar.center, polar.point(now_phase.rem_euclid(1.0), polar.outer), theme.lines.playhead, [r, g, b, 0.9]));
+    }
+    shapes
+}
+
+/// Puntos entre `start` y `end` (segundos), como máximo un ciclo, cada 1/48 de ciclo.
+fn arc_points(start: f32, end: f32, cycle: f32, point_at: impl Fn(f32) -> Point2) -> Vec<Point2> {
+    let end = end.min(start + cycle).max(start);
+    let steps = (((end - start) / cycle * 48.0).ceil() as usize).max(1);
+    (0..=steps).map(|i| point_at(start + (end - start) * i as f32 / steps as f32)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polar_mapping_and_cycles() {
+        let polar = Polar::new(Rect::from_w_h(500.0, 500.0), 0.2);
+        let audio = AudioConfig { freq_min: 100.0, freq_max: 1600.0, ..AudioConfig::default() };
+        assert_eq!(polar.outer, 210.0);
+        assert_eq!(polar.radius(100.0, &audio), polar.inner);
+        assert!((polar.radius(400.0, &audio) - (polar.inner + polar.outer) / 2.0).abs() < 1e-3);
+        // Fase 0 arriba, 1/4 a la derecha
+        assert!(polar.point(0.0, 10.0).distance(pt2(0.0, 10.0)) < 1e-4);
+        assert!(polar.point(0.25, 10.0).distance(pt2(10.0, 0.0)) < 1e-4);
+
+        let config = RadialConfig { cycle_seconds: 4.0, ..RadialConfig::default() };
+        let mut tempo = TempoMap::new();
+        assert_eq!(cycle_position(&tempo, &config, 5.0), (4.0, 1.25));
+        // 120 bpm en 3/4: compases de 1.5 s
+        tempo.set_tempo(0.0, 120.0, 3.0);
+        let (cycle, bar) = cycle_position(&tempo, &config, 2.25);
+        assert_eq!(cycle, 1.5);
+        assert!((bar - 1.5).abs() < 1e-4);
+
+        assert!((chroma_phase(440.0, 440.0) - 0.75).abs() < 1e-4);
+        assert!(chroma_phase(261.63, 440.0) < 1e-3 || chroma_phase(261.63, 440.0) > 0.999);
+    }
+}
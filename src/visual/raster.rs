@@ -0,0 +1,171 @@
+// src/visual/raster.rs
+
+//! 🖼️ Rasterizado por software
+//!
+//! Convierte una `Scene` en una imagen RGBA sin GPU ni ventana (render offline en máquinas
+//! sin pantalla). Las formas se dibujan con antialiasing por cobertura y el texto con la
+//! fuente integrada de nannou.
+
+use nannou::image::{Rgba as Pixel, RgbaImage};
+use nannou::prelude::*;
+use nannou::text::{rt, Scale};
+use std::sync::OnceLock;
+use crate::visual::scene::{Color, Scene, Shape, TextAlign};
+
+fn font() -> &'static nannou::text::Font {
+    static FONT: OnceLock<nannou::text::Font> = OnceLock::new();
+    FONT.get_or_init(nannou::text::font::default_notosans)
+}
+
+/// Lienzo en coordenadas de píxel (origen arriba a la izquierda, y hacia abajo).
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 3]>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, background: Color) -> Self {
+        let [r, g, b, a] = background;
+        Self { width, height, pixels: vec![[r * a, g * a, b * a]; (width * height) as usize] }
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: Color, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let alpha = (color[3] * coverage).clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+        let pixel = &mut self.pixels[(y as u32 * self.width + x as u32) as usize];
+        for (channel, value) in pixel.iter_mut().zip(color) {
+            *channel = *channel * (1.0 - alpha) + value * alpha;
+        }
+    }
+
+    /// Recorre los píxeles del rectángulo `[x0, x1] × [y0, y1]` (recortado al lienzo).
+    fn pixels_in(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> impl Iterator<Item = (i64, i64)> {
+        let (left, right) = (x0.floor().max(0.0) as i64, x1.ceil().min(self.width as f32) as i64);
+        let (top, bottom) = (y0.floor().max(0.0) as i64, y1.ceil().min(self.height as f32) as i64);
+        (top..bottom).flat_map(move |y| (left..right).map(move |x| (x, y)))
+    }
+
+    fn fill_circle(&mut self, center: Point2, radius: f32, color: Color) {
+        let reach = radius + 1.0;
+        let pixels: Vec<_> = self.pixels_in(center.x - reach, center.y - reach, center.x + reach, center.y + reach).collect();
+        for (x, y) in pixels {
+            let d = pt2(x as f32 + 0.5, y as f32 + 0.5).distance(center);
+            self.blend(x, y, color, (radius + 0.5 - d).clamp(0.0, 1.0));
+        }
+    }
+
+    fn fill_rect(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        let pixels: Vec<_> = self.pixels_in(x0, y0, x1, y1).collect();
+        for (x, y) in pixels {
+            // Cobertura exacta del píxel por el rectángulo alineado con los ejes
+            let cover_x = (x1.min(x as f32 + 1.0) - x0.max(x as f32)).clamp(0.0, 1.0);
+            let cover_y = (y1.min(y as f32 + 1.0) - y0.max(y as f32)).clamp(0.0, 1.0);
+            self.blend(x, y, color, cover_x * cover_y);
+        }
+    }
+
+    fn stroke_segment(&mut self, a: Point2, b: Point2, weight: f32, color: Color) {
+        let half = weight.max(1.0) / 2.0;
+        let reach = half + 1.0;
+        let pixels: Vec<_> = self
+            .pixels_in(a.x.min(b.x) - reach, a.y.min(b.y) - reach, a.x.max(b.x) + reach, a.y.max(b.y) + reach)
+            .collect();
+        let ab = b - a;
+        let length_sq = ab.length_squared().max(f32::EPSILON);
+        for (x, y) in pixels {
+            let p = pt2(x as f32 + 0.5, y as f32 + 0.5);
+            let t = ((p - a).dot(ab) / length_sq).clamp(0.0, 1.0);
+            let d = p.distance(a + ab * t);
+            self.blend(x, y, color, (half + 0.5 - d).clamp(0.0, 1.0));
+        }
+    }
+
+    /// Texto de una línea centrado en vertical en `position.y`.
+    fn draw_text(&mut self, position: Point2, text: &str, size: u32, color: Color, align: TextAlign) {
+        let font = font();
+        let scale = Scale::uniform(size as f32);
+        let metrics = font.v_metrics(scale);
+        let baseline = position.y + (metrics.ascent + metrics.descent) / 2.0;
+        let width = font
+            .layout(text, scale, rt::point(0.0, 0.0))
+            .last()
+            .map_or(0.0, |g| g.position().x + g.unpositioned().h_metrics().advance_width);
+        let x = match align {
+            TextAlign::Left => position.x,
+            TextAlign::Center => position.x - width / 2.0,
+            TextAlign::Right => position.x - width,
+        };
+        for glyph in font.layout(text, scale, rt::point(x, baseline)) {
+            if let Some(bounds) = glyph.pixel_bounding_box() {
+                glyph.draw(|gx, gy, coverage| {
+                    self.blend(bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64, color, coverage);
+                });
+            }
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let [r, g, b] = self.pixels[(y * self.width + x) as usize];
+            Pixel([to_u8(r), to_u8(g), to_u8(b), 255])
+        })
+    }
+}
+
+impl Scene {
+    /// Imagen de la escena con un píxel por unidad de `bounds`.
+    pub fn to_image(&self) -> RgbaImage {
+        let width = self.bounds.w().round().max(1.0) as u32;
+        let height = self.bounds.h().round().max(1.0) as u32;
+        let (left, top) = (self.bounds.left(), self.bounds.top());
+        let px = |p: Point2| pt2(p.x - left, top - p.y);
+
+        let mut canvas = Canvas::new(width, height, self.background);
+        for shape in &self.shapes {
+            match shape {
+                Shape::Ellipse { center, radius, color } => canvas.fill_circle(px(*center), *radius, *color),
+                Shape::Rect { center, size, color } => {
+                    let c = px(*center);
+                    canvas.fill_rect(c.x - size.x / 2.0, c.y - size.y / 2.0, c.x + size.x / 2.0, c.y + size.y / 2.0, *color);
+                }
+                Shape::Line { start, end, weight, color } => canvas.stroke_segment(px(*start), px(*end), *weight, *color),
+                Shape::Polyline { points, weight, color } => {
+                    for pair in points.windows(2) {
+                        canvas.stroke_segment(px(pair[0]), px(pair[1]), *weight, *color);
+                    }
+                }
+                Shape::Text { position, text, size, color, align } => {
+                    canvas.draw_text(px(*position), text, *size, *color, *align);
+                }
+            }
+        }
+        canvas.into_image()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rasterizes_shapes_in_pixel_space() {
+        let mut scene = Scene::new(Rect::from_w_h(40.0, 20.0), [0.0, 0.0, 1.0, 1.0]);
+        // Círculo rojo en el centro y rectángulo verde opaco en la esquina superior izquierda
+        scene.extend([
+            Shape::Ellipse { center: pt2(0.0, 0.0), radius: 4.0, color: [1.0, 0.0, 0.0, 1.0] },
+            Shape::Rect { center: pt2(-18.0, 8.0), size: vec2(4.0, 4.0), color: [0.0, 1.0, 0.0, 1.0] },
+        ]);
+        let image = scene.to_image();
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.get_pixel(20, 10).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(39, 19).0, [0, 0, 255, 255]);
+    }
+}
@@ -98,32 +98,12 @@ fn draw_timeline_bar(draw: &Draw, win: &Rect, current_time: f32, config: &AppCon
     }
 }
 
-// Dibuja información de depuración (FPS, estadísticas OSC)
-fn draw_debug_info(draw: &Draw, win: &Rect, model: &Model) {
+// Dibuja información de depuración (modos, análisis y sincronización; FPS y OSC van en el HUD)
+pub fn draw_debug_info(draw: &Draw, win: &Rect, model: &Model) {
     let text_color = WHITE;
     let font_size = 12;
     let margin = 20.0;
 
-    let fps = 1.0 / model.time_info.last_update_time.elapsed().as_secs_f32();
-    let fps_text = draw.text(&format!("FPS: {fps:.0}"))
-        .font_size(font_size)
-        .color(text_color)
-        .x(win.right() - margin)
-        .y(win.top() - margin);
-    fps_text.right_justify().no_line_wrap();
-
-    let osc_stats_text = draw.text(&format!(
-        "OSC Recibidos: {}\nOSC Procesados: {}\nErrores OSC: {}",
-        model.osc_stats.total_received,
-        model.osc_stats.total_processed,
-        model.osc_stats.failed_messages
-    ))
-    .font_size(font_size)
-    .color(text_color)
-    .x(win.right() - margin)
-    .y(win.top() - margin - 30.0);
-    osc_stats_text.right_justify().no_line_wrap();
-
     let scroll_text = draw.text(&format!(
         "Scroll Mode: {:?}\nDisplay Mode: {:?}",
         model.scroll_mode, model.display_mode
@@ -150,6 +130,28 @@ fn draw_debug_info(draw: &Draw, win: &Rect, model: &Model) {
         .x(win.left() + margin)
         .y(win.top() - margin - 50.0);
     analysis_text_obj.left_justify().no_line_wrap();
+
+    // Sincronización de reloj con sclang (/viz/sync)
+    if model.clock_sync.is_enabled() {
+        let sync_text = match model.clock_sync.estimate() {
+            Some(e) => format!(
+                "Sync: desfase {:+.1} ms | latencia {:.1} ms | jitter {:.1} ms (n={})",
+                e.offset * 1000.0,
+                e.latency * 1000.0,
+                e.jitter * 1000.0,
+                e.samples
+            ),
+            None => "Sync: esperando /viz/sync/reply de sclang".to_string(),
+        };
+        draw.text(&sync_text)
+            .font_size(font_size)
+            .color(text_color)
+            .left_justify()
+            .no_line_wrap()
+            .w(win.w() - 2.0 * margin)
+            .x(win.x())
+            .y(win.top() - margin - 70.0);
+    }
 }
 
 
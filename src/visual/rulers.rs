@@ -0,0 +1,304 @@
+// src/visual/rulers.rs
+
+use nannou::prelude::*;
+use crate::config::{AudioConfig, VisualConfig};
+use crate::events::map_freq_to_y;
+use crate::pitch::{freq_to_midi, midi_to_freq, note_name};
+use crate::visual::scene::{Shape, TextAlign};
+use crate::visual::styles::VisualTheme;
+use crate::visual::timeline::TimelineView;
+
+/// Alto de la regla de tiempo (borde superior) y ancho de la regla de alturas (borde izquierdo).
+pub const TIME_RULER_H: f32 = 20.0;
+pub const PITCH_RULER_W: f32 = 56.0;
+
+/// Separación mínima en píxeles entre marcas con etiqueta.
+const MIN_TICK_PX: f32 = 70.0;
+
+/// Pasos "redondos" para las marcas de tiempo, de milisegundos a minutos.
+const TIME_STEPS: [f32; 19] = [
+    0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5,
+    1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0,
+];
+
+/// Paso entre marcas de tiempo para que queden al menos `min_px` píxeles entre ellas.
+pub fn time_tick_step(px_per_sec: f32, min_px: f32) -> f32 {
+    let min_step = min_px / px_per_sec.max(f32::EPSILON);
+    TIME_STEPS.iter().copied().find(|s| *s >= min_step).unwrap_or(TIME_STEPS[TIME_STEPS.len() - 1])
+}
+
+/// Etiqueta de una marca de tiempo con la unidad adecuada al paso.
+pub fn format_time_label(t: f32, step: f32) -> String {
+    if step < 1.0 && t.abs() < 1.0 {
+        format!("{:.0} ms", t * 1000.0)
+    } else if step < 1.0 {
+        let decimals = if step < 0.01 { 3 } else if step < 0.1 { 2 } else { 1 };
+        format!("{t:.decimals$} s")
+    } else if step < 60.0 && t.abs() < 60.0 {
+        format!("{t:.0} s")
+    } else {
+        let total = t.max(0.0).round() as u32;
+        format!("{}:{:02}", total / 60, total % 60)
+    }
+}
+
+/// Frecuencias "redondas" (serie 1-2-5 por década) dentro de `[min, max]`,
+/// para cuando el zoom deja pocas líneas de la rejilla configurada a la vista.
+pub fn adaptive_freq_lines(min: f32, max: f32, max_lines: usize) -> Vec<f32> {
+    let mut lines = Vec::new();
+    for mantissas in [&[1.0][..], &[1.0, 2.0, 5.0][..], &[1.0, 1.5, 2.0, 3.0, 5.0, 7.0][..]] {
+        let candidate: Vec<f32> = (-1..=5)
+            .flat_map(|exp| mantissas.iter().map(move |m| m * 10f32.powi(exp)))
+            .filter(|f| *f >= min && *f <= max)
+            .collect();
+        if candidate.len() > max_lines {
+            break;
+        }
+        lines = candidate;
+    }
+    lines
+}
+
+/// Etiquetas de la regla de alturas (tecla N).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PitchLabelMode {
+    Hz,
+    Note,
+    Midi,
+}
+
+impl PitchLabelMode {
+    /// Interpreta el nombre usado en `config.toml` ("hz" / "note" / "midi"); por defecto, Hz.
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "note" | "nota" | "notas" => PitchLabelMode::Note,
+            "midi" => PitchLabelMode::Midi,
+            _ => PitchLabelMode::Hz,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            PitchLabelMode::Hz => PitchLabelMode::Note,
+            PitchLabelMode::Note => PitchLabelMode::Midi,
+            PitchLabelMode::Midi => PitchLabelMode::Hz,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PitchLabelMode::Hz => "Hz",
+            PitchLabelMode::Note => "nota",
+            PitchLabelMode::Midi => "MIDI",
+        }
+    }
+}
+
+/// Opciones de la regla de alturas.
+#[derive(Debug, Clone, Copy)]
+pub struct PitchAxis {
+    pub labels: PitchLabelMode,
+    /// Frecuencia del La4 en Hz.
+    pub a4_reference: f32,
+    /// Marcas de cents entre semitonos cuando el zoom deja sitio.
+    pub show_cents: bool,
+}
+
+/// Saltos en semitonos entre etiquetas, de cada semitono a cada cuatro octavas.
+const SEMITONE_STEPS: [i32; 8] = [1, 2, 3, 4, 6, 12, 24, 48];
+/// Separación mínima en píxeles entre etiquetas de nota.
+const MIN_NOTE_LABEL_PX: f32 = 14.0;
+/// Píxeles por semitono a partir de los que se dibujan las marcas de cents.
+const MIN_CENTS_SEMITONE_PX: f32 = 30.0;
+
+/// Píxeles por semitono con el rango de alturas actual (mismo mapeo que `map_freq_to_y`).
+pub fn px_per_semitone(audio_view: &AudioConfig, win: Rect) -> f32 {
+    let octaves = (audio_view.freq_max / audio_view.freq_min.max(f32::EPSILON)).log2().max(f32::EPSILON);
+    (win.h() - 100.0).max(1.0) / (octaves * 12.0)
+}
+
+/// Salto en semitonos entre etiquetas para que queden al menos `min_px` píxeles entre ellas.
+pub fn semitone_label_step(px_per_semitone: f32, min_px: f32) -> i32 {
+    SEMITONE_STEPS
+        .iter()
+        .copied()
+        .find(|s| *s as f32 * px_per_semitone >= min_px)
+        .unwrap_or(SEMITONE_STEPS[SEMITONE_STEPS.len() - 1])
+}
+
+/// Regla de tiempo en el borde superior con marcas adaptadas al zoom actual.
+pub fn time_ruler_shapes(win: Rect, timeline: &TimelineView, theme: &VisualTheme) -> Vec<Shape> {
+    let color = theme.text;
+    let strip_y = win.top() - TIME_RULER_H / 2.0;
+    let mut shapes = vec![Shape::Rect {
+        center: pt2(win.x(), strip_y),
+        size: vec2(win.w(), TIME_RULER_H),
+        color: [0.0, 0.0, 0.0, 0.5],
+    }];
+
+    let step = time_tick_step(1.0 / timeline.seconds_per_pixel(), MIN_TICK_PX);
+    let (from, to) = timeline.visible_range();
+    let minor = step / 5.0;
+    let first = (from.max(0.0) / minor).floor() as i64;
+    let last = (to / minor).ceil() as i64;
+    for i in first..=last {
+        let t = i as f32 * minor;
+        if t < 0.0 || t < from || t > to {
+            continue;
+        }
+        let x = timeline.time_to_x(t);
+        let major = i % 5 == 0;
+        let tick_h = if major { TIME_RULER_H * 0.6 } else { TIME_RULER_H * 0.25 };
+        shapes.push(Shape::line(pt2(x, win.top()), pt2(x, win.top() - tick_h), 1.0, color));
+        if major {
+            shapes.push(Shape::text(
+                pt2(x + 3.0, win.top() - TIME_RULER_H * 0.7),
+                format_time_label(t, step),
+                theme.fonts.label_size,
+                color,
+                TextAlign::Left,
+            ));
+        }
+    }
+    shapes
+}
+
+/// Regla de alturas en el borde izquierdo. En Hz usa `visual.grid_frequency_lines` y, si el
+/// zoom deja menos de tres de ellas a la vista, añade líneas redondas adaptadas al rango; con
+/// nombres de nota o números MIDI dibuja semitonos afinados respecto a `axis.a4_reference`,
+/// resaltando los límites de octava (Do). Con `visual.show_grid` las líneas atraviesan toda la ventana.
+pub fn pitch_ruler_shapes(win: Rect, audio_view: &AudioConfig, visual: &VisualConfig, axis: &PitchAxis, theme: &VisualTheme) -> Vec<Shape> {
+    let mut shapes = vec![Shape::Rect {
+        center: pt2(win.left() + PITCH_RULER_W / 2.0, win.y()),
+        size: vec2(PITCH_RULER_W, win.h()),
+        color: [0.0, 0.0, 0.0, 0.4],
+    }];
+    match axis.labels {
+        PitchLabelMode::Hz => shapes.extend(hz_ruler_shapes(win, audio_view, visual, theme)),
+        PitchLabelMode::Note | PitchLabelMode::Midi => shapes.extend(semitone_ruler_shapes(win, audio_view, visual, axis, theme)),
+    }
+    shapes
+}
+
+fn hz_ruler_shapes(win: Rect, audio_view: &AudioConfig, visual: &VisualConfig, theme: &VisualTheme) -> Vec<Shape> {
+    let color = theme.grid;
+    let mut shapes = Vec::new();
+
+    let (min, max) = (audio_view.freq_min, audio_view.freq_max);
+    let mut lines: Vec<(f32, bool)> = visual
+        .grid_frequency_lines
+        .iter()
+        .copied()
+        .filter(|f| *f >= min && *f <= max)
+        .map(|f| (f, true))
+        .collect();
+    if lines.len() < 3 {
+        let max_lines = (win.h() / 40.0).max(3.0) as usize;
+        lines.extend(
+            adaptive_freq_lines(min, max, max_lines)
+                .into_iter()
+                .filter(|f| !visual.grid_frequency_lines.iter().any(|g| (g - f).abs() < 0.01))
+                .map(|f| (f, false)),
+        );
+    }
+
+    for (freq, configured) in lines {
+        let y = map_freq_to_y(freq, audio_view, win);
+        let right = if visual.show_grid { win.right() } else { win.left() + PITCH_RULER_W };
+        let line_color = if configured { color } else { [color[0], color[1], color[2], color[3] * 0.5] };
+        shapes.push(Shape::line(pt2(win.left() + PITCH_RULER_W - 8.0, y), pt2(right, y), theme.lines.grid, line_color));
+        let label = if freq >= 1000.0 { format!("{:.1}k", freq / 1000.0) } else { format!("{freq:.0}") };
+        shapes.push(Shape::text(pt2(win.left() + PITCH_RULER_W - 10.0, y + 5.0), label, theme.fonts.label_size, theme.text, TextAlign::Right));
+    }
+    shapes
+}
+
+fn semitone_ruler_shapes(win: Rect, audio_view: &AudioConfig, visual: &VisualConfig, axis: &PitchAxis, theme: &VisualTheme) -> Vec<Shape> {
+    let color = theme.grid;
+    let weight = theme.lines.grid;
+    let a4 = axis.a4_reference.max(1.0);
+    let px = px_per_semitone(audio_view, win);
+    let step = semitone_label_step(px, MIN_NOTE_LABEL_PX);
+    let ruler_right = win.left() + PITCH_RULER_W;
+    let right = if visual.show_grid { win.right() } else { ruler_right };
+    let y_of = |midi: f32| map_freq_to_y(midi_to_freq(midi, a4), audio_view, win);
+
+    let first = freq_to_midi(audio_view.freq_min, a4).ceil() as i32;
+    let last = freq_to_midi(audio_view.freq_max, a4).floor() as i32;
+    let mut shapes = Vec::new();
+    for midi in first..=last {
+        let y = y_of(midi as f32);
+        let octave = midi.rem_euclid(12) == 0;
+        let labeled = midi.rem_euclid(step) == 0 || (octave && step <= 12);
+        if octave {
+            shapes.push(Shape::line(pt2(ruler_right - 12.0, y), pt2(right, y), theme.lines.octave, [color[0], color[1], color[2], (color[3] * 2.0).min(1.0)]));
+        } else if labeled {
+            shapes.push(Shape::line(pt2(ruler_right - 8.0, y), pt2(right, y), weight, [color[0], color[1], color[2], color[3] * 0.5]));
+        } else if px >= 4.0 {
+            shapes.push(Shape::line(pt2(ruler_right - 4.0, y), pt2(ruler_right, y), weight, [color[0], color[1], color[2], color[3] * 0.5]));
+        }
+        if labeled {
+            let label = match axis.labels {
+                PitchLabelMode::Midi => midi.to_string(),
+                _ => note_name(midi),
+            };
+            shapes.push(Shape::text(pt2(ruler_right - 14.0, y + 5.0), label, theme.fonts.label_size, theme.text, TextAlign::Right));
+        }
+    }
+
+    if axis.show_cents && px >= MIN_CENTS_SEMITONE_PX {
+        let cents_step = if px >= 120.0 { 10 } else if px >= 60.0 { 25 } else { 50 };
+        for midi in (first - 1)..=last {
+            for cents in (cents_step..100).step_by(cents_step as usize) {
+                let y = y_of(midi as f32 + cents as f32 / 100.0);
+                if y < win.bottom() || y > win.top() {
+                    continue;
+                }
+                let tick = if cents == 50 { 6.0 } else { 3.0 };
+                shapes.push(Shape::line(pt2(ruler_right - tick, y), pt2(ruler_right, y), 1.0, [color[0], color[1], color[2], color[3] * 0.4]));
+            }
+        }
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_tick_step_adapts_to_zoom() {
+        assert_eq!(time_tick_step(100.0, 70.0), 1.0);
+        assert_eq!(time_tick_step(100_000.0, 70.0), 0.001);
+        assert_eq!(time_tick_step(0.5, 70.0), 300.0);
+        assert_eq!(format_time_label(0.25, 0.05), "250 ms");
+        assert_eq!(format_time_label(125.0, 60.0), "2:05");
+    }
+
+    #[test]
+    fn test_adaptive_freq_lines_within_range() {
+        let lines = adaptive_freq_lines(400.0, 900.0, 8);
+        assert!(!lines.is_empty());
+        assert!(lines.iter().all(|f| *f >= 400.0 && *f <= 900.0));
+    }
+
+    #[test]
+    fn test_note_axis_labels_octaves_with_reference() {
+        let audio = AudioConfig { freq_min: 65.0, freq_max: 1050.0, ..AudioConfig::default() };
+        let visual = VisualConfig::default();
+        let win = Rect::from_w_h(800.0, 600.0);
+        let axis = PitchAxis { labels: PitchLabelMode::Note, a4_reference: 440.0, show_cents: false };
+        let labels: Vec<String> = pitch_ruler_shapes(win, &audio, &visual, &axis, &VisualTheme::dark_theme())
+            .into_iter()
+            .filter_map(|s| match s {
+                Shape::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        for octave in ["C2", "C3", "C4", "C5", "C6"] {
+            assert!(labels.iter().any(|l| l == octave), "falta {octave} en {labels:?}");
+        }
+        assert_eq!(semitone_label_step(2.0, 14.0), 12);
+        assert_eq!(PitchLabelMode::from_name("MIDI"), PitchLabelMode::Midi);
+    }
+}
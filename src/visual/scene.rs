@@ -0,0 +1,772 @@
+// src/visual/scene.rs
+
+//! 🧩 Escena independiente del dispositivo
+//!
+//! La vista principal y la exportación comparten la misma disposición: primero se genera una
+//! lista de primitivas (`Shape`) en coordenadas de nannou (origen en el centro, y hacia arriba)
+//! y después se dibuja con `Draw` en la ventana o se serializa a SVG sin GPU ni ventana.
+
+use nannou::prelude::*;
+use std::time::Instant;
+use std::collections::HashSet;
+use crate::config::AudioConfig;
+use crate::event_history::{CompactEvent, CompactKind, EventHistory};
+use crate::events::{glissando_freq, map_freq_to_y, MusicalEvent};
+use crate::model::{DisplayMode, Model};
+use crate::visual::density::DensityGrid;
+use crate::visual::lanes::LaneLayout;
+use crate::visual::styles::VisualTheme;
+use crate::visual::timeline::TimelineView;
+use crate::visual::VisualNote;
+
+/// Color RGBA lineal (0.0 - 1.0).
+pub type Color = [f32; 4];
+
+/// Ancho de la caja de texto usada para alinear etiquetas con nannou.
+const TEXT_BOX_W: f32 = 400.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Ellipse { center: Point2, radius: f32, color: Color },
+    Rect { center: Point2, size: Vec2, color: Color },
+    Line { start: Point2, end: Point2, weight: f32, color: Color },
+    Polyline { points: Vec<Point2>, weight: f32, color: Color },
+    /// `position` es el punto de anclaje según `align`, centrado en vertical.
+    Text { position: Point2, text: String, size: u32, color: Color, align: TextAlign },
+}
+
+impl Shape {
+    pub fn line(start: Point2, end: Point2, weight: f32, color: Color) -> Self {
+        Shape::Line { start, end, weight, color }
+    }
+
+    pub fn text(position: Point2, text: impl Into<String>, size: u32, color: Color, align: TextAlign) -> Self {
+        Shape::Text { position, text: text.into(), size, color, align }
+    }
+
+    pub fn rect(area: Rect, color: Color) -> Self {
+        Shape::Rect { center: area.xy(), size: area.wh(), color }
+    }
+
+    /// Si la caja envolvente de la primitiva toca `area`. El texto se conserva siempre.
+    pub fn overlaps(&self, area: Rect) -> bool {
+        let bounds = match self {
+            Shape::Ellipse { center, radius, .. } => Rect::from_xy_wh(*center, vec2(*radius, *radius) * 2.0),
+            Shape::Rect { center, size, .. } => Rect::from_xy_wh(*center, *size),
+            Shape::Line { start, end, weight, .. } => {
+                let half = vec2(*weight, *weight) / 2.0;
+                Rect::from_corners(start.min(*end) - half, start.max(*end) + half)
+            }
+            Shape::Polyline { points, weight, .. } => {
+                let Some(first) = points.first() else { return false };
+                let (low, high) = points.iter().fold((*first, *first), |(lo, hi), p| (lo.min(*p), hi.max(*p)));
+                let half = vec2(*weight, *weight) / 2.0;
+                Rect::from_corners(low - half, high + half)
+            }
+            Shape::Text { .. } => return true,
+        };
+        bounds.overlap(area).is_some()
+    }
+}
+
+/// Escena completa de un frame: límites, fondo y primitivas en orden de dibujo.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub bounds: Rect,
+    pub background: Color,
+    pub shapes: Vec<Shape>,
+}
+
+impl Scene {
+    pub fn new(bounds: Rect, background: Color) -> Self {
+        Self { bounds, background, shapes: Vec::new() }
+    }
+
+    pub fn extend(&mut self, shapes: impl IntoIterator<Item = Shape>) {
+        self.shapes.extend(shapes);
+    }
+
+    /// La escena escalada (sin deformar) y centrada en `target`, fondo incluido: la vista
+    /// previa de otra ventana.
+    pub fn fit_into(&self, target: Rect) -> Vec<Shape> {
+        let scale = (target.w() / self.bounds.w().max(1.0)).min(target.h() / self.bounds.h().max(1.0));
+        let map = |p: Point2| target.xy() + (p - self.bounds.xy()) * scale;
+        let area = Rect::from_xy_wh(target.xy(), self.bounds.wh() * scale);
+        let mut shapes = vec![Shape::rect(area, self.background)];
+        shapes.extend(self.shapes.iter().map(|shape| match shape {
+            Shape::Ellipse { center, radius, color } => Shape::Ellipse { center: map(*center), radius: radius * scale, color: *color },
+            Shape::Rect { center, size, color } => Shape::Rect { center: map(*center), size: *size * scale, color: *color },
+            Shape::Line { start, end, weight, color } => Shape::line(map(*start), map(*end), (weight * scale).max(0.5), *color),
+            Shape::Polyline { points, weight, color } => Shape::Polyline {
+                points: points.iter().map(|p| map(*p)).collect(),
+                weight: (weight * scale).max(0.5),
+                color: *color,
+            },
+            Shape::Text { position, text, size, color, align } => {
+                Shape::text(map(*position), text.clone(), ((*size as f32 * scale).round() as u32).max(6), *color, *align)
+            }
+        }));
+        shapes
+    }
+
+    /// Dibuja la escena (fondo incluido) con nannou.
+    pub fn draw(&self, draw: &Draw) {
+        let [r, g, b, a] = self.background;
+        draw.background().color(rgba(r, g, b, a));
+        draw_shapes(draw, &self.shapes);
+    }
+}
+
+fn to_rgba(color: Color) -> Rgba {
+    rgba(color[0], color[1], color[2], color[3])
+}
+
+/// Dibuja una lista de primitivas con nannou.
+pub fn draw_shapes(draw: &Draw, shapes: &[Shape]) {
+    for shape in shapes {
+        match shape {
+            Shape::Ellipse { center, radius, color } => {
+                draw.ellipse().xy(*center).radius(*radius).color(to_rgba(*color));
+            }
+            Shape::Rect { center, size, color } => {
+                draw.rect().xy(*center).wh(*size).color(to_rgba(*color));
+            }
+            Shape::Line { start, end, weight, color } => {
+                draw.line().points(*start, *end).stroke_weight(*weight).color(to_rgba(*color));
+            }
+            Shape::Polyline { points, weight, color } => {
+                draw.polyline().weight(*weight).points(points.iter().copied()).color(to_rgba(*color));
+            }
+            Shape::Text { position, text, size, color, align } => {
+                let text = draw.text(text).font_size(*size).color(to_rgba(*color)).no_line_wrap().w(TEXT_BOX_W).y(position.y);
+                match align {
+                    TextAlign::Left => text.left_justify().x(position.x + TEXT_BOX_W / 2.0),
+                    TextAlign::Center => text.x(position.x),
+                    TextAlign::Right => text.right_justify().x(position.x - TEXT_BOX_W / 2.0),
+                };
+            }
+        }
+    }
+}
+
+/// Color de una nota según su frecuencia (hue de 0 a 360 grados entre 20 Hz y 5 kHz).
+pub fn note_color(frequency: f32, alpha: f32) -> Color {
+    let min_freq = 20.0;
+    let max_freq = 5000.0;
+    let norm = ((frequency - min_freq) / (max_freq - min_freq)).clamp(0.0, 1.0);
+    let hsv = nannou::color::Hsv::new(norm * 360.0, 0.85, 1.0);
+    let rgb: nannou::color::Rgb<f32> = nannou::color::Rgb::from(hsv);
+    let (r, g, b) = rgb.into_components();
+    [r, g, b, alpha]
+}
+
+/// Lado (px) de las celdas de la textura de ruido y máximo de celdas por banda.
+const NOISE_CELL: f32 = 6.0;
+const MAX_NOISE_CELLS: f32 = 3000.0;
+
+/// Hash entero para texturas estables entre frames (la misma banda siempre tiene el mismo grano).
+fn hash3(seed: u32, i: i32, j: i32) -> u32 {
+    let mut h = seed ^ (i as u32).wrapping_mul(0x9E37_79B1) ^ (j as u32).wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297A_2D39);
+    h ^ (h >> 15)
+}
+
+fn unit(h: u32) -> f32 {
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// Disposición de eventos musicales en un panel de la línea de tiempo.
+pub struct EventLayout<'a> {
+    pub pane: Rect,
+    pub timeline: &'a TimelineView,
+    pub audio_view: &'a AudioConfig,
+    pub lanes: &'a LaneLayout,
+    pub theme: &'a VisualTheme,
+    /// Aumento de tamaño reactivo de las notas (fuerza de ataque × escala reactiva).
+    pub reactive_boost: f32,
+}
+
+impl EventLayout<'_> {
+    /// Primitiva de un evento que empieza en `t` (segundos de sesión), o `None` si el modo
+    /// lo filtra o queda fuera de la línea de tiempo.
+    pub fn shape_for(&self, event: &MusicalEvent, t: f32, mode: DisplayMode) -> Option<Shape> {
+        match event {
+            MusicalEvent::Note { frequency, amplitude, instrument, .. } if mode.shows_notes() => {
+                self.note_shape(t, *frequency, *amplitude, Some(instrument))
+            }
+            MusicalEvent::Drone { frequency, amplitude, duration, instrument, .. } if mode.shows_drones() => {
+                self.drone_shape(t, *frequency, *amplitude, *duration, Some(instrument))
+            }
+            MusicalEvent::Cluster { center_freq, density, amplitude, duration, instrument, .. } if mode.shows_clusters() => {
+                self.cluster_shape(t, *center_freq, *density, *amplitude, *duration, Some(instrument))
+            }
+            _ => None,
+        }
+    }
+
+    fn note_shape(&self, t: f32, frequency: f32, amplitude: f32, instrument: Option<&str>) -> Option<Shape> {
+        let (x, fade) = self.timeline.place(t, 1.0)?;
+        Some(Shape::Ellipse {
+            center: pt2(x, self.freq_y(frequency, instrument)),
+            radius: (amplitude.abs() * 40.0 + 10.0) * (1.0 + self.reactive_boost),
+            color: self.theme.note_color(frequency, 0.8 * fade),
+        })
+    }
+
+    fn drone_shape(&self, t: f32, frequency: f32, amplitude: f32, duration: f32, instrument: Option<&str>) -> Option<Shape> {
+        let (x, fade) = self.timeline.place(t, duration)?;
+        Some(Shape::Rect {
+            center: pt2(x, self.freq_y(frequency, instrument)),
+            size: vec2(duration.abs() * 60.0 + 30.0, amplitude.abs() * 20.0 + 8.0),
+            color: with_alpha(self.theme.events.drone, 0.6 * fade),
+        })
+    }
+
+    fn cluster_shape(&self, t: f32, center_freq: f32, density: f32, amplitude: f32, duration: f32, instrument: Option<&str>) -> Option<Shape> {
+        let (x, fade) = self.timeline.place(t, duration)?;
+        let y = self.freq_y(center_freq, instrument);
+        let height = density.abs() * 80.0 + 20.0;
+        let width = amplitude.abs() * 10.0 + 4.0;
+        Some(Shape::line(pt2(x - width, y - height / 2.0), pt2(x + width, y + height / 2.0), width, with_alpha(self.theme.events.cluster, 0.7 * fade)))
+    }
+
+    /// Primitivas de cualquier evento con tiempo. Glissandos y bandas de ruido se dibujan
+    /// hasta la cabeza de lectura mientras suenan; los acordes, como notas unidas por una plica.
+    pub fn shapes_for(&self, event: &MusicalEvent, t: f32, mode: DisplayMode) -> Vec<Shape> {
+        match event {
+            MusicalEvent::Glissando { start_freq, end_freq, amplitude, duration, curve, instrument, .. } if mode.shows_notes() => {
+                self.glissando_shapes(t, *start_freq, *end_freq, *amplitude, *duration, *curve, Some(instrument))
+            }
+            MusicalEvent::Noise { center_freq, bandwidth, amplitude, duration, instrument, .. } if mode.shows_noise() => {
+                self.noise_shapes(t, *center_freq, *bandwidth, *amplitude, *duration, Some(instrument))
+            }
+            MusicalEvent::Chord { frequencies, amplitude, duration, instrument, .. } if mode.shows_notes() => {
+                self.chord_shapes(t, frequencies, *amplitude, *duration, Some(instrument))
+            }
+            other => self.shape_for(other, t, mode).into_iter().collect(),
+        }
+    }
+
+    /// Igual que `shapes_for` para un evento del historial, sin reconstruir el `MusicalEvent`
+    /// (ni el nombre del instrumento ni las alturas del acorde).
+    pub fn compact_shapes(&self, history: &EventHistory, event: &CompactEvent, t: f32, mode: DisplayMode) -> Vec<Shape> {
+        let instrument = Some(history.instrument_name(event.instrument));
+        match event.kind {
+            CompactKind::Note if mode.shows_notes() => self.note_shape(t, event.freq, event.amp, instrument).into_iter().collect(),
+            CompactKind::Drone if mode.shows_drones() => self.drone_shape(t, event.freq, event.amp, event.duration, instrument).into_iter().collect(),
+            CompactKind::Cluster if mode.shows_clusters() => {
+                self.cluster_shape(t, event.freq, event.b, event.amp, event.duration, instrument).into_iter().collect()
+            }
+            CompactKind::Glissando if mode.shows_notes() => self.glissando_shapes(t, event.freq, event.a, event.amp, event.duration, event.b, instrument),
+            CompactKind::Noise if mode.shows_noise() => self.noise_shapes(t, event.freq, event.a, event.amp, event.duration, instrument),
+            CompactKind::Chord if mode.shows_notes() => self.chord_shapes(t, history.chord_frequencies(event), event.amp, event.duration, instrument),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Progreso de un evento de `duration` segundos que empieza en `t`, o `None` si aún no ha empezado.
+    fn progress(&self, t: f32, duration: f32) -> Option<f32> {
+        let elapsed = self.timeline.now - t;
+        match elapsed {
+            e if e < 0.0 => None,
+            _ if duration <= 0.0 => Some(1.0),
+            e => Some((e / duration).min(1.0)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn glissando_shapes(&self, t: f32, start_freq: f32, end_freq: f32, amplitude: f32, duration: f32, curve: f32, instrument: Option<&str>) -> Vec<Shape> {
+        let (Some(progress), Some(fade)) = (self.progress(t, duration), self.timeline.place_span(t, t + duration)) else {
+            return Vec::new();
+        };
+        let point_at = |p: f32| {
+            let freq = glissando_freq(start_freq, end_freq, curve, p);
+            pt2(self.timeline.time_to_x(t + p * duration), self.freq_y(freq, instrument))
+        };
+        let span_px = (self.timeline.time_to_x(t + duration) - self.timeline.time_to_x(t)).abs() * progress;
+        let steps = (span_px / 4.0).clamp(2.0, 128.0) as usize;
+        let points: Vec<Point2> = (0..=steps).map(|i| point_at(progress * i as f32 / steps as f32)).collect();
+
+        let weight = amplitude.abs() * 6.0 + 1.5;
+        let color = self.theme.note_color(start_freq, 0.85 * fade);
+        let head = points[points.len() - 1];
+        let mut shapes = vec![
+            Shape::Polyline { points, weight, color },
+            Shape::Ellipse { center: point_at(0.0), radius: weight, color },
+        ];
+        if progress < 1.0 {
+            // Cabeza del glissando en la frecuencia actual y, cerca del final, el destino
+            let current = glissando_freq(start_freq, end_freq, curve, progress);
+            shapes.push(Shape::Ellipse { center: head, radius: weight + 2.0, color: self.theme.note_color(current, fade) });
+            if progress > 0.9 {
+                shapes.push(Shape::Ellipse { center: point_at(1.0), radius: weight, color: self.theme.note_color(end_freq, 0.3 * fade) });
+            }
+        }
+        shapes
+    }
+
+    fn noise_shapes(&self, t: f32, center_freq: f32, bandwidth: f32, amplitude: f32, duration: f32, instrument: Option<&str>) -> Vec<Shape> {
+        let (Some(progress), Some(fade)) = (self.progress(t, duration), self.timeline.place_span(t, t + duration)) else {
+            return Vec::new();
+        };
+        let origin_x = self.timeline.time_to_x(t);
+        let x0 = origin_x.max(self.pane.left());
+        let x1 = self.timeline.time_to_x(t + duration * progress).min(self.pane.right());
+        let half = bandwidth.abs().max(1.0) / 2.0;
+        let y0 = self.freq_y((center_freq - half).max(1.0), instrument);
+        let y1 = self.freq_y(center_freq + half, instrument).max(y0 + 4.0);
+        if x1 <= x0 {
+            return Vec::new();
+        }
+        let band = Rect::from_corners(pt2(x0, y0), pt2(x1, y1));
+        let mut shapes = vec![Shape::rect(band, with_alpha(self.theme.events.noise, 0.12 * fade))];
+
+        // Grano: celdas ancladas al inicio del evento para que la textura se desplace con él
+        let cell = NOISE_CELL.max((band.w() * band.h() / MAX_NOISE_CELLS).sqrt());
+        let density = (0.25 + amplitude.abs() * 0.5).min(0.9);
+        let seed = t.to_bits();
+        let (first_i, last_i) = (((x0 - origin_x) / cell).floor() as i32, ((x1 - origin_x) / cell).ceil() as i32);
+        let rows = (band.h() / cell).ceil() as i32;
+        for i in first_i..last_i {
+            for j in 0..rows {
+                let h = hash3(seed, i, j);
+                let v = unit(h);
+                if v >= density {
+                    continue;
+                }
+                let jitter = vec2(unit(hash3(h, 1, 0)), unit(hash3(h, 0, 1))) * cell;
+                let center = pt2(origin_x + i as f32 * cell, y0 + j as f32 * cell) + jitter;
+                if band.contains(center) {
+                    let alpha = (0.25 + 0.5 * amplitude.abs()) * fade * (1.0 - v / density * 0.5);
+                    shapes.push(Shape::Ellipse { center, radius: 0.8 + v * 1.5, color: with_alpha(self.theme.events.noise, alpha) });
+                }
+            }
+        }
+        shapes
+    }
+
+    fn chord_shapes(&self, t: f32, frequencies: &[f32], amplitude: f32, duration: f32, instrument: Option<&str>) -> Vec<Shape> {
+        let Some((x, fade)) = self.timeline.place(t, duration) else {
+            return Vec::new();
+        };
+        let ys: Vec<f32> = frequencies.iter().map(|f| self.freq_y(*f, instrument)).collect();
+        let (low, high) = ys.iter().fold((f32::MAX, f32::MIN), |(lo, hi), y| (lo.min(*y), hi.max(*y)));
+        let mut shapes = Vec::new();
+        if ys.len() > 1 {
+            let stem = self.theme.events.chord_stem;
+            shapes.push(Shape::line(pt2(x, low), pt2(x, high), self.theme.lines.chord_stem, with_alpha(stem, stem[3] * fade)));
+        }
+        let radius = (amplitude.abs() * 30.0 + 8.0) * (1.0 + self.reactive_boost);
+        for (freq, y) in frequencies.iter().zip(ys) {
+            shapes.push(Shape::Ellipse { center: pt2(x, y), radius, color: self.theme.note_color(*freq, 0.8 * fade) });
+        }
+        shapes
+    }
+
+    /// Suma a `grid` los eventos puntuales (notas, acordes y clusters) en lugar de dibujarlos.
+    /// Devuelve `false` para los demás tipos, que se siguen dibujando uno a uno.
+    pub fn accumulate(&self, grid: &mut DensityGrid, event: &MusicalEvent, t: f32, mode: DisplayMode) -> bool {
+        match event {
+            MusicalEvent::Note { frequency, instrument, .. } if mode.shows_notes() => {
+                self.accumulate_tones(grid, t, 1.0, std::slice::from_ref(frequency), Some(instrument));
+            }
+            MusicalEvent::Chord { frequencies, duration, instrument, .. } if mode.shows_notes() => {
+                self.accumulate_tones(grid, t, *duration, frequencies, Some(instrument));
+            }
+            MusicalEvent::Cluster { center_freq, duration, instrument, .. } if mode.shows_clusters() => {
+                self.accumulate_tones(grid, t, *duration, std::slice::from_ref(center_freq), Some(instrument));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Igual que `accumulate` para un evento del historial, sin reconstruir el `MusicalEvent`.
+    pub fn accumulate_compact(&self, grid: &mut DensityGrid, history: &EventHistory, event: &CompactEvent, t: f32, mode: DisplayMode) -> bool {
+        let instrument = Some(history.instrument_name(event.instrument));
+        match event.kind {
+            CompactKind::Note if mode.shows_notes() => {
+                self.accumulate_tones(grid, t, 1.0, &[event.freq], instrument);
+            }
+            CompactKind::Chord if mode.shows_notes() => {
+                self.accumulate_tones(grid, t, event.duration, history.chord_frequencies(event), instrument);
+            }
+            CompactKind::Cluster if mode.shows_clusters() => {
+                self.accumulate_tones(grid, t, event.duration, &[event.freq], instrument);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn accumulate_tones(&self, grid: &mut DensityGrid, t: f32, duration: f32, frequencies: &[f32], instrument: Option<&str>) {
+        let Some((x, fade)) = self.timeline.place(t, duration) else { return };
+        for frequency in frequencies {
+            grid.add(pt2(x, self.freq_y(*frequency, instrument)), *frequency, fade);
+        }
+    }
+
+    /// Posición vertical de una frecuencia: en el carril del instrumento si lo tiene,
+    /// si no sobre todo el panel.
+    pub fn freq_y(&self, frequency: f32, instrument: Option<&str>) -> f32 {
+        match instrument.and_then(|name| self.lanes.index_of(name)) {
+            Some(lane) => self.lanes.map_freq_in_lane(lane, frequency, self.audio_view, self.pane),
+            None => map_freq_to_y(frequency, self.audio_view, self.pane),
+        }
+    }
+
+    /// Nota abierta como barra que sigue su contorno: cada cambio de frecuencia sube o baja
+    /// la barra y cada cambio de amplitud cambia su grosor. `start` en segundos de sesión.
+    pub fn contour_shapes(&self, note: &VisualNote, start: f32, now: Instant) -> Vec<Shape> {
+        let (from, to) = self.timeline.visible_range();
+        let mut shapes = Vec::new();
+        let mut previous_y = None;
+        let mut end_point = None;
+        for (segment_start, segment_end, frequency, amplitude) in note.segments(now) {
+            let y = self.freq_y(frequency, Some(&note.instrument));
+            let (a, b) = ((start + segment_start).max(from), (start + segment_end).min(to));
+            if b >= a {
+                let (x0, x1) = (self.timeline.time_to_x(a), self.timeline.time_to_x(b));
+                let color = self.theme.note_color(frequency, 0.8);
+                shapes.push(Shape::Rect {
+                    center: pt2((x0 + x1) / 2.0, y),
+                    size: vec2((x1 - x0).max(2.0), amplitude.abs() * 20.0 + 3.0),
+                    color,
+                });
+                if let Some(previous_y) = previous_y {
+                    shapes.push(Shape::line(pt2(x0, previous_y), pt2(x0, y), 1.5, color));
+                }
+                end_point = Some((pt2(x1, y), color));
+            }
+            previous_y = Some(y);
+        }
+        if let (true, Some((center, color))) = (note.held, end_point) {
+            shapes.push(Shape::Ellipse { center, radius: 4.0, color });
+        }
+        shapes
+    }
+}
+
+fn with_alpha(color: Color, alpha: f32) -> Color {
+    [color[0], color[1], color[2], alpha]
+}
+
+/// Muestra de la leyenda dibujada en un punto con un color.
+type Swatch = fn(Point2, Color) -> Shape;
+
+/// Leyenda de los tipos de evento visibles en el modo actual.
+pub fn legend_shapes(pane: Rect, mode: DisplayMode, theme: &VisualTheme) -> Vec<Shape> {
+    let origin = pt2(pane.left() + crate::visual::rulers::PITCH_RULER_W + 16.0, pane.bottom() + 24.0);
+    let note = theme.note_color(440.0, 0.8);
+    let events = &theme.events;
+    let entries: [(&str, bool, Swatch, Color); 6] = [
+        ("Nota", mode.shows_notes(), |c, color| Shape::Ellipse { center: c, radius: 5.0, color }, note),
+        ("Acorde", mode.shows_notes(), |c, color| Shape::line(c - vec2(0.0, 6.0), c + vec2(0.0, 6.0), 2.0, color), note),
+        ("Glissando", mode.shows_notes(), |c, color| Shape::line(c - vec2(8.0, 5.0), c + vec2(8.0, 5.0), 2.0, color), note),
+        ("Drone", mode.shows_drones(), |c, color| Shape::Rect { center: c, size: vec2(16.0, 6.0), color }, events.drone),
+        ("Cluster", mode.shows_clusters(), |c, color| Shape::line(c - vec2(4.0, 6.0), c + vec2(4.0, 6.0), 3.0, color), events.cluster),
+        ("Ruido", mode.shows_noise(), |c, color| Shape::Rect { center: c, size: vec2(16.0, 10.0), color }, with_alpha(events.noise, 0.5)),
+    ];
+    let mut shapes = Vec::new();
+    for (i, (label, _, swatch, color)) in entries.iter().filter(|entry| entry.1).enumerate() {
+        let center = origin + vec2(i as f32 * 110.0, 0.0);
+        shapes.push(swatch(center, *color));
+        shapes.push(Shape::text(center + vec2(14.0, 0.0), *label, theme.fonts.legend_size, theme.text, TextAlign::Left));
+    }
+    shapes
+}
+
+/// Opciones de la escena que no dependen del estado del modelo.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SceneOptions {
+    /// Añadir la leyenda de tipos de evento (exportación).
+    pub legend: bool,
+    /// Modo de display de la escena; sin él, el del modelo (cada ventana puede tener el suyo).
+    pub mode: Option<DisplayMode>,
+    /// Barra de navegación con la vista congelada (no en la proyección).
+    pub transport: bool,
+}
+
+/// Disposición según el modo de display: paneles de eventos y de análisis dentro de `bounds`.
+/// Combinado apila eventos (arriba) y análisis (abajo); Partitura, Piano roll y Radial no usan ninguno.
+pub fn panes(bounds: Rect, mode: DisplayMode) -> (Option<Rect>, Option<Rect>) {
+    match mode {
+        DisplayMode::Combined => {
+            let split = bounds.bottom() + bounds.h() * 0.3;
+            (
+                Some(Rect::from_corners(pt2(bounds.left(), split), pt2(bounds.right(), bounds.top()))),
+                Some(Rect::from_corners(pt2(bounds.left(), bounds.bottom()), pt2(bounds.right(), split))),
+            )
+        }
+        DisplayMode::Analysis => (None, Some(bounds)),
+        DisplayMode::Staff | DisplayMode::PianoRoll | DisplayMode::Radial => (None, None),
+        _ => (Some(bounds), None),
+    }
+}
+
+/// Genera la escena de la línea de tiempo: rejilla y reglas, carriles, eventos (según el modo
+/// de display), curvas de análisis, rejilla de compases y cabeza de lectura.
+/// Las superposiciones interactivas (depuración, medidores, navegación) quedan fuera.
+pub fn build_scene(model: &Model, bounds: Rect, timeline: &TimelineView, options: SceneOptions) -> Scene {
+    let features = model.feature_bus.features();
+    let reactive = model.config.features.enabled;
+    let reactive_scale = if reactive { model.config.features.reactive_scale } else { 0.0 };
+    let glow = if reactive && model.config.features.reactive_background {
+        features.rms * reactive_scale * 0.3 + features.beat_intensity() * reactive_scale * 0.1
+    } else {
+        0.0
+    };
+    let theme = model.theme();
+    let [r, g, b, _] = theme.background;
+    let mut scene = Scene::new(bounds, [r + glow, g + glow * 0.5, b + glow, 1.0]);
+
+    // Rango de frecuencias visible según el zoom/desplazamiento vertical
+    let audio_view = model.viewport.apply_to(&model.config.audio);
+
+    let mode = options.mode.unwrap_or(model.display_mode);
+    let (events_pane, analysis_pane) = panes(bounds, mode);
+
+    if let Some(pane) = events_pane {
+        // Con carriles, cada uno muestra su propio rango en la cabecera
+        if model.config.view.show_pitch_ruler && model.lane_layout.is_empty() {
+            let axis = crate::visual::rulers::PitchAxis {
+                labels: model.pitch_labels,
+                a4_reference: model.config.view.a4_reference,
+                show_cents: model.config.view.show_cents,
+            };
+            scene.extend(crate::visual::rulers::pitch_ruler_shapes(pane, &audio_view, &model.config.visual, &axis, theme));
+        }
+        // Carriles por instrumento o canal de entrada (`[lanes]`)
+        scene.extend(model.lane_layout.shapes(pane, &audio_view, theme));
+
+        let layout = EventLayout {
+            pane,
+            timeline,
+            audio_view: &audio_view,
+            lanes: &model.lane_layout,
+            theme,
+            reactive_boost: reactive_scale * features.onset_strength,
+        };
+        // Solo los eventos del intervalo visible (búsqueda binaria en los activos y en el
+        // historial, que incluye lo archivado al navegar hacia atrás)
+        let (visible_from, visible_to) = timeline.visible_range();
+        let lookback = visible_from - timeline.page_duration;
+        let live = model.live_events_in(lookback, visible_to);
+        let archived = || model.history.range(lookback, visible_to);
+        let is_point = |event: &MusicalEvent| matches!(event, MusicalEvent::Note { .. } | MusicalEvent::Chord { .. } | MusicalEvent::Cluster { .. });
+        let lod = &model.config.lod;
+        let dense = lod.enabled && {
+            let points = live.iter().filter(|e| is_point(e)).count()
+                + archived().filter(|e| matches!(e.kind, CompactKind::Note | CompactKind::Chord | CompactKind::Cluster)).count();
+            points > lod.max_individual_events
+        };
+
+        if dense {
+            // Nivel de detalle bajo: los eventos puntuales se agregan en manchas de densidad
+            let mut grid = DensityGrid::new(pane, lod.cell_px);
+            for event in live {
+                let Some(start_time) = event.start_time() else { continue };
+                let t = model.placement_time(start_time);
+                if !layout.accumulate(&mut grid, event, t, mode) {
+                    scene.extend(layout.shapes_for(event, t, mode).into_iter().filter(|s| s.overlaps(pane)));
+                }
+            }
+            for compact in archived() {
+                let t = model.placement_time_secs(compact.time);
+                if !layout.accumulate_compact(&mut grid, &model.history, compact, t, mode) {
+                    scene.extend(layout.compact_shapes(&model.history, compact, t, mode).into_iter().filter(|s| s.overlaps(pane)));
+                }
+            }
+            scene.extend(grid.shapes(theme));
+        } else {
+            // Notas que caen en el mismo píxel con el mismo tamaño solo se dibujan una vez
+            let mut drawn_points = HashSet::new();
+            let live_shapes = live.iter().filter_map(|event| {
+                let start_time = event.start_time()?;
+                Some(layout.shapes_for(event, model.placement_time(start_time), mode))
+            });
+            let archived_shapes = archived().map(|compact| layout.compact_shapes(&model.history, compact, model.placement_time_secs(compact.time), mode));
+            for shapes in live_shapes.chain(archived_shapes) {
+                if let [Shape::Ellipse { center, radius, .. }] = shapes.as_slice() {
+                    if !drawn_points.insert((center.x.round() as i32, center.y.round() as i32, radius.round() as i32)) {
+                        continue;
+                    }
+                }
+                scene.extend(shapes.into_iter().filter(|s| s.overlaps(pane)));
+            }
+        }
+        if model.config.particles.enabled {
+            scene.extend(model.particles.shapes(&layout));
+        }
+        // Notas abiertas (/note_on id ...): las mantenidas crecen hasta la cabeza de lectura
+        if mode.shows_notes() {
+            let now = model.clock.now();
+            for note in model.visual_notes.iter().chain(model.active_notes.values()) {
+                scene.extend(layout.contour_shapes(note, model.session_time(note.start_time), now));
+            }
+        }
+        if options.legend {
+            scene.extend(legend_shapes(pane, mode, theme));
+        }
+    }
+
+    match mode {
+        DisplayMode::Staff => scene.extend(crate::visual::staff::staff_shapes(model, bounds, timeline, theme)),
+        DisplayMode::PianoRoll => scene.extend(crate::visual::piano_roll::piano_roll_shapes(model, bounds, timeline, theme)),
+        DisplayMode::Radial => scene.extend(crate::visual::radial::radial_shapes(model, bounds, timeline, theme)),
+        _ => {}
+    }
+
+    if let Some(pane) = analysis_pane {
+        scene.extend(crate::visual::analysis_view::analysis_pane_shapes(
+            pane,
+            timeline,
+            model.feature_bus.history(),
+            &model.config.features,
+            &audio_view,
+        ));
+    }
+
+    // La vista radial lleva su propia aguja y radios de pulso
+    if mode != DisplayMode::Radial {
+        // Rejilla de compases y pulsos (solo cuando se conoce el tempo)
+        if model.show_beat_grid && !model.tempo_map.is_empty() {
+            scene.extend(crate::visual::beat_grid::beat_grid_shapes(bounds, timeline, &model.tempo_map, &model.config.tempo, theme));
+        }
+
+        scene.extend(timeline.playhead_shapes(bounds, theme));
+        if model.config.view.show_time_ruler {
+            scene.extend(crate::visual::rulers::time_ruler_shapes(bounds, timeline, theme));
+        }
+    }
+    if options.transport {
+        scene.extend(crate::visual::timeline::transport_shapes(
+            bounds,
+            model.display_time(),
+            model.time_info.elapsed_time,
+            model.is_frozen(),
+            theme,
+        ));
+    }
+    scene
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_layout_is_deterministic() {
+        let win = Rect::from_w_h(1000.0, 500.0);
+        let timeline = TimelineView::range(10.0, 20.0, win);
+        let audio = AudioConfig { freq_min: 100.0, freq_max: 1600.0, ..AudioConfig::default() };
+        let lanes = LaneLayout::default();
+        let theme = VisualTheme::dark_theme();
+        let layout = EventLayout { pane: win, timeline: &timeline, audio_view: &audio, lanes: &lanes, theme: &theme, reactive_boost: 0.0 };
+        let note = MusicalEvent::Note {
+            frequency: 400.0,
+            amplitude: 0.5,
+            duration: 1.0,
+            instrument: "default".to_string(),
+            start_time: Instant::now(),
+        };
+
+        // Mitad del intervalo y mitad (logarítmica) del rango de alturas: centro del panel
+        match layout.shape_for(&note, 15.0, DisplayMode::Events) {
+            Some(Shape::Ellipse { center, radius, color }) => {
+                assert!(center.x.abs() < 1e-3 && center.y.abs() < 1e-3);
+                assert_eq!(radius, 30.0);
+                assert_eq!(color[3], 0.8);
+            }
+            other => panic!("forma inesperada: {other:?}"),
+        }
+        assert!(layout.shape_for(&note, 25.0, DisplayMode::Events).is_none());
+        assert!(layout.shape_for(&note, 15.0, DisplayMode::Drones).is_none());
+    }
+
+    #[test]
+    fn test_glissando_is_drawn_up_to_the_playhead() {
+        let win = Rect::from_w_h(1000.0, 500.0);
+        let audio = AudioConfig { freq_min: 100.0, freq_max: 1600.0, ..AudioConfig::default() };
+        let lanes = LaneLayout::default();
+        let theme = VisualTheme::dark_theme();
+        let gliss = MusicalEvent::Glissando {
+            start_freq: 100.0,
+            end_freq: 1600.0,
+            amplitude: 0.5,
+            duration: 4.0,
+            curve: 0.0,
+            instrument: "default".to_string(),
+            start_time: Instant::now(),
+        };
+        let scroll = crate::config::ScrollConfig::default();
+        let at = |now: f32| TimelineView::new(crate::model::ScrollMode::Continuous, now, win, 100.0, 10.0, &scroll);
+
+        // Antes de empezar no se dibuja; a mitad, la curva llega a la mitad del rango de alturas
+        let before = at(9.0);
+        let layout = EventLayout { pane: win, timeline: &before, audio_view: &audio, lanes: &lanes, theme: &theme, reactive_boost: 0.0 };
+        assert!(layout.shapes_for(&gliss, 10.0, DisplayMode::Events).is_empty());
+
+        let halfway = at(12.0);
+        let layout = EventLayout { pane: win, timeline: &halfway, audio_view: &audio, lanes: &lanes, theme: &theme, reactive_boost: 0.0 };
+        match &layout.shapes_for(&gliss, 10.0, DisplayMode::Events)[0] {
+            Shape::Polyline { points, .. } => {
+                let head = points.last().unwrap();
+                assert!((head.x - halfway.playhead_x()).abs() < 1e-3);
+                assert!(head.y.abs() < 1e-3);
+            }
+            other => panic!("forma inesperada: {other:?}"),
+        }
+    }
+    #[test]
+    fn test_off_screen_shapes_are_culled() {
+        let pane = Rect::from_w_h(100.0, 100.0);
+        assert!(Shape::Ellipse { center: pt2(55.0, 0.0), radius: 10.0, color: [1.0; 4] }.overlaps(pane));
+        assert!(!Shape::Ellipse { center: pt2(0.0, 80.0), radius: 10.0, color: [1.0; 4] }.overlaps(pane));
+        assert!(!Shape::line(pt2(60.0, 60.0), pt2(90.0, 70.0), 2.0, [1.0; 4]).overlaps(pane));
+        assert!(Shape::text(pt2(500.0, 0.0), "x", 9, [1.0; 4], TextAlign::Left).overlaps(pane));
+    }
+
+    #[test]
+    fn test_every_event_kind_is_placed_in_its_lane() {
+        let win = Rect::from_w_h(1000.0, 600.0);
+        let timeline = TimelineView::range(10.0, 20.0, win);
+        let audio = AudioConfig { freq_min: 100.0, freq_max: 1600.0, ..AudioConfig::default() };
+        let lanes = LaneLayout::new(vec!["piano".to_string(), "violin".to_string()]);
+        let theme = VisualTheme::dark_theme();
+        let layout = EventLayout { pane: win, timeline: &timeline, audio_view: &audio, lanes: &lanes, theme: &theme, reactive_boost: 0.0 };
+        let lane = lanes.lane_rect(1, win);
+        let (start_time, instrument) = (Instant::now(), "violin".to_string());
+
+        let events = [
+            MusicalEvent::Cluster { center_freq: 400.0, freq_width: 100.0, density: 0.5, amplitude: 0.5, duration: 2.0, instrument: instrument.clone(), start_time },
+            MusicalEvent::Glissando { start_freq: 200.0, end_freq: 800.0, amplitude: 0.5, duration: 20.0, curve: 0.0, instrument: instrument.clone(), start_time },
+            MusicalEvent::Noise { center_freq: 400.0, bandwidth: 200.0, amplitude: 0.5, duration: 20.0, instrument: instrument.clone(), start_time },
+            MusicalEvent::Chord { frequencies: vec![200.0, 400.0, 800.0], amplitude: 0.5, duration: 2.0, instrument, start_time },
+        ];
+        for event in &events {
+            let shapes = layout.shapes_for(event, 12.0, DisplayMode::Combined);
+            assert!(!shapes.is_empty(), "{event:?} no se dibuja");
+            let ys: Vec<f32> = shapes
+                .iter()
+                .flat_map(|shape| match shape {
+                    Shape::Ellipse { center, .. } | Shape::Rect { center, .. } => vec![center.y],
+                    Shape::Line { start, end, .. } => vec![start.y, end.y],
+                    Shape::Polyline { points, .. } => points.iter().map(|p| p.y).collect(),
+                    _ => Vec::new(),
+                })
+                .collect();
+            assert!(ys.iter().all(|y| *y >= lane.bottom() - 1.0 && *y <= lane.top() + 1.0), "{event:?} fuera del carril: {ys:?}");
+        }
+    }
+}
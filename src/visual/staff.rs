@@ -0,0 +1,333 @@
+// src/visual/staff.rs
+
+//! 🎼 Modo partitura (tecla 6)
+//!
+//! Las alturas se cuantizan al semitono o al cuarto de tono más cercano y se escriben en un
+//! gran pentagrama (clave de sol arriba, de fa abajo y el Do4 en una línea adicional entre
+//! ambos). La figura de cada nota sale de su duración en pulsos según el tempo vigente, y
+//! las notas se desplazan con la línea de tiempo como en el resto de vistas.
+
+use nannou::prelude::*;
+use crate::config::StaffConfig;
+use crate::events::MusicalEvent;
+use crate::model::Model;
+use crate::pitch::{freq_to_midi, spell, Accidental};
+use crate::visual::scene::{Color, Shape};
+use crate::visual::styles::VisualTheme;
+use crate::visual::timeline::TimelineView;
+
+/// Posición diatónica del Do4 (un paso por línea o espacio).
+pub const MIDDLE_C_STEP: i32 = 28;
+/// Líneas del pentagrama en clave de sol (Mi4 ... Fa5) y de fa (Sol2 ... La3).
+const TREBLE_LINES: [i32; 5] = [30, 32, 34, 36, 38];
+const BASS_LINES: [i32; 5] = [18, 20, 22, 24, 26];
+/// Ancho de la zona de claves, en espacios de pentagrama.
+const CLEF_AREA_GAPS: f32 = 5.0;
+
+/// Figura según la duración en pulsos (negra = 1 pulso).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+}
+
+impl NoteValue {
+    /// Figura más cercana en escala logarítmica (de semicorchea a redonda).
+    pub fn from_beats(beats: f32) -> Self {
+        match beats.max(1e-3).log2().round() as i32 {
+            i32::MIN..=-2 => NoteValue::Sixteenth,
+            -1 => NoteValue::Eighth,
+            0 => NoteValue::Quarter,
+            1 => NoteValue::Half,
+            _ => NoteValue::Whole,
+        }
+    }
+
+    fn hollow(self) -> bool {
+        matches!(self, NoteValue::Whole | NoteValue::Half)
+    }
+
+    fn flags(self) -> usize {
+        match self {
+            NoteValue::Eighth => 1,
+            NoteValue::Sixteenth => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// Geometría del gran pentagrama dentro de `pane`: el Do4 queda en el centro vertical.
+#[derive(Debug, Clone, Copy)]
+pub struct Staff {
+    pub pane: Rect,
+    /// Distancia entre dos líneas del pentagrama.
+    pub gap: f32,
+}
+
+impl Staff {
+    pub fn new(pane: Rect) -> Self {
+        Self { pane, gap: (pane.h() / 26.0).clamp(5.0, 16.0) }
+    }
+
+    pub fn step_y(&self, step: i32) -> f32 {
+        self.pane.y() + (step - MIDDLE_C_STEP) as f32 * self.gap / 2.0
+    }
+
+    /// Borde izquierdo de la zona de notas (a la derecha de las claves).
+    pub fn notes_left(&self) -> f32 {
+        self.pane.left() + CLEF_AREA_GAPS * self.gap
+    }
+
+    /// Número MIDI cuantizado a semitonos o, con `quarter`, a cuartos de tono.
+    pub fn quantize(freq: f32, a4: f32, quarter: bool) -> f32 {
+        let midi = freq_to_midi(freq, a4);
+        if quarter { (midi * 2.0).round() / 2.0 } else { midi.round() }
+    }
+
+    /// Las diez líneas de los dos pentagramas.
+    pub fn line_shapes(&self, theme: &VisualTheme) -> Vec<Shape> {
+        let color = with_alpha(theme.text, 0.6);
+        TREBLE_LINES
+            .iter()
+            .chain(BASS_LINES.iter())
+            .map(|step| {
+                let y = self.step_y(*step);
+                Shape::line(pt2(self.pane.left(), y), pt2(self.pane.right(), y), theme.lines.grid.max(1.0), color)
+            })
+            .collect()
+    }
+
+    /// Zona de claves opaca (tapa las notas que salen por la izquierda), llave de sistema,
+    /// clave de sol y clave de fa.
+    pub fn clef_shapes(&self, theme: &VisualTheme) -> Vec<Shape> {
+        let g = self.gap;
+        let area = Rect::from_corners(pt2(self.pane.left(), self.pane.bottom()), pt2(self.notes_left(), self.pane.top()));
+        let color = theme.text;
+        let left = self.pane.left() + g * 0.5;
+        let mut shapes = vec![
+            Shape::rect(area, theme.background),
+            Shape::line(pt2(left, self.step_y(18)), pt2(left, self.step_y(38)), 2.0, color),
+        ];
+        for step in TREBLE_LINES.iter().chain(BASS_LINES.iter()) {
+            let y = self.step_y(*step);
+            shapes.push(Shape::line(pt2(left, y), pt2(self.notes_left(), y), theme.lines.grid.max(1.0), with_alpha(color, 0.6)));
+        }
+
+        // Clave de sol: espiral alrededor del Sol4 y trazo vertical que la atraviesa
+        let x = left + g * 2.0;
+        let center = pt2(x, self.step_y(32));
+        let mut points: Vec<Point2> = (0..=24)
+            .map(|i| {
+                let p = i as f32 / 24.0;
+                let angle = -PI / 2.0 - p * TAU * 1.1;
+                center + vec2(angle.cos(), angle.sin()) * g * (0.35 + 0.85 * p)
+            })
+            .collect();
+        points.extend([
+            pt2(x + g * 0.4, self.step_y(40)),
+            pt2(x, self.step_y(42)),
+            pt2(x - g * 0.3, self.step_y(40)),
+            pt2(x + g * 0.2, self.step_y(25)),
+            pt2(x - g * 0.5, self.step_y(25)),
+        ]);
+        shapes.push(Shape::Polyline { points, weight: (g * 0.18).max(1.5), color });
+
+        // Clave de fa: punto en la línea del Fa3, arco hacia la derecha y dos puntos
+        let f3 = self.step_y(24);
+        let center = pt2(x - g * 0.2, f3);
+        let mut points: Vec<Point2> = (0..=16)
+            .map(|i| {
+                let angle = PI * (0.95 - 1.2 * i as f32 / 16.0);
+                center + vec2(angle.cos(), angle.sin() * 0.9) * g
+            })
+            .collect();
+        points.push(pt2(x - g * 0.9, self.step_y(17)));
+        shapes.push(Shape::Polyline { points, weight: (g * 0.18).max(1.5), color });
+        shapes.push(Shape::Ellipse { center: pt2(x - g * 1.1, f3), radius: g * 0.3, color });
+        for step in [25, 23] {
+            shapes.push(Shape::Ellipse { center: pt2(x + g * 1.3, self.step_y(step)), radius: g * 0.15, color });
+        }
+        shapes
+    }
+
+    /// Cabezas, líneas adicionales, alteraciones, plica y corchetes de una nota o acorde en `x`.
+    pub fn chord_shapes(&self, x: f32, notes: &[(i32, Accidental)], value: NoteValue, color: Color) -> Vec<Shape> {
+        let g = self.gap;
+        let mut shapes = Vec::new();
+        let Some(low) = notes.iter().map(|(step, _)| *step).min() else { return shapes };
+        let high = notes.iter().map(|(step, _)| *step).max().unwrap_or(low);
+
+        for &(step, accidental) in notes {
+            let y = self.step_y(step);
+            for ledger in ledger_steps(step) {
+                let ly = self.step_y(ledger);
+                shapes.push(Shape::line(pt2(x - g * 0.9, ly), pt2(x + g * 0.9, ly), 1.2, color));
+            }
+            shapes.extend(accidental_shapes(accidental, pt2(x - g * 1.5, y), g, color));
+            if value.hollow() {
+                let points = (0..=20)
+                    .map(|i| {
+                        let angle = i as f32 / 20.0 * TAU;
+                        pt2(x + angle.cos() * g * 0.6, y + angle.sin() * g * 0.42)
+                    })
+                    .collect();
+                shapes.push(Shape::Polyline { points, weight: (g * 0.16).max(1.0), color });
+            } else {
+                shapes.push(Shape::Ellipse { center: pt2(x, y), radius: g * 0.5, color });
+            }
+        }
+
+        if value != NoteValue::Whole {
+            // Plica hacia abajo por encima de la línea central de su pentagrama
+            let middle = if (low + high) / 2 >= MIDDLE_C_STEP { 34 } else { 22 };
+            let up = (low + high) / 2 < middle;
+            let (stem_x, base, tip) = if up {
+                (x + g * 0.5, self.step_y(low), self.step_y(high) + g * 3.5)
+            } else {
+                (x - g * 0.5, self.step_y(high), self.step_y(low) - g * 3.5)
+            };
+            shapes.push(Shape::line(pt2(stem_x, base), pt2(stem_x, tip), 1.2, color));
+            let direction = if up { -1.0 } else { 1.0 };
+            for flag in 0..value.flags() {
+                let start = pt2(stem_x, tip + direction * flag as f32 * g * 0.8);
+                shapes.push(Shape::line(start, start + vec2(g * 0.9, direction * g * 1.6), (g * 0.2).max(1.0), color));
+            }
+        }
+        shapes
+    }
+}
+
+/// Líneas adicionales que necesita una nota: por debajo del pentagrama de fa, por encima
+/// del de sol y la del Do4 entre ambos.
+pub fn ledger_steps(step: i32) -> Vec<i32> {
+    match step {
+        s if s <= 16 => (s..=16).rev().filter(|l| l % 2 == 0).collect(),
+        s if s >= 40 => (40..=s).filter(|l| l % 2 == 0).collect(),
+        MIDDLE_C_STEP => vec![MIDDLE_C_STEP],
+        _ => Vec::new(),
+    }
+}
+
+/// Alteración dibujada con trazos (sin depender de una fuente musical), centrada en `at`.
+fn accidental_shapes(accidental: Accidental, at: Point2, g: f32, color: Color) -> Vec<Shape> {
+    let w = (g * 0.12).max(1.0);
+    let sharp = |verticals: &[f32], half_width: f32| {
+        let mut shapes: Vec<Shape> =
+            verticals.iter().map(|dx| Shape::line(at + vec2(dx * g, -g * 1.1), at + vec2(dx * g, g * 1.1), w, color)).collect();
+        for dy in [-0.35, 0.35] {
+            shapes.push(Shape::line(at + vec2(-half_width * g, (dy - 0.12) * g), at + vec2(half_width * g, (dy + 0.12) * g), w * 2.0, color));
+        }
+        shapes
+    };
+    // `side` = 1 bemol normal, -1 bemol invertido (cuarto de tono bajo)
+    let flat = |center: Point2, side: f32| {
+        let stem = center.x - side * g * 0.3;
+        vec![
+            Shape::line(pt2(stem, center.y - g * 0.5), pt2(stem, center.y + g * 1.7), w, color),
+            Shape::Polyline {
+                points: vec![
+                    pt2(stem, center.y + g * 0.2),
+                    pt2(stem + side * g * 0.65, center.y + g * 0.3),
+                    pt2(stem + side * g * 0.55, center.y - g * 0.15),
+                    pt2(stem, center.y - g * 0.5),
+                ],
+                weight: w,
+                color,
+            },
+        ]
+    };
+    match accidental {
+        Accidental::Natural => Vec::new(),
+        Accidental::Sharp => sharp(&[-0.2, 0.2], 0.5),
+        Accidental::QuarterSharp => sharp(&[0.0], 0.35),
+        Accidental::ThreeQuarterSharp => sharp(&[-0.35, 0.0, 0.35], 0.6),
+        Accidental::Flat => flat(at, 1.0),
+        Accidental::QuarterFlat => flat(at, -1.0),
+        Accidental::ThreeQuarterFlat => {
+            let mut shapes = flat(at + vec2(g * 0.35, 0.0), 1.0);
+            shapes.extend(flat(at - vec2(g * 0.35, 0.0), -1.0));
+            shapes
+        }
+    }
+}
+
+fn with_alpha(color: Color, alpha: f32) -> Color {
+    [color[0], color[1], color[2], color[3] * alpha]
+}
+
+/// Pulsos que dura `duration` segundos empezando en `t`, según el tempo vigente.
+fn duration_beats(model: &Model, config: &StaffConfig, t: f32, duration: f32) -> f32 {
+    let bpm = model.tempo_map.tempo_at(t).map_or(config.default_bpm, |(bpm, _)| bpm);
+    duration * bpm.max(1.0) / 60.0
+}
+
+/// Escena del modo partitura: pentagramas, notas, acordes y glissandos visibles y claves.
+pub fn staff_shapes(model: &Model, pane: Rect, timeline: &TimelineView, theme: &VisualTheme) -> Vec<Shape> {
+    let staff = Staff::new(pane);
+    let config = &model.config.staff;
+    let quarter = config.resolution.eq_ignore_ascii_case("quarter");
+    let a4 = model.config.view.a4_reference;
+    let write = |freq: f32| spell(Staff::quantize(freq, a4, quarter), config.prefer_flats);
+
+    let mut shapes = staff.line_shapes(theme);
+    let (visible_from, visible_to) = timeline.visible_range();
+    let lookback = visible_from - timeline.page_duration;
+    let archived = if model.history.is_empty() { Vec::new() } else { model.history_events_in(lookback, visible_to) };
+    for event in model.live_events_in(lookback, visible_to).iter().chain(archived.iter()) {
+        let Some(start_time) = event.start_time() else { continue };
+        let t = model.placement_time(start_time);
+        let (notes, duration, gliss_to) = match event {
+            MusicalEvent::Note { frequency, duration, .. } | MusicalEvent::NoteColored { frequency, duration, .. } => {
+                (vec![write(*frequency)], *duration, None)
+            }
+            MusicalEvent::Chord { frequencies, duration, .. } => (frequencies.iter().map(|f| write(*f)).collect(), *duration, None),
+            MusicalEvent::Glissando { start_freq, end_freq, duration, .. } => (vec![write(*start_freq)], *duration, Some(write(*end_freq).0)),
+            _ => continue,
+        };
+        let Some((x, fade)) = timeline.place(t, duration) else { continue };
+        if x < staff.notes_left() {
+            continue;
+        }
+        let color = with_alpha(theme.text, fade);
+        let value = NoteValue::from_beats(duration_beats(model, config, t, duration));
+        shapes.extend(staff.chord_shapes(x, &notes, value, color));
+        if let (Some(end_step), Some((start_step, _))) = (gliss_to, notes.first()) {
+            let end = pt2(timeline.time_to_x(t + duration), staff.step_y(end_step));
+            shapes.push(Shape::line(pt2(x + staff.gap * 0.7, staff.step_y(*start_step)), end, (staff.gap * 0.15).max(1.0), color));
+        }
+    }
+    shapes.extend(staff.clef_shapes(theme));
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staff_positions_values_and_ledgers() {
+        let staff = Staff::new(Rect::from_w_h(800.0, 520.0));
+        assert_eq!(staff.gap, 16.0);
+        // La4 (440 Hz) en el segundo espacio del pentagrama de sol
+        let (step, accidental) = spell(Staff::quantize(440.0, 440.0, false), false);
+        assert_eq!((step, accidental), (33, Accidental::Natural));
+        assert!(staff.step_y(step) > staff.step_y(TREBLE_LINES[1]) && staff.step_y(step) < staff.step_y(TREBLE_LINES[2]));
+        // 452 Hz está a medio semitono: cuarto de tono alto con resolución de cuartos
+        assert_eq!(spell(Staff::quantize(452.9, 440.0, true), false).1, Accidental::QuarterSharp);
+
+        assert_eq!(ledger_steps(MIDDLE_C_STEP), vec![28]);
+        assert_eq!(ledger_steps(43), vec![40, 42]);
+        assert_eq!(ledger_steps(13), vec![16, 14]);
+        assert!(ledger_steps(33).is_empty());
+
+        assert_eq!(NoteValue::from_beats(4.0), NoteValue::Whole);
+        assert_eq!(NoteValue::from_beats(1.1), NoteValue::Quarter);
+        assert_eq!(NoteValue::from_beats(0.25), NoteValue::Sixteenth);
+        let whole = staff.chord_shapes(0.0, &[(33, Accidental::Natural)], NoteValue::Whole, [1.0; 4]);
+        let eighth = staff.chord_shapes(0.0, &[(33, Accidental::Natural)], NoteValue::Eighth, [1.0; 4]);
+        assert_eq!((whole.len(), eighth.len()), (1, 3));
+    }
+}
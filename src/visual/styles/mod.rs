@@ -1,57 +1,135 @@
-// 🎨 Estilos visuales
-// Definiciones de colores, fuentes y estilos (futuro)
-use nannou::prelude::*;
+// src/visual/styles/mod.rs
 
-pub fn theme_from_config(config: &crate::config::AppConfig) -> VisualTheme {
-    if let Some(style) = config.visual.style.as_ref() {
-        VisualTheme::from_name(style).unwrap_or_default()
-    } else {
-        VisualTheme::default()
+//! 🎨 Temas visuales
+//!
+//! Colores, tamaños de fuente y grosores de línea de la escena. Además de los temas integrados
+//! ("dark", "light") se cargan archivos TOML de `[themes].directory` (`themes/<nombre>.toml`);
+//! los campos que falten toman el valor del tema oscuro. El tema activo se cambia con la tecla T
+//! o con `/theme nombre`, y se vuelve a leer cuando su archivo cambia en disco.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::config::{AppConfig, ThemeConfig};
+use crate::errors::{VisualizerError, VisualizerResult};
+use crate::visual::scene::Color;
+
+/// Tema inicial según `[visual].theme`; si no existe se usa el oscuro.
+pub fn theme_from_config(config: &AppConfig) -> VisualTheme {
+    ThemeLibrary::new(&config.themes, &config.visual.theme).theme().clone()
+}
+
+/// Colores por tipo de evento.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EventColors {
+    /// Color fijo de las notas; sin él se colorean según su altura.
+    pub note: Option<Color>,
+    pub drone: Color,
+    pub cluster: Color,
+    pub noise: Color,
+    /// Plica que une las notas de un acorde.
+    pub chord_stem: Color,
+}
+
+/// Los campos que falten en un archivo de tema toman el valor del tema oscuro.
+impl Default for EventColors {
+    fn default() -> Self {
+        VisualTheme::dark_theme().events
+    }
+}
+
+/// Tamaños de fuente en puntos.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ThemeFonts {
+    /// Etiquetas de las reglas de tiempo y altura.
+    pub label_size: u32,
+    pub legend_size: u32,
+    pub hud_size: u32,
+}
+
+impl Default for ThemeFonts {
+    fn default() -> Self {
+        VisualTheme::dark_theme().fonts
     }
 }
+
+/// Grosores de línea en píxeles.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LineWeights {
+    pub grid: f32,
+    /// Límites de octava en la regla de alturas.
+    pub octave: f32,
+    pub playhead: f32,
+    pub chord_stem: f32,
+}
+
+impl Default for LineWeights {
+    fn default() -> Self {
+        VisualTheme::dark_theme().lines
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
 pub struct VisualTheme {
+    /// Nombre del tema (el del archivo si no se indica).
+    pub name: String,
     /// Color de fondo general de la interfaz
-    pub background_color: Srgb<u8>,
+    pub background: Color,
     /// Color de las líneas de la cuadrícula
-    pub grid_color: Srgba<f32>,
+    pub grid: Color,
     /// Color del texto principal
-    pub text_color: Srgba<f32>,
-    /// Color de acento para elementos activos
-    pub accent_color: Srgba<f32>,
-    /// Grosor de las líneas de la cuadrícula
-    pub grid_line_thickness: f32,
-    /// Tamaño base de fuente
-    pub font_size: u32,
-    /// Radio de las esquinas en elementos visuales
-    pub border_radius: f32,
+    pub text: Color,
+    /// Color de acento para elementos activos (cabeza de lectura)
+    pub accent: Color,
+    pub events: EventColors,
+    pub fonts: ThemeFonts,
+    pub lines: LineWeights,
 }
 
 impl VisualTheme {
     pub fn dark_theme() -> Self {
         Self {
-            background_color: srgb8(15, 15, 20),
-            grid_color: srgba(0.3, 0.5, 0.8, 0.8),
-            text_color: srgba(0.9, 0.9, 1.0, 1.0),
-            accent_color: srgba(0.4, 0.6, 0.9, 0.9),
-            grid_line_thickness: 1.5,
-            font_size: 14,
-            border_radius: 4.0,
+            name: "dark".to_string(),
+            background: [15.0 / 255.0, 15.0 / 255.0, 20.0 / 255.0, 1.0],
+            grid: [0.3, 0.5, 0.8, 0.8],
+            text: [0.9, 0.9, 1.0, 1.0],
+            accent: [0.4, 0.6, 0.9, 0.9],
+            events: EventColors {
+                note: None,
+                drone: [0.1, 0.9, 0.3, 0.6],
+                cluster: [0.7, 0.2, 0.8, 0.7],
+                noise: [0.95, 0.9, 0.6, 1.0],
+                chord_stem: [1.0, 1.0, 1.0, 0.4],
+            },
+            fonts: ThemeFonts { label_size: 9, legend_size: 11, hud_size: 10 },
+            lines: LineWeights { grid: 1.5, octave: 1.5, playhead: 1.5, chord_stem: 2.0 },
         }
     }
 
     pub fn light_theme() -> Self {
         Self {
-            background_color: srgb8(245, 245, 250),
-            grid_color: srgba(0.6, 0.6, 0.6, 0.7),
-            text_color: srgba(0.1, 0.1, 0.2, 1.0),
-            accent_color: srgba(0.2, 0.4, 0.8, 1.0),
-            grid_line_thickness: 1.0,
-            font_size: 14,
-            border_radius: 4.0,
+            name: "light".to_string(),
+            background: [245.0 / 255.0, 245.0 / 255.0, 250.0 / 255.0, 1.0],
+            grid: [0.6, 0.6, 0.6, 0.7],
+            text: [0.1, 0.1, 0.2, 1.0],
+            accent: [0.2, 0.4, 0.8, 1.0],
+            events: EventColors {
+                note: None,
+                drone: [0.05, 0.55, 0.2, 0.7],
+                cluster: [0.5, 0.1, 0.6, 0.7],
+                noise: [0.55, 0.45, 0.1, 1.0],
+                chord_stem: [0.0, 0.0, 0.0, 0.4],
+            },
+            fonts: ThemeFonts::default(),
+            lines: LineWeights { grid: 1.0, ..LineWeights::default() },
         }
     }
 
-    /// Devuelve un tema visual a partir de un nombre ("dark", "light")
+    /// Devuelve un tema integrado a partir de su nombre ("dark", "light")
     pub fn from_name(name: &str) -> Option<Self> {
         match name.to_lowercase().as_str() {
             "dark" => Some(Self::dark_theme()),
@@ -59,14 +137,145 @@ impl VisualTheme {
             _ => None,
         }
     }
+
+    /// Interpreta un tema en TOML; `name` se usa si el archivo no declara el suyo.
+    pub fn parse(name: &str, text: &str) -> Result<Self, toml::de::Error> {
+        let mut theme: VisualTheme = toml::from_str(text)?;
+        if theme.name.is_empty() {
+            theme.name = name.to_string();
+        }
+        Ok(theme)
+    }
+
+    /// Color de una nota: el fijo del tema o, si no hay, el de su altura.
+    pub fn note_color(&self, frequency: f32, alpha: f32) -> Color {
+        match self.events.note {
+            Some([r, g, b, _]) => [r, g, b, alpha],
+            None => crate::visual::scene::note_color(frequency, alpha),
+        }
+    }
 }
 
 impl Default for VisualTheme {
     fn default() -> Self {
-        Self::dark_theme()
+        let mut theme = Self::dark_theme();
+        theme.name.clear();
+        theme
     }
 }
 
+/// Temas disponibles (integrados y archivos del directorio) y tema activo.
+#[derive(Debug, Clone)]
+pub struct ThemeLibrary {
+    directory: PathBuf,
+    reload_interval: f32,
+    /// Nombre con el que se seleccionó el tema activo (archivo o integrado).
+    selected: String,
+    current: VisualTheme,
+    /// Archivo del tema activo y su última fecha de modificación leída.
+    source: Option<(PathBuf, Option<SystemTime>)>,
+    last_check: f32,
+}
+
+impl ThemeLibrary {
+    /// Carga `initial`; si no existe se registra un aviso y se usa el tema oscuro.
+    pub fn new(config: &ThemeConfig, initial: &str) -> Self {
+        let mut library = Self {
+            directory: PathBuf::from(&config.directory),
+            reload_interval: config.reload_interval_secs,
+            selected: "dark".to_string(),
+            current: VisualTheme::dark_theme(),
+            source: None,
+            last_check: 0.0,
+        };
+        if let Err(e) = library.select(initial) {
+            crate::logging::Logger::log_warn(&format!("🎨 Tema '{initial}' no disponible ({e}); se usa 'dark'"));
+        }
+        library
+    }
+
+    pub fn theme(&self) -> &VisualTheme {
+        &self.current
+    }
+
+    /// Nombres disponibles: los integrados y los `.toml` del directorio, sin repetir.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec!["dark".to_string(), "light".to_string()];
+        if let Ok(entries) = std::fs::read_dir(&self.directory) {
+            let mut files: Vec<String> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+                .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_lowercase()))
+                .filter(|name| !names.contains(name))
+                .collect();
+            files.sort();
+            names.extend(files);
+        }
+        names
+    }
+
+    /// Activa un tema por nombre. Un archivo del directorio tiene prioridad sobre el integrado.
+    pub fn select(&mut self, name: &str) -> VisualizerResult<()> {
+        let path = self.directory.join(format!("{}.toml", name.to_lowercase()));
+        if path.is_file() {
+            self.current = load_theme_file(&path)?;
+            self.source = Some((path.clone(), modified(&path)));
+            self.selected = name.to_lowercase();
+            return Ok(());
+        }
+        match VisualTheme::from_name(name) {
+            Some(theme) => {
+                self.current = theme;
+                self.source = None;
+                self.selected = name.to_lowercase();
+                Ok(())
+            }
+            None => Err(VisualizerError::ConfigError { message: format!("tema desconocido: {name}") }),
+        }
+    }
+
+    /// Pasa al siguiente tema disponible y devuelve su nombre.
+    pub fn select_next(&mut self) -> VisualizerResult<String> {
+        let names = self.names();
+        let current = names.iter().position(|n| *n == self.selected);
+        let next = names[current.map_or(0, |i| (i + 1) % names.len())].clone();
+        self.select(&next)?;
+        Ok(next)
+    }
+
+    /// Vuelve a leer el archivo del tema activo si ha cambiado desde la última lectura
+    /// (como mucho una vez cada `reload_interval_secs`). Devuelve `true` si se recargó.
+    /// Si el archivo nuevo no es válido se conserva el tema anterior.
+    pub fn poll(&mut self, now: f32) -> VisualizerResult<bool> {
+        if now - self.last_check < self.reload_interval {
+            return Ok(false);
+        }
+        self.last_check = now;
+        let Some((path, seen)) = &self.source else { return Ok(false) };
+        let current = modified(path);
+        if current == *seen {
+            return Ok(false);
+        }
+        let path = path.clone();
+        self.source = Some((path.clone(), current));
+        self.current = load_theme_file(&path)?;
+        Ok(true)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Lee un tema desde un archivo TOML; el nombre por defecto es el del archivo.
+pub fn load_theme_file(path: &Path) -> VisualizerResult<VisualTheme> {
+    let text = std::fs::read_to_string(path)?;
+    let name = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default();
+    VisualTheme::parse(&name, &text).map_err(|e| VisualizerError::ConfigError {
+        message: format!("tema {} inválido: {e}", path.display()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,12 +283,61 @@ mod tests {
     #[test]
     fn test_default_is_dark_theme() {
         let theme = VisualTheme::default();
-        assert_eq!(theme.background_color, srgb8(15, 15, 20));
+        assert_eq!(theme.background, [15.0 / 255.0, 15.0 / 255.0, 20.0 / 255.0, 1.0]);
     }
 
     #[test]
     fn test_light_theme_values() {
         let theme = VisualTheme::light_theme();
-        assert_eq!(theme.background_color, srgb8(245, 245, 250));
+        assert_eq!(theme.background, [245.0 / 255.0, 245.0 / 255.0, 250.0 / 255.0, 1.0]);
+    }
+
+    #[test]
+    fn test_partial_theme_file_falls_back_to_dark() {
+        let theme = VisualTheme::parse(
+            "escenario",
+            r#"
+background = [0.0, 0.0, 0.0, 1.0]
+[events]
+note = [1.0, 0.5, 0.0, 1.0]
+[lines]
+playhead = 3.0
+"#,
+        )
+        .unwrap();
+        assert_eq!(theme.name, "escenario");
+        assert_eq!(theme.background, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(theme.note_color(440.0, 0.5), [1.0, 0.5, 0.0, 0.5]);
+        assert_eq!(theme.lines.playhead, 3.0);
+        assert_eq!(theme.lines.octave, VisualTheme::dark_theme().lines.octave);
+        assert_eq!(theme.events.drone, VisualTheme::dark_theme().events.drone);
+    }
+
+    #[test]
+    fn test_partial_sections_keep_dark_values() {
+        let theme = VisualTheme::parse("escenario", "[lines]\nplayhead = 3.0\n[fonts]\nhud_size = 14\n").unwrap();
+        let dark = VisualTheme::dark_theme();
+        assert_eq!(theme.lines, LineWeights { playhead: 3.0, ..dark.lines.clone() });
+        assert_eq!(theme.fonts, ThemeFonts { hud_size: 14, ..dark.fonts.clone() });
+        assert_eq!(theme.events, dark.events);
+    }
+
+    #[test]
+    fn test_library_reloads_changed_file() {
+        let dir = std::env::temp_dir().join(format!("temas_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("prueba.toml");
+        std::fs::write(&path, "text = [1.0, 0.0, 0.0, 1.0]\n").unwrap();
+        let config = ThemeConfig { directory: dir.to_string_lossy().into_owned(), reload_interval_secs: 0.0 };
+        let mut library = ThemeLibrary::new(&config, "prueba");
+        assert_eq!(library.theme().text, [1.0, 0.0, 0.0, 1.0]);
+        assert!(library.names().contains(&"prueba".to_string()));
+
+        std::fs::write(&path, "text = [0.0, 1.0, 0.0, 1.0]\n").unwrap();
+        // Forzar una fecha distinta aunque el sistema de archivos tenga poca resolución
+        library.source = Some((path.clone(), None));
+        assert!(library.poll(1.0).unwrap());
+        assert_eq!(library.theme().text, [0.0, 1.0, 0.0, 1.0]);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
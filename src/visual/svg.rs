@@ -0,0 +1,139 @@
+// src/visual/svg.rs
+
+//! 🖋️ Exportación SVG
+//!
+//! Serializa una `Scene` a SVG sin GPU ni ventana. La salida es determinista (dos decimales,
+//! orden de dibujo), de modo que puede compararse con archivos de referencia en las pruebas.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use crate::errors::VisualizerResult;
+use crate::visual::scene::{Color, Scene, Shape, TextAlign};
+
+/// Componentes de color SVG (`rgb(...)` y opacidad por separado).
+fn svg_color(color: Color) -> (String, f32) {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (
+        format!("rgb({},{},{})", channel(color[0]), channel(color[1]), channel(color[2])),
+        color[3].clamp(0.0, 1.0),
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl Scene {
+    /// Documento SVG de la escena. El origen pasa de centro con y hacia arriba (nannou)
+    /// a esquina superior izquierda con y hacia abajo (SVG).
+    pub fn to_svg(&self) -> String {
+        let (left, top) = (self.bounds.left(), self.bounds.top());
+        let sx = |x: f32| x - left;
+        let sy = |y: f32| top - y;
+        let (w, h) = (self.bounds.w(), self.bounds.h());
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.0} {h:.0}" font-family="sans-serif">"#
+        );
+        let (fill, opacity) = svg_color(self.background);
+        let _ = writeln!(out, r#"<rect width="100%" height="100%" fill="{fill}" fill-opacity="{opacity:.2}"/>"#);
+
+        for shape in &self.shapes {
+            let _ = match shape {
+                Shape::Ellipse { center, radius, color } => {
+                    let (fill, opacity) = svg_color(*color);
+                    writeln!(
+                        out,
+                        r#"<circle cx="{:.2}" cy="{:.2}" r="{radius:.2}" fill="{fill}" fill-opacity="{opacity:.2}"/>"#,
+                        sx(center.x),
+                        sy(center.y)
+                    )
+                }
+                Shape::Rect { center, size, color } => {
+                    let (fill, opacity) = svg_color(*color);
+                    writeln!(
+                        out,
+                        r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{fill}" fill-opacity="{opacity:.2}"/>"#,
+                        sx(center.x - size.x / 2.0),
+                        sy(center.y + size.y / 2.0),
+                        size.x,
+                        size.y
+                    )
+                }
+                Shape::Line { start, end, weight, color } => {
+                    let (stroke, opacity) = svg_color(*color);
+                    writeln!(
+                        out,
+                        r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{stroke}" stroke-opacity="{opacity:.2}" stroke-width="{weight:.2}"/>"#,
+                        sx(start.x),
+                        sy(start.y),
+                        sx(end.x),
+                        sy(end.y)
+                    )
+                }
+                Shape::Polyline { points, weight, color } => {
+                    let (stroke, opacity) = svg_color(*color);
+                    let points: Vec<String> = points.iter().map(|p| format!("{:.2},{:.2}", sx(p.x), sy(p.y))).collect();
+                    writeln!(
+                        out,
+                        r#"<polyline points="{}" fill="none" stroke="{stroke}" stroke-opacity="{opacity:.2}" stroke-width="{weight:.2}"/>"#,
+                        points.join(" ")
+                    )
+                }
+                Shape::Text { position, text, size, color, align } => {
+                    let (fill, opacity) = svg_color(*color);
+                    let anchor = match align {
+                        TextAlign::Left => "start",
+                        TextAlign::Center => "middle",
+                        TextAlign::Right => "end",
+                    };
+                    writeln!(
+                        out,
+                        r#"<text x="{:.2}" y="{:.2}" font-size="{size}" text-anchor="{anchor}" dominant-baseline="central" fill="{fill}" fill-opacity="{opacity:.2}">{}</text>"#,
+                        sx(position.x),
+                        sy(position.y),
+                        escape(text)
+                    )
+                }
+            };
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Escribe la escena como SVG en `path`, creando la carpeta si hace falta.
+    pub fn write_svg(&self, path: &Path) -> VisualizerResult<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_svg())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::prelude::*;
+
+    #[test]
+    fn test_svg_golden_output() {
+        let mut scene = Scene::new(Rect::from_w_h(200.0, 100.0), [0.0, 0.0, 0.0, 1.0]);
+        scene.extend([
+            Shape::Ellipse { center: pt2(0.0, 0.0), radius: 10.0, color: [1.0, 0.0, 0.0, 0.5] },
+            Shape::line(pt2(-100.0, 50.0), pt2(100.0, -50.0), 1.5, [1.0, 1.0, 1.0, 1.0]),
+            Shape::text(pt2(90.0, 40.0), "A < B", 9, [1.0, 1.0, 1.0, 1.0], TextAlign::Right),
+        ]);
+        let expected = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100" viewBox="0 0 200 100" font-family="sans-serif">"#, "\n",
+            r#"<rect width="100%" height="100%" fill="rgb(0,0,0)" fill-opacity="1.00"/>"#, "\n",
+            r#"<circle cx="100.00" cy="50.00" r="10.00" fill="rgb(255,0,0)" fill-opacity="0.50"/>"#, "\n",
+            r#"<line x1="0.00" y1="0.00" x2="200.00" y2="100.00" stroke="rgb(255,255,255)" stroke-opacity="1.00" stroke-width="1.50"/>"#, "\n",
+            r#"<text x="190.00" y="10.00" font-size="9" text-anchor="end" dominant-baseline="central" fill="rgb(255,255,255)" fill-opacity="1.00">A &lt; B</text>"#, "\n",
+            "</svg>\n",
+        );
+        assert_eq!(scene.to_svg(), expected);
+    }
+}
@@ -1,8 +1,9 @@
 // ⏱️ Sistema de sincronización temporal
 // Maneja la visualización de eventos en tiempo real según su timestamp y duración
 
-use crate::musical_events::MusicalEvent;
-use tracing::{debug, info};
+use std::time::Instant;
+use crate::events::MusicalEvent;
+use crate::logging::Logger;
 
 /// Estados de sincronización de un evento
 #[derive(Debug, Clone, PartialEq)]
@@ -16,11 +17,16 @@ pub enum EventTimeState {
 }
 
 /// Calculadora de sincronización temporal
+#[derive(Debug, Clone)]
 pub struct TimeSync {
+    /// Instante que corresponde al segundo 0 de los eventos
+    pub origin: Instant,
     /// Tiempo de inicio del sistema
     pub system_start_time: f32,
     /// Offset para sincronización con SuperCollider
     pub time_offset: f32,
+    /// Latencia de red de un sentido medida con `/viz/sync` (segundos)
+    pub latency: f32,
     /// Tolerancia para eventos "casi terminados"
     pub finish_tolerance: f32,
 }
@@ -29,22 +35,40 @@ impl TimeSync {
     /// Crea un nuevo sistema de sincronización
     pub fn new() -> Self {
         Self {
+            origin: Instant::now(),
             system_start_time: 0.0,
             time_offset: 0.0,
+            latency: 0.0,
             finish_tolerance: 0.1, // 100ms de tolerancia
         }
     }
 
+    /// Establece el instante de inicio de la sesión (el de `Model::time_info.start_time`)
+    pub fn set_origin(&mut self, origin: Instant) {
+        self.origin = origin;
+    }
+
     /// Establece el tiempo de inicio del sistema
     pub fn set_start_time(&mut self, start_time: f32) {
         self.system_start_time = start_time;
-        info!("⏱️ Tiempo de inicio establecido: {:.3}s", start_time);
+        Logger::log_info(&format!("⏱️ Tiempo de inicio establecido: {start_time:.3}s"));
     }
 
     /// Establece un offset de tiempo para sincronización
     pub fn set_time_offset(&mut self, offset: f32) {
         self.time_offset = offset;
-        debug!("🔄 Offset de tiempo establecido: {:.3}s", offset);
+        Logger::log_debug(&format!("🔄 Offset de tiempo establecido: {offset:.3}s"));
+    }
+
+    /// Aplica una estimación nueva de `/viz/sync`: desfase mediano y latencia de red.
+    pub fn apply_clock_estimate(&mut self, estimate: &crate::clock_sync::SyncEstimate) {
+        self.set_time_offset(estimate.offset as f32);
+        self.latency = estimate.latency as f32;
+    }
+
+    /// Tiempo ajustado en el instante actual de `clock`, con `origin` como inicio del sistema.
+    pub fn adjusted_now(&self, clock: &dyn crate::clock::Clock, origin: std::time::Instant) -> f32 {
+        self.adjusted_time(clock.now().saturating_duration_since(origin).as_secs_f32())
     }
 
     /// Calcula el tiempo ajustado actual
@@ -55,7 +79,9 @@ impl TimeSync {
     /// Determina el estado temporal de un evento
     pub fn get_event_state(&self, event: &MusicalEvent, current_time: f32) -> EventTimeState {
         let adjusted_time = self.adjusted_time(current_time);
-        let start_time = event.start_time();
+        let start_time = event
+            .start_time()
+            .map_or(0.0, |t| t.saturating_duration_since(self.origin).as_secs_f32());
         let end_time = start_time + event.duration();
 
         if adjusted_time < start_time {
@@ -63,7 +89,11 @@ impl TimeSync {
         } else if adjusted_time > end_time + self.finish_tolerance {
             EventTimeState::Finished
         } else {
-            let progress = event.progress(adjusted_time);
+            let progress = if event.duration() > 0.0 {
+                ((adjusted_time - start_time) / event.duration()).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
             EventTimeState::Active { progress }
         }
     }
@@ -132,6 +162,12 @@ impl TimeSync {
     }
 }
 
+impl Default for TimeSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Estado de renderizado para glissandos
 #[derive(Debug, Clone)]
 pub enum GlissandoRenderState {
@@ -0,0 +1,273 @@
+// src/visual/timeline.rs
+
+use nannou::prelude::*;
+use crate::config::ScrollConfig;
+use crate::model::ScrollMode;
+use crate::visual::scene::{Shape, TextAlign};
+use crate::visual::styles::VisualTheme;
+
+/// Margen entre la cabeza de lectura continua y el borde derecho de la ventana.
+const PLAYHEAD_MARGIN: f32 = 50.0;
+
+/// Correspondencia entre tiempo de sesión (segundos) y posición horizontal en pantalla.
+///
+/// - `Continuous`: la cabeza de lectura está fija cerca del borde derecho y los eventos
+///   se desplazan hacia la izquierda a `scroll_speed` píxeles por segundo.
+/// - `Page`: la cabeza de lectura recorre la ventana de izquierda a derecha durante
+///   `timeline_duration` segundos y después se pasa de página.
+#[derive(Debug, Clone, Copy)]
+pub struct TimelineView {
+    pub mode: ScrollMode,
+    pub now: f32,
+    pub left: f32,
+    pub right: f32,
+    pub px_per_sec: f32,
+    pub page_duration: f32,
+    pub keep_previous_page: bool,
+    pub previous_page_opacity: f32,
+    /// Atenuar los eventos antiguos hacia el borde izquierdo (desactivado al exportar).
+    pub fade: bool,
+}
+
+impl TimelineView {
+    pub fn new(mode: ScrollMode, now: f32, win: Rect, scroll_speed: f32, page_duration: f32, scroll: &ScrollConfig) -> Self {
+        Self {
+            mode,
+            now,
+            left: win.left(),
+            right: win.right(),
+            px_per_sec: scroll_speed.max(1.0),
+            page_duration: page_duration.max(0.1),
+            keep_previous_page: scroll.keep_previous_page,
+            previous_page_opacity: scroll.previous_page_opacity,
+            fade: true,
+        }
+    }
+
+    /// Vista fija que muestra exactamente `[from, to]` a lo ancho de `win`, sin atenuación.
+    /// Se usa para exportar un intervalo arbitrario de la sesión.
+    pub fn range(from: f32, to: f32, win: Rect) -> Self {
+        let px_per_sec = win.w().max(1.0) / (to - from).max(1e-3);
+        Self {
+            mode: ScrollMode::Continuous,
+            now: to - PLAYHEAD_MARGIN / px_per_sec,
+            left: win.left(),
+            right: win.right(),
+            px_per_sec,
+            page_duration: (to - from).max(0.1),
+            keep_previous_page: false,
+            previous_page_opacity: 0.0,
+            fade: false,
+        }
+    }
+
+    /// Inicio (en segundos de sesión) de la página actual.
+    pub fn page_start(&self) -> f32 {
+        self.page_index() as f32 * self.page_duration
+    }
+
+    pub fn page_index(&self) -> u64 {
+        (self.now.max(0.0) / self.page_duration) as u64
+    }
+
+    /// Posición horizontal de la cabeza de lectura.
+    pub fn playhead_x(&self) -> f32 {
+        self.time_to_x(self.now)
+    }
+
+    /// Intervalo de tiempo visible `(desde, hasta)` en segundos de sesión.
+    pub fn visible_range(&self) -> (f32, f32) {
+        match self.mode {
+            ScrollMode::Continuous => {
+                let span = (self.right - PLAYHEAD_MARGIN - self.left) / self.px_per_sec;
+                (self.now - span, self.now + PLAYHEAD_MARGIN / self.px_per_sec)
+            }
+            ScrollMode::Page => {
+                let start = self.page_start();
+                (start, start + self.page_duration)
+            }
+        }
+    }
+
+    /// Segundos representados por cada píxel horizontal.
+    pub fn seconds_per_pixel(&self) -> f32 {
+        match self.mode {
+            ScrollMode::Continuous => 1.0 / self.px_per_sec,
+            ScrollMode::Page => self.page_duration / (self.right - self.left).max(1.0),
+        }
+    }
+
+    pub fn time_to_x(&self, t: f32) -> f32 {
+        match self.mode {
+            ScrollMode::Continuous => self.right - PLAYHEAD_MARGIN - (self.now - t) * self.px_per_sec,
+            ScrollMode::Page => self.page_time_to_x(t, self.page_start()),
+        }
+    }
+
+    fn page_time_to_x(&self, t: f32, page_start: f32) -> f32 {
+        map_range(t - page_start, 0.0, self.page_duration, self.left, self.right)
+    }
+
+    /// Posición y factor de opacidad de un evento que empieza en `t` y dura `duration`.
+    /// En modo continuo los eventos se desvanecen al acercarse al borde izquierdo; en modo
+    /// página la página anterior puede mantenerse atenuada por delante de la cabeza de lectura.
+    pub fn place(&self, t: f32, duration: f32) -> Option<(f32, f32)> {
+        let (from, to) = self.visible_range();
+        match self.mode {
+            ScrollMode::Continuous => {
+                if t < from || t > to {
+                    return None;
+                }
+                let age = self.now - t;
+                let span = self.now - from;
+                let fade = if self.fade && age > duration && duration > 0.0 && span > duration {
+                    1.0 - ((age - duration) / (span - duration)).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+                Some((self.time_to_x(t), fade))
+            }
+            ScrollMode::Page => {
+                if t >= from && t <= self.now.min(to) {
+                    return Some((self.time_to_x(t), 1.0));
+                }
+                let previous_start = from - self.page_duration;
+                if self.keep_previous_page && t >= previous_start && t < from {
+                    let x = self.page_time_to_x(t, previous_start);
+                    if x > self.playhead_x() {
+                        return Some((x, self.previous_page_opacity));
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Atenuación de un evento que suena de `start` a `end` si alguna parte es visible.
+    /// A diferencia de `place`, el evento sigue a la vista aunque empezara antes del borde.
+    pub fn place_span(&self, start: f32, end: f32) -> Option<f32> {
+        let (from, to) = self.visible_range();
+        if end < from || start > to {
+            return None;
+        }
+        match self.mode {
+            ScrollMode::Continuous => {
+                let age = self.now - end;
+                let span = self.now - from;
+                if self.fade && age > 0.0 && span > 0.0 {
+                    Some(1.0 - (age / span).clamp(0.0, 1.0))
+                } else {
+                    Some(1.0)
+                }
+            }
+            ScrollMode::Page => (start <= self.now).then_some(1.0),
+        }
+    }
+
+    /// Cabeza de lectura y, en modo página, el número de página.
+    pub fn playhead_shapes(&self, win: Rect, theme: &VisualTheme) -> Vec<Shape> {
+        let x = self.playhead_x();
+        let mut shapes = vec![Shape::line(pt2(x, win.bottom()), pt2(x, win.top()), theme.lines.playhead, theme.accent)];
+        if self.mode == ScrollMode::Page {
+            shapes.push(Shape::text(
+                pt2(win.right() - 10.0, win.bottom() + 30.0),
+                format!("Página {}", self.page_index() + 1),
+                theme.fonts.legend_size,
+                theme.text,
+                TextAlign::Right,
+            ));
+        }
+        shapes
+    }
+}
+
+/// Altura de la barra de navegación por la sesión.
+pub const TRANSPORT_BAR_H: f32 = 8.0;
+
+/// Rectángulo de la barra de navegación (borde inferior de la ventana).
+pub fn transport_bar_rect(win: Rect) -> Rect {
+    Rect::from_corners(pt2(win.left(), win.bottom()), pt2(win.right(), win.bottom() + TRANSPORT_BAR_H))
+}
+
+/// Barra de navegación por toda la sesión y, si la vista está congelada, el indicador con el
+/// instante mostrado respecto al directo. Sin congelar no se dibuja nada.
+pub fn transport_shapes(win: Rect, display_time: f32, live_time: f32, frozen: bool, theme: &VisualTheme) -> Vec<Shape> {
+    if !frozen {
+        return Vec::new();
+    }
+    let bar = transport_bar_rect(win);
+    let fraction = if live_time > 0.0 { (display_time / live_time).clamp(0.0, 1.0) } else { 1.0 };
+    let marker_x = bar.left() + fraction * bar.w();
+    let ([gr, gg, gb, _], [ar, ag, ab, _]) = (theme.grid, theme.accent);
+    vec![
+        Shape::rect(bar, [gr, gg, gb, 0.8]),
+        Shape::rect(Rect::from_corners(bar.bottom_left(), pt2(marker_x, bar.top())), [ar, ag, ab, 0.8]),
+        Shape::Ellipse { center: pt2(marker_x, bar.y()), radius: 6.0, color: theme.text },
+        Shape::text(
+            pt2(win.left() + 20.0, bar.top() + 14.0),
+            format!(
+                "⏸ CONGELADO  {:.1}s / {:.1}s  ({:+.1}s)   Espacio/Fin: volver al directo",
+                display_time,
+                live_time,
+                display_time - live_time
+            ),
+            theme.fonts.legend_size,
+            theme.accent,
+            TextAlign::Left,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(mode: ScrollMode, now: f32) -> TimelineView {
+        let win = Rect::from_w_h(1000.0, 500.0);
+        let scroll = ScrollConfig { keep_previous_page: true, ..ScrollConfig::default() };
+        TimelineView::new(mode, now, win, 100.0, 10.0, &scroll)
+    }
+
+    #[test]
+    fn test_transport_only_when_frozen() {
+        let win = Rect::from_w_h(1000.0, 500.0);
+        let theme = VisualTheme::default();
+        assert!(transport_shapes(win, 5.0, 10.0, false, &theme).is_empty());
+        let shapes = transport_shapes(win, 5.0, 10.0, true, &theme);
+        assert!(matches!(shapes[2], Shape::Ellipse { center, .. } if center == pt2(0.0, -246.0)));
+    }
+
+    #[test]
+    fn test_continuous_scrolls_at_speed() {
+        let tl = view(ScrollMode::Continuous, 5.0);
+        let x_now = tl.time_to_x(5.0);
+        let x_past = tl.time_to_x(4.0);
+        assert!((x_now - x_past - 100.0).abs() < 1e-3);
+        assert!((x_now - (500.0 - PLAYHEAD_MARGIN)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_page_sweeps_and_flips() {
+        let tl = view(ScrollMode::Page, 12.5);
+        assert_eq!(tl.page_index(), 1);
+        assert!((tl.playhead_x() - (-250.0)).abs() < 1e-3);
+        // Evento de la página actual ya tocado
+        assert_eq!(tl.place(11.0, 1.0).map(|p| p.1), Some(1.0));
+        // Evento de la página anterior por delante de la cabeza de lectura: atenuado
+        let (x, opacity) = tl.place(8.0, 1.0).unwrap();
+        assert!(x > tl.playhead_x());
+        assert!(opacity < 1.0);
+        // Evento de la página anterior ya cubierto por la cabeza de lectura: oculto
+        assert!(tl.place(10.5 - 10.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_range_view_spans_window_without_fade() {
+        let win = Rect::from_w_h(1000.0, 500.0);
+        let tl = TimelineView::range(10.0, 20.0, win);
+        assert!((tl.visible_range().0 - 10.0).abs() < 1e-3);
+        assert!((tl.time_to_x(10.0) - win.left()).abs() < 1e-3);
+        assert!((tl.time_to_x(20.0) - win.right()).abs() < 1e-3);
+        assert_eq!(tl.place(10.5, 0.1).map(|p| p.1), Some(1.0));
+    }
+}
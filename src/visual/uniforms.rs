@@ -3,6 +3,7 @@
 // Debe coincidir con la estructura WGSL usada en vertex.wgsl y particles.wgsl.
 
 use bytemuck::{Pod, Zeroable};
+use crate::feature_bus::AudioFeatures;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -16,7 +17,8 @@ pub struct Uniforms {
     pub particle_radius: f32,
     pub edge_softness: f32,
     pub mix_strength: f32,
-    pub _padding: [f32; 3], // Para alineación de 16 bytes
+    // Relleno hasta 128 bytes: el `vec3<f32>` final de WGSL se alinea a 16 bytes
+    pub _padding: [f32; 7],
 }
 
 impl Uniforms {
@@ -34,7 +36,7 @@ impl Uniforms {
             particle_radius: 1.0,
             edge_softness: 0.1,
             mix_strength: 0.0,
-            _padding: [0.0; 3],
+            _padding: [0.0; 7],
         }
     }
 
@@ -43,4 +45,18 @@ impl Uniforms {
         self.audio_level = audio_level;
         self.beat_intensity = beat_intensity;
     }
+
+    /// Vuelca las características del bus de audio en el bloque de uniforms:
+    /// el ataque controla el brillo (glow) y el centroide normalizado la mezcla de color.
+    pub fn update_from_features(&mut self, features: &AudioFeatures, centroid_max: f32) {
+        self.update_from_audio(features.time, features.rms, features.beat_intensity());
+        self.glow_strength = features.onset_strength;
+        self.mix_strength = (features.centroid / centroid_max.max(1.0)).clamp(0.0, 1.0);
+    }
+}
+
+impl Default for Uniforms {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -1,9 +1,11 @@
 // 📦 Código para gestionar un buffer de Uniforms en wgpu compatible con WGSL
 // El struct Uniforms ya está definido en visual/uniforms.rs con #[repr(C)] y bytemuck
 
-use wgpu::util::DeviceExt;
+use nannou::wgpu;
+use nannou::wgpu::util::DeviceExt;
 use crate::visual::uniforms::Uniforms;
 
+#[derive(Debug)]
 pub struct UniformsGpu {
     pub buffer: wgpu::Buffer,
     pub bind_group_layout: wgpu::BindGroupLayout,
@@ -0,0 +1,174 @@
+// src/visual/viewport.rs
+
+use crate::config::{AudioConfig, ViewConfig};
+
+/// Zoom y desplazamiento interactivos de la vista en ambos ejes.
+///
+/// - Tiempo: `time_zoom` multiplica los píxeles por segundo (modo continuo) y divide la
+///   duración de página (modo página). El desplazamiento temporal lo gestiona `Transport`.
+/// - Altura: el rango de frecuencias visible se guarda en octavas (`log2(Hz)`), de modo que
+///   el zoom y el desplazamiento son uniformes en toda la escala logarítmica.
+///
+/// Los cambios fijan un objetivo y `update` se acerca a él de forma exponencial, lo que
+/// produce una animación suave con independencia de la tasa de frames.
+#[derive(Debug, Clone)]
+pub struct Viewport {
+    config: ViewConfig,
+    time_zoom: f32,
+    target_time_zoom: f32,
+    log_min: f32,
+    log_max: f32,
+    target_log_min: f32,
+    target_log_max: f32,
+    bounds: (f32, f32),
+    /// Posición del ratón (y) y rango de octavas al empezar a arrastrar verticalmente.
+    pub pan_anchor: Option<(f32, f32, f32)>,
+}
+
+impl Viewport {
+    pub fn new(config: ViewConfig, audio_config: &AudioConfig) -> Self {
+        let log_min = audio_config.freq_min.max(1.0).log2();
+        let log_max = audio_config.freq_max.max(audio_config.freq_min.max(1.0) * 2.0).log2();
+        Self {
+            config,
+            time_zoom: 1.0,
+            target_time_zoom: 1.0,
+            log_min,
+            log_max,
+            target_log_min: log_min,
+            target_log_max: log_max,
+            bounds: (log_min, log_max),
+            pan_anchor: None,
+        }
+    }
+
+    pub fn time_zoom(&self) -> f32 {
+        self.time_zoom
+    }
+
+    /// Rango de frecuencias visible `(mínima, máxima)` en Hz.
+    pub fn freq_range(&self) -> (f32, f32) {
+        (self.log_min.exp2(), self.log_max.exp2())
+    }
+
+    /// Copia de `audio_config` con el rango de frecuencias visible, para reutilizar
+    /// `map_freq_to_y` y el resto de funciones de mapeo sin cambios.
+    pub fn apply_to(&self, audio_config: &AudioConfig) -> AudioConfig {
+        let (freq_min, freq_max) = self.freq_range();
+        AudioConfig { freq_min, freq_max, ..audio_config.clone() }
+    }
+
+    /// Acerca (`steps > 0`) o aleja (`steps < 0`) el eje temporal.
+    pub fn zoom_time(&mut self, steps: f32) {
+        let factor = self.config.zoom_step.max(1.01).powf(steps);
+        self.target_time_zoom = (self.target_time_zoom * factor)
+            .clamp(self.config.min_time_zoom, self.config.max_time_zoom);
+    }
+
+    /// Acerca o aleja el eje de alturas manteniendo fija la frecuencia `anchor` (Hz),
+    /// o el centro del rango visible si no se indica.
+    pub fn zoom_pitch(&mut self, steps: f32, anchor: Option<f32>) {
+        let factor = self.config.zoom_step.max(1.01).powf(-steps);
+        let span = self.target_log_max - self.target_log_min;
+        let anchor = anchor
+            .filter(|f| *f > 0.0)
+            .map(f32::log2)
+            .unwrap_or((self.target_log_min + self.target_log_max) / 2.0)
+            .clamp(self.target_log_min, self.target_log_max);
+        let full = self.bounds.1 - self.bounds.0;
+        let new_span = (span * factor).clamp(self.config.min_octaves.min(full), full);
+        let ratio = (anchor - self.target_log_min) / span.max(f32::EPSILON);
+        let new_min = anchor - ratio * new_span;
+        self.set_pitch_target(new_min, new_min + new_span);
+    }
+
+    /// Desplaza el rango de alturas `octaves` octavas (positivo = hacia los agudos).
+    pub fn pan_pitch(&mut self, octaves: f32) {
+        self.set_pitch_target(self.target_log_min + octaves, self.target_log_max + octaves);
+    }
+
+    /// Desplaza el rango de alturas un número de pasos de `pan_step_octaves`.
+    pub fn pan_pitch_steps(&mut self, steps: f32) {
+        self.pan_pitch(steps * self.config.pan_step_octaves);
+    }
+
+    /// Arrastre vertical: el contenido sigue al ratón. `dy_fraction` es el desplazamiento
+    /// del ratón desde el ancla dividido por la altura de la ventana.
+    pub fn drag_pitch(&mut self, anchor_min: f32, anchor_max: f32, dy_fraction: f32) {
+        let shift = -dy_fraction * (anchor_max - anchor_min);
+        self.set_pitch_target(anchor_min + shift, anchor_max + shift);
+        // El arrastre se sigue sin animación para que no haya retraso respecto al ratón
+        self.log_min = self.target_log_min;
+        self.log_max = self.target_log_max;
+    }
+
+    /// Rango de octavas objetivo actual, para anclar un arrastre.
+    pub fn pitch_target(&self) -> (f32, f32) {
+        (self.target_log_min, self.target_log_max)
+    }
+
+    /// Vuelve al zoom 1:1 y al rango de frecuencias completo de la configuración.
+    pub fn reset(&mut self) {
+        self.target_time_zoom = 1.0;
+        self.target_log_min = self.bounds.0;
+        self.target_log_max = self.bounds.1;
+    }
+
+    fn set_pitch_target(&mut self, min: f32, max: f32) {
+        let span = (max - min).min(self.bounds.1 - self.bounds.0);
+        let min = min.clamp(self.bounds.0, self.bounds.1 - span);
+        self.target_log_min = min;
+        self.target_log_max = min + span;
+    }
+
+    /// Avanza la animación `dt` segundos hacia los valores objetivo.
+    pub fn update(&mut self, dt: f32) {
+        let k = if self.config.smoothing > 0.0 { 1.0 - (-self.config.smoothing * dt).exp() } else { 1.0 };
+        self.time_zoom += (self.target_time_zoom - self.time_zoom) * k;
+        self.log_min += (self.target_log_min - self.log_min) * k;
+        self.log_max += (self.target_log_max - self.log_max) * k;
+    }
+
+    pub fn config(&self) -> &ViewConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audio() -> AudioConfig {
+        AudioConfig { freq_min: 20.0, freq_max: 20480.0, ..AudioConfig::default() }
+    }
+
+    #[test]
+    fn test_pitch_zoom_keeps_anchor_and_bounds() {
+        let mut vp = Viewport::new(ViewConfig::default(), &audio());
+        vp.zoom_pitch(4.0, Some(440.0));
+        vp.update(10.0);
+        let (lo, hi) = vp.freq_range();
+        assert!(lo < 440.0 && hi > 440.0);
+        assert!(hi / lo < 20480.0 / 20.0);
+
+        vp.pan_pitch(100.0);
+        vp.update(10.0);
+        assert!((vp.freq_range().1 - 20480.0).abs() < 1.0);
+
+        vp.zoom_pitch(-100.0, None);
+        vp.update(10.0);
+        let (lo, hi) = vp.freq_range();
+        assert!((lo - 20.0).abs() < 0.1 && (hi - 20480.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_time_zoom_animates_and_clamps() {
+        let mut vp = Viewport::new(ViewConfig::default(), &audio());
+        vp.zoom_time(1.0);
+        vp.update(0.01);
+        assert!(vp.time_zoom() > 1.0 && vp.time_zoom() < ViewConfig::default().zoom_step);
+        vp.zoom_time(1000.0);
+        vp.update(10.0);
+        assert!((vp.time_zoom() - ViewConfig::default().max_time_zoom).abs() < 1e-3);
+    }
+}
@@ -4,6 +4,14 @@ use nannou::prelude::*;
 use std::time::Instant;
 use crate::config::{AudioConfig, VisualConfig};
 
+/// Cambio de frecuencia o amplitud de una nota abierta, `time` segundos después de su inicio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContourPoint {
+    pub time: f32,
+    pub frequency: f32,
+    pub amplitude: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct VisualNote {
     pub frequency: f32,
@@ -16,6 +24,10 @@ pub struct VisualNote {
     pub size: f32,
     pub color: Rgba,
     pub instrument: String,
+    /// Contorno de frecuencia/amplitud de las notas abiertas (`/note_set`); vacío en notas de duración fija.
+    pub contour: Vec<ContourPoint>,
+    /// La nota sigue sonando hasta recibir `/note_off`.
+    pub held: bool,
 }
 
 impl VisualNote {
@@ -38,7 +50,69 @@ impl VisualNote {
             size: 0.0,
             color: rgba(1.0, 1.0, 1.0, 1.0),
             instrument,
+            contour: Vec::new(),
+            held: false,
+        }
+    }
+
+    /// Nota abierta (`/note_on id freq amp`): crece hasta que `release` fija su final.
+    pub fn open(frequency: f32, amplitude: f32, instrument: String, start_time: Instant) -> Self {
+        let mut note = Self::new(frequency, amplitude, 0.0, instrument, start_time);
+        note.held = true;
+        note.contour.push(ContourPoint { time: 0.0, frequency, amplitude });
+        note
+    }
+
+    /// Aplica `/note_set id param value`. Devuelve `false` si el parámetro no se reconoce.
+    pub fn set_param(&mut self, param: &str, value: f32, at: Instant) -> bool {
+        match param {
+            "freq" | "frequency" => self.frequency = value,
+            "midinote" | "note" => self.frequency = 440.0 * ((value - 69.0) / 12.0).exp2(),
+            "amp" | "amplitude" => self.amplitude = value,
+            "db" => self.amplitude = 10f32.powf(value / 20.0),
+            _ => return false,
+        }
+        let time = at.saturating_duration_since(self.start_time).as_secs_f32();
+        let point = ContourPoint { time, frequency: self.frequency, amplitude: self.amplitude };
+        // Varios cambios en el mismo instante se funden en un único punto
+        match self.contour.last_mut() {
+            Some(last) if (last.time - time).abs() < 1e-4 => *last = point,
+            _ => self.contour.push(point),
+        }
+        true
+    }
+
+    /// Cierra la nota exactamente en `at` (`/note_off id`).
+    pub fn release(&mut self, at: Instant) {
+        let at = at.max(self.start_time);
+        self.duration = at.duration_since(self.start_time).as_secs_f32();
+        self.end_time = at;
+        self.held = false;
+    }
+
+    /// Duración actual: la transcurrida hasta `now` mientras se mantiene, la fijada después.
+    pub fn current_duration(&self, now: Instant) -> f32 {
+        if self.held {
+            now.saturating_duration_since(self.start_time).as_secs_f32()
+        } else {
+            self.duration
+        }
+    }
+
+    /// Tramos `(inicio, fin, frecuencia, amplitud)` en segundos desde el inicio de la nota.
+    pub fn segments(&self, now: Instant) -> Vec<(f32, f32, f32, f32)> {
+        let end = self.current_duration(now);
+        if self.contour.is_empty() {
+            return vec![(0.0, end, self.frequency, self.amplitude)];
         }
+        self.contour
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let next = self.contour.get(i + 1).map_or(end, |n| n.time);
+                (p.time, next.min(end), p.frequency, p.amplitude)
+            })
+            .collect()
     }
 
     pub fn update(
@@ -61,9 +135,9 @@ impl VisualNote {
         );
 
         // X: desplazamiento horizontal basado en el tiempo
-        let timeline_start = Instant::now()
+        let timeline_start = current_time
             .checked_sub(std::time::Duration::from_secs_f32(visual_config.timeline_duration))
-            .unwrap_or(Instant::now());
+            .unwrap_or(current_time);
 
         let x_start_time = self.start_time.duration_since(timeline_start).as_secs_f32();
 
@@ -96,3 +170,27 @@ impl VisualNote {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_open_note_grows_and_ends_at_release() {
+        let start = Instant::now();
+        let mut note = VisualNote::open(440.0, 0.5, "default".to_string(), start);
+        assert!((note.current_duration(start + Duration::from_secs(2)) - 2.0).abs() < 1e-3);
+
+        assert!(note.set_param("freq", 660.0, start + Duration::from_secs(1)));
+        assert!(!note.set_param("pan", 0.0, start + Duration::from_secs(1)));
+        note.release(start + Duration::from_millis(1500));
+
+        let segments = note.segments(start + Duration::from_secs(10));
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].2, 440.0);
+        assert!((segments[1].1 - 1.5).abs() < 1e-3);
+        assert_eq!(segments[1].2, 660.0);
+        assert!(!note.held);
+    }
+}